@@ -32,11 +32,248 @@
 //!    cargo run --release
 //!    ```
 //!
+//! 3. Pass options after `--` (see `src/cli.rs` or `--help`):
+//!    ```text
+//!    cargo run --release -- --port /dev/ttyUSB1 --baud 57600 --bind 0.0.0.0:4000
+//!    cargo run --release -- --list-ports
+//!    ```
+//!
+//! ## Command-Line Options
+//!
+//! | Option | Default | Meaning |
+//! |---|---|---|
+//! | `--source <spec>` | | Telemetry input, repeatable (see *Telemetry Sources*) |
+//! | `-p, --port <dev>` | `/dev/ttyACM0` | Serial device of the LoRa receiver |
+//! | `-b, --baud <n>` | `115200` | Serial baud rate |
+//! | `--bind <addr>` | `127.0.0.1:4000` | TCP control server address |
+//! | `--json-bind <addr>` | *(none)* | Extra control server speaking JSON lines |
+//! | `--frame-ms <ms>` | `100` | UI frame duration (10..=5000) |
+//! | `-t, --title <text>` | `Live CanSat Telemetry` | Title bar text |
+//! | `-c, --channels <file>` | built-in `channels.toml` | Channel schema |
+//! | `--station <lat,lon>` | | Ground-station position for the track map |
+//! | `--qnh <hPa>` | | Recompute the altitude from pressure with this baseline |
+//! | `--record-dir <dir>` | `sessions` | Where session recordings are written |
+//! | `--no-record` | | Disable session recording |
+//! | `--reject-bad-packets` | | Drop packets with any unparsable field |
+//! | `--wait` | | Start even if the port cannot be opened yet |
+//! | `-r, --replay <file>` | | Replay an onboard log file instead of the serial port |
+//! | `--speed <s>` | `1` | Initial replay speed: `0.5`, `1`, `4` or `max` |
+//! | `--simulate` | | Simulated flight instead of the serial port |
+//! | `--sim-rate <hz>` | `2` | Simulated packet rate |
+//! | `--sim-loss <pct>` | `2` | Simulated base packet loss |
+//! | `--list-ports` | | Print available serial ports and exit (on its own only) |
+//!
+//! The serial port is opened before the terminal UI starts, so a wrong device
+//! or baud rate is reported as a plain error message instead of an empty screen
+//! (unless `--wait` is given).
+//!
+//! # Telemetry Sources
+//!
+//! Every input is a `telemetry::source::TelemetrySource` (open, next frame,
+//! status, close) read by its own thread; the lines go through the pipeline's
+//! `Decoder` (`telemetry::parse`), which turns them into samples named after
+//! the schema channels. `--source` picks them, and can be repeated to read
+//! several at once (each feeds the channels its lines carry):
+//!
+//! | Spec | Input |
+//! |---|---|
+//! | `serial:<dev>[@<baud>]` | LoRa receiver on a serial port (`--baud` by default) |
+//! | `tcp:<host>:<port>` | Receiver lines forwarded by a TCP server (reconnects; IPv6 as `[::1]:5000`) |
+//! | `udp:[<host>:]<port>` | Receiver lines sent as UDP datagrams to this address |
+//! | `file:<path>` | Onboard log replay, as `--replay` (only on its own) |
+//! | `stdin` | Lines piped into the program; ends with the input |
+//! | `sim` | Simulated flight, as `--simulate` |
+//!
+//! Append `=<channel>,<channel>...` to a spec to feed only those channels
+//! from it, e.g. a second receiver for the GPS fix only:
+//! `--source serial:/dev/ttyACM0 --source 'tcp:pi.local:5000=GPS LAT,GPS LON'`.
+//! When several sources may feed the message counter, their numbers interleave
+//! and the link-quality figures are meaningless, so a warning is logged. A
+//! replay has its own clock and rewinds, so a file source cannot be combined
+//! with any other.
+//!
+//! Without `--source`, `--replay`, `--simulate` or `--port` select one input.
+//! The first connected source with a return path (serial port, TCP
+//! connection, simulator) carries the uplink commands.
+//!
+//! # Network Inputs
+//!
+//! When the receiver sits on a machine out on the field, forward its serial
+//! lines over the network (`telemetry::network`). Over TCP, with e.g.
+//! `socat TCP-LISTEN:5000,reuseaddr,fork FILE:/dev/ttyACM0,b115200,raw` on the
+//! Pi and `--source tcp:pi.local:5000` here: the connection is retried with
+//! backoff while the Pi is unreachable, reopened when it drops or stays silent
+//! for 30 s, and carries uplink commands back. Over UDP, with
+//! `--source udp:5000` here and the Pi sending each line (or several per
+//! datagram) to this machine's port 5000: no connection to lose, but no uplink
+//! either.
+//!
+//! # Serial Link
+//!
+//! Sources reconnect on their own (`telemetry::source::start_source`): when
+//! the receiver is unplugged or resets, the link goes `LOST`, the port is
+//! reopened with exponential backoff (250 ms up to 5 s), and a USB device that
+//! comes back under another name with the same VID/PID/serial is picked up
+//! automatically (`telemetry::serial`). The title bar shows `CONNECTING` /
+//! `CONNECTED` / `LOST` (`ENDED` for stdin) for each source, the reconnect
+//! count and the last error; worker-thread messages go to the *Log* panel at
+//! the bottom instead of stdout/stderr.
+//!
 //! ### Environment Notes
 //! - Terminal UI uses the `ratatui` and `crossterm` crates.
 //! - Remote control server binds to `127.0.0.1:4000` by default.  
-//!   Change this by editing the string passed to `remote_server(...)` inside
-//!   the thread spawn.
+//!   Change this with `--bind`.
+//!
+//! # Time Axis
+//!
+//! Every sample is stamped when it is received (or, in replay, with the
+//! logger's own timestamp), so graph X axes show mission time `T+mm:ss` since
+//! the session started / the first replayed record, and lost packets appear as
+//! gaps instead of being squeezed out. A channel's `window` in the schema is
+//! either a sample count (`50`) or a duration (`"60s"`, `"2m"`); the window in
+//! use is shown in each graph's title.
+//!
+//! # Session Recording
+//!
+//! Unless `--no-record` is given (or a file is being replayed), every received
+//! line is appended to `<record-dir>/session-YYYYmmdd-HHMMSS.csv` with its
+//! wall-clock receive time, the raw text, and one column per channel
+//! (`telemetry::record`). Every row is flushed and synced to disk as it is
+//! written. The title bar shows the file path, size and row count.
+//!
+//! # Link Quality
+//!
+//! The channel with `role = "counter"` in the schema (the CanSat message
+//! number) feeds `telemetry::quality`: skipped numbers count as missing,
+//! repeats as duplicates, late arrivals as out-of-order, and a jump back to a
+//! low number as a counter reset. The *Link quality* panel shows totals, the
+//! loss and packet rate over the last 30 s of mission time (replays report the
+//! flight's own rates at any speed), and for every `role = "rssi"` channel its
+//! mean and the loss rate per 10 dBm bucket.
+//!
+//! # GPS
+//!
+//! Latitude, longitude, GPS altitude, satellites in use, HDOP and fix time are
+//! ordinary schema channels. They are decoded from optional extra `Received:`
+//! fields when the CanSat appends them, and from raw NMEA `$..GGA` / `$..RMC`
+//! sentences forwarded by the receiver (`telemetry::nmea`, checksum-verified).
+//! The default schema keeps them without graph regions (`graph = false`); they
+//! are recorded and reachable over TCP like any other channel.
+//!
+//! # Derived Channels
+//!
+//! A schema channel may be computed from channels declared before it instead
+//! of decoded (`derive = { kind = ... }`, see `telemetry::derive`): the
+//! time derivative of a channel (`derivative`, least-squares slope over a
+//! span, optionally scaled), barometric altitude from pressure
+//! (`isa_altitude`) and the dew point from temperature and humidity
+//! (`dew_point`). The default schema adds vertical speed, ISA altitude and dew
+//! point graphs, plus a descent rate without a graph. Derived values are
+//! recorded, drawn, tracked and served over TCP exactly like decoded ones.
+//!
+//! # Altitude Baseline
+//!
+//! The CanSat computes its altitude against a hard-coded sea-level pressure.
+//! When the schema has channels with `role = "pressure"` and
+//! `role = "altitude"`, the monitor can recompute the altitude itself
+//! (`telemetry::altimeter`) from a baseline (QNH) given with `--qnh`, nudged
+//! with **b** / **B**, set over TCP (`set qnh`), or measured by "zero on pad"
+//! (**z** or `zero`): the pressure is averaged over 10 s and the pad then
+//! reads 0 m. Whenever the baseline changes, the stored altitude history is
+//! recomputed from the pressure history and the flight state is shifted to
+//! match. The altitude graph's Info panel shows the baseline in effect.
+//!
+//! # Stale Data
+//!
+//! Every graph remembers when it last received a sample. After the channel's
+//! `stale_after` (schema, default 5 s) without data, its graph is dimmed and
+//! badged `NO DATA <n>s`, so a frozen "Last" value is not mistaken for a live
+//! one. The title bar shows the time since the last packet on any channel (red
+//! once all of them are stale), and `stale` reports the state over TCP.
+//!
+//! # Alarms
+//!
+//! Channels may declare threshold alarms (`alarms = [...]` in the schema,
+//! `telemetry::alarm`): above, below, outside a band, or a rate of change,
+//! each with hysteresis and a minimum duration. Thresholds are drawn as red
+//! lines on the graph (bright while in alarm). A raised alarm turns the
+//! channel's Info panel border red, flashing until acknowledged, rings the
+//! terminal bell, is written to the event log, and is listed in the *Alarms*
+//! panel with the value that raised it and when it cleared. **x**
+//! acknowledges all alarms.
+//!
+//! # Uplink Commands
+//!
+//! Commands for the CanSat (trigger the buzzer, change the TX interval,
+//! reset the counter, ...) are typed in the uplink console (**u**, **Enter**
+//! to send) or sent with the TCP `send` command (`telemetry::uplink`). They
+//! are written to the receiver as `$CMD,<id>,<text>*<checksum>` frames, one
+//! at a time, and the receiver relays each one with its next ACK. The
+//! *Uplink* panel echoes every frame with its state: `QUEUED`, `SENT`,
+//! `CARRIED` (an `ACK ...` line followed), `CONFIRMED #<packet>` (the first
+//! packet received after that ACK), or `NO ACK` after 10 s. Commands typed
+//! while the receiver is disconnected wait in the queue (up to 100 commands
+//! not yet sent or answered, further ones are refused); replays have no
+//! uplink.
+//!
+//! # Raw Terminal
+//!
+//! **t** swaps the graphs for the raw terminal: every line received from the
+//! source (`telemetry::rawlog`, last 5000 kept), with its receive time,
+//! white when every declared field decoded, yellow when only partly (short
+//! line, unparsable field, NMEA sentence without a fix), gray when it is not
+//! telemetry at all (unknown prefix, banners, ACKs). The title counts each
+//! kind since the start. **/** filters by a case-insensitive substring;
+//! scrolling back holds the view while new lines keep arriving.
+//!
+//! # Parser Diagnostics
+//!
+//! The *Parser* panel beside the raw terminal (`telemetry::diag`) counts
+//! malformed telemetry lines: too short to decode, or with fields that do not
+//! parse as finite numbers (counted per channel). It shows the share and rate
+//! of bad lines over the last 30 s of mission time and the last offending line
+//! with what was wrong.
+//! By default the fields of a malformed packet that did parse are still used;
+//! `--reject-bad-packets` (or TCP `parse reject on`) drops the whole packet so
+//! the graphs stay in step with each other.
+//!
+//! # Flight Phases
+//!
+//! The channel with `role = "altitude"` drives a state machine
+//! (`telemetry::flight`): the altitude is low-pass filtered, a vertical speed
+//! is derived, and the flight is classified as `PAD → ASCENT → APOGEE →
+//! DESCENT → LANDED`. The *Flight* panel shows the current phase, altitude and
+//! vertical speed, the maximum altitude above the pad and the mean descent
+//! rate, and the timeline of phase events. Every event is also drawn as a
+//! colored vertical line on all graphs.
+//!
+//! # Track Map
+//!
+//! When the schema has channels with `role = "latitude"` and
+//! `role = "longitude"`, a *Track* panel is added as the rightmost column. It
+//! plots the ground track in metres around the launch point (the first fix),
+//! fading the trail with age, and marks the launch point `▲`, the ground
+//! station `⌂` and the current position `✕`. Below the map: the current
+//! coordinates, time since the last fix, and distance/bearing from the ground
+//! station (`--station LAT,LON`; the simulator supplies its own), or from the
+//! launch point when the station is unknown.
+//!
+//! # Replay
+//!
+//! `--replay logs.txt` plays a file written by the onboard `Logger`
+//! (`[HH:MM:SS.mmm]  [INFO]  <msg#> <rssi> <temp> <pres> <hum> <alt>`) through
+//! the same pipeline and UI as live data, paced by the original timestamps
+//! (`telemetry::replay`). Non-telemetry log entries show up in the *Log* panel.
+//! Seeking backward clears the graphs and re-plays the file up to the target.
+//!
+//! # Simulation
+//!
+//! `--simulate` runs a synthetic CanSat flight (`telemetry::sim`): pad,
+//! ascent, apogee, parachute descent, landing, repeated. Temperature, pressure
+//! and humidity follow the standard atmosphere, RSSI degrades with distance, and
+//! packets are dropped per `--sim-loss`. The generated lines go through the same
+//! decoder as serial data, so the dashboard can be developed without hardware.
+//! Uplink commands are acknowledged as on hardware but have no effect.
 //!
 //! # Keyboard Controls (Interactive)
 //!
@@ -47,16 +284,54 @@
 //! - **l** — Lock/unlock the current graph’s Y-axis bounds.
 //! - **q** — Quit and restore terminal state.
 //!
+//! - **f** — Freeze/unfreeze all graphs. Data keeps arriving in the background;
+//!   the Info panels show `FROZEN` with the visible time range, or `LIVE`.
+//! - **←** / **→** — While frozen: pan back/forward through the stored history
+//!   (a quarter of the view per press).
+//! - **+** / **-** — While frozen: zoom the time range in/out.
+//! - **End** — Return to the live view.
+//! - **c** — Inspection cursor on/off (freezes the view). **←** / **→** then step
+//!   the cursor through the focused graph's samples; every graph's stats row
+//!   shows its own value at the cursor instant, and the focused graph marks the
+//!   nearest sample. A left click or drag inside a plot focuses that graph and
+//!   moves the cursor there. **Esc** removes the cursor.
+//!
+//! - **x** — Acknowledge all alarms.
+//! - **z** — Zero the altitude on the pad (average the pressure for 10 s).
+//! - **b** / **B** — Lower/raise the altitude baseline by 0.1 hPa.
+//! - **u** — Open the uplink console: type a command, **Enter** sends it,
+//!   **Esc** cancels, **↑** recalls the last command. Other keys are typed
+//!   into the console while it is open.
+//! - **t** — Show the raw terminal instead of the graphs (again to go back).
+//!   There, **↑** / **↓** and **PgUp** / **PgDn** scroll, **End** follows the
+//!   newest line, and **/** types a filter (**Enter** keeps it, **Esc**
+//!   clears it).
+//!
+//! Replay mode only (`--replay`):
+//! - **Space** — Pause/resume.
+//! - **[** / **]** — Slower/faster (`0.5x`, `1x`, `4x`, `max`).
+//! - **,** / **.** — Seek 10 s backward/forward.
+//!
 //! # Remote TCP Protocol (ASCII, Line-Based)
 //!
 //! A small TCP server runs in a dedicated thread.  
 //! Each received line is parsed as a whitespace-separated ASCII command.  
 //! The server replies with one line per command (`OK` or `ERR <msg>`).
 //!
-//! **Default bind address:** `127.0.0.1:4000`
+//! **Default bind address:** `127.0.0.1:4000` (see `--bind`)
 //!
 //! ## Supported Commands
 //!
+//! - `hello [text|json]`  
+//!   Switch this connection's protocol (see *JSON Mode*); the reply
+//!   `OK proto=<text|json>` still uses the old one. Without an argument,
+//!   reports the current protocol.
+//!
+//! - `list`  
+//!   Every graph as `<idx> name="..." unit="..." color=<color>
+//!   bounds=<lo>..<hi>|none autoscale=<bool> smoothing=<val>
+//!   locked=<lo>..<hi>|none`, entries separated by ` | `.
+//!
 //! - `toggle autoscale <idx>`  
 //!   Toggle autoscale for graph `<idx>`.
 //!
@@ -70,6 +345,87 @@
 //! - `unlock <idx>`  
 //!   Clear locked bounds and resume autoscale if enabled.
 //!
+//! - `set bounds <idx> <min> <max>`  
+//!   Lock the Y axis to explicit bounds (`min < max`).
+//!
+//! - `set window <idx> <n|duration>`  
+//!   Change the live window to `n` samples or a duration (`60s`, `2m`,
+//!   `500ms`), refilled from the stored history.
+//!
+//! - `set color <idx> <color>`  
+//!   Change the series color (a ratatui color name or `#rrggbb`).
+//!
+//! - `focus <idx>`  
+//!   Focus a graph shown on screen, like **Tab**.
+//!
+//! - `clear <idx|all>`  
+//!   Drop the stored history of one graph, or of every graph.
+//!
+//! - `pause` / `resume`  
+//!   Freeze every graph (like **f**; data keeps arriving) and pause a replay,
+//!   or return to the live view and resume the replay.
+//!
+//! - `send <text>`  
+//!   Queue an uplink command; replies `OK id=<id> state=<state>
+//!   frame=<frame>`.
+//!
+//! - `uplink`  
+//!   Uplink state and the last 10 commands, newest first:
+//!   `OK connected=<bool> queued=<n> | <id> state=<queued|sent|carried|confirmed|no_ack|failed>
+//!   [packet=<n>] frame=<frame> | ...`.
+//!
+//! - `shutdown`  
+//!   Quit the application, like **q**.
+//!
+//! - `stats`  
+//!   Link-quality statistics as `OK key=value ...`: `received`, `expected`,
+//!   `missing`, `duplicates`, `out_of_order`, `resets`, `loss_pct`,
+//!   `rolling_loss_pct`, `rate_hz` and `rssi_mean[<idx>]` per RSSI channel.
+//!
+//! - `parse`  
+//!   Parser diagnostics as `OK key=value ...`: `lines`, `malformed`, `short`,
+//!   `rejected`, `bad_pct`, `bad_per_min`, `reject`, `errors[<idx>]` per
+//!   channel with parse errors, then `| last: <reason>: <line>`.
+//!
+//! - `parse reject <on|off>`  
+//!   Drop (or keep using) packets with unparsable fields.
+//!
+//! - `stale [idx]`  
+//!   Without `<idx>`: `OK <idx>=<seconds> ...` for every stale channel (just
+//!   `OK` when all are fresh). With `<idx>`: `OK idx=<idx> stale=<bool>
+//!   age=<seconds|none> timeout=<seconds>`.
+//!
+//! - `set qnh <hPa>`  
+//!   Recompute the altitude (and its history) from pressure with this
+//!   sea-level pressure.
+//!
+//! - `zero [seconds]`  
+//!   Zero the altitude on the pad by averaging the pressure (default 10 s).
+//!   Refused once the flight is under way.
+//!
+//! - `baseline`  
+//!   Altitude baseline as `OK baseline=<transmitted|manual|pad> qnh=<hPa>`,
+//!   plus `zeroing=<elapsed>/<seconds>` while averaging.
+//!
+//! - `subscribe <idx|name|all> [text|json]`  
+//!   Push every new sample of a channel (by index or case-insensitive name,
+//!   or all of them) until `unsubscribe`. Replies `OK subscribed=<idx,...|all>
+//!   format=<text|json>`; the format applies to the whole subscription. Text
+//!   lines are `DATA <idx> <t> <value>`, JSON lines are
+//!   `{"type":"sample","idx":..,"name":..,"t":..,"value":..}`. Samples are
+//!   queued per client; a client that does not keep up loses samples rather
+//!   than slowing the ingest down.
+//!
+//! - `unsubscribe [idx|name|all]`  
+//!   Stop pushing one channel (default: all). Replies `OK dropped=<n>` with
+//!   the number of samples lost so far because the client read too slowly.
+//!
+//! - `get <idx|name> [n]`  
+//!   Current statistics and the last `n` history points (default 10, at most
+//!   1000): `OK idx=<idx> n=<stored points> min=<v> max=<v> last=<v>
+//!   points=<t>:<v>,...`. `min`/`max`/`last` cover the visible window and are
+//!   omitted before the first sample.
+//!
 //! - `quit`  
 //!   Replies `OK bye` and closes the connection.
 //!
@@ -103,8 +459,26 @@
 //! OK
 //! ```
 //!
+//! ## JSON Mode
+//!
+//! After `hello json`, or from the start on the `--json-bind` port, requests
+//! and replies are JSON objects, one per line. `args` is optional and is
+//! appended to `cmd`; `id` can be any JSON value and is echoed back:
+//! ```text
+//! {"id":1,"cmd":"get","args":["TEMP",2]}
+//! {"type":"reply","id":1,"ok":true,"result":{"idx":2,"name":"TEMP","n":130,"min":19.2,"max":19.5,"last":19.4,"points":[[32.5,19.37],[32.75,19.41]]}}
+//! {"id":2,"cmd":"set qnh 2000"}
+//! {"type":"reply","id":2,"ok":false,"error":{"code":"rejected","message":"QNH 2000 hPa is outside 850..=1100 hPa"}}
+//! ```
+//! Error codes: `bad_request` (not a JSON request), `unknown_command`,
+//! `bad_argument`, `no_graph`, `rejected` (refused in the current state).
+//! Results carry the same fields as the text replies; `list` returns
+//! `{"graphs":[{"idx","name","unit","color","bounds","autoscale","smoothing","locked"}]}`.
+//! Subscriptions default to JSON samples (`"type":"sample"`) in this mode.
+//!
 //! ### Notes
-//! - `<idx>` is the index in the `Vec<SharedGraph>` created in `run()`.
+//! - `<idx>` is the channel's position in the schema (`channels.toml`), or
+//!   its name, case-insensitive (`set smoothing vert speed 0.5`).
 //! - Multiple clients can connect concurrently; each connection gets a dedicated thread.
 //! - Errors return helpful `ERR` messages.
 //!
@@ -132,8 +506,21 @@
 //! # Extending the Application
 //!
 //! - **Adding graphs:**  
-//!   Modify the graph configuration values (`cfg1`, `cfg2`, etc.) before creating
-//!   `SharedGraph` instances. Index order determines the remote `<idx>` values.
+//!   Add a `[[channel]]` entry to `channels.toml` (or to your own file passed
+//!   with `--channels`). Each entry declares the name, unit, color, window,
+//!   history, y-range, autoscale/smoothing defaults and which token of which
+//!   line carries the value. Graph construction, decoding (`telemetry::parse`)
+//!   and the column layout are all derived from it; no code change is needed.
+//!   Index order determines the remote `<idx>` values.
+//!
+//! - **Adding computed channels:**  
+//!   Give a `[[channel]]` a `derive` rule instead of `line`/`field`. New
+//!   kinds go into `schema::DeriveSpec` and `telemetry::derive`.
+//!
+//! - **Adding inputs:**  
+//!   Implement `telemetry::source::TelemetrySource` (cut the input into
+//!   `Frame`s, report what it is connected to) and add a `SourceSpec` form;
+//!   `start_source` provides reconnection, link status and uplink handling.
 //!
 //! # Example Workflow
 //!
@@ -157,439 +544,433 @@
 //! maintainable and easy to extend.
 
 use std::error::Error;
-use std::io::{BufRead, BufReader};
-use std::sync::{Arc, RwLock};
+use std::io::Write;
+use std::sync::{Arc, RwLock, mpsc};
 use std::thread;
 use std::time::Duration;
 
-use crate::graph::GraphConfig;
-use crate::graph::shared::{GraphShared, SharedGraph};
-use crate::net::remote::remote_server;
-use crate::panels::{GraphPanel, HistoryPanel, InfoPanel, ParagraphPanel, TitlePanel};
+use crate::cli::Cli;
+use crate::events::{Level, log, shared_log};
+use crate::graph::shared::SharedGraph;
+use crate::net::protocol::Proto;
+use crate::net::remote::{RemoteContext, UiCommand, remote_server};
+use crate::panels::flight::phase_color;
+use crate::panels::{
+    AlarmPanel, FlightPanel, GraphPanel, HistoryPanel, InfoPanel, LinkQualityPanel, LogPanel,
+    ParserPanel, TerminalPanel, Threshold, TimeMarker, TitlePanel, TrackPanel, UplinkPanel,
+};
+use crate::schema::{ChannelRole, Schema};
+use crate::telemetry::Pipeline;
+use crate::telemetry::alarm::{Alarms, SharedAlarms};
+use crate::telemetry::altimeter::{Altimeter, SharedAltimeter, ZERO_SECONDS};
+use crate::telemetry::diag::{ParseDiagnostics, SharedDiagnostics};
+use crate::telemetry::feed::{Feed, SharedFeed};
+use crate::telemetry::flight::{FlightTracker, SharedFlight};
+use crate::telemetry::link::{LinkStatus, SharedLink};
+use crate::telemetry::network::{TcpClientSource, UdpSource};
+use crate::telemetry::quality::{LinkQuality, SharedQuality};
+use crate::telemetry::rawlog::{RawLog, SharedRawLog};
+use crate::telemetry::record::{Recorder, SharedRecorder};
+use crate::telemetry::replay::{ReplaySource, SharedReplay};
+use crate::telemetry::serial::{SerialSource, open_serial};
+use crate::telemetry::sim::{SimConfig, SimSource, station_position};
+use crate::telemetry::source::{SourceSpec, TelemetrySource, start_source};
+use crate::telemetry::stdin::StdinSource;
+use crate::telemetry::track::{SharedTrack, Track};
+use crate::telemetry::uplink::{MAX_COMMAND, SharedUplink, Uplink};
 use crate::ui::{Node, group, leaf};
 
+use ratatui::layout::{Constraint, Direction};
 use ratatui::style::Color;
 
-/// Spawn a thread that reads telemetry from a serial port (e.g., /dev/ttyACM0),
-/// parses each line for message
-/// and pushes them into the corresponding shared graphs.
-fn start_serial_reader(
-    port_name: &str,
-    g_msg: SharedGraph,
-    g_rssi: SharedGraph,
-    g_temp: SharedGraph,
-    g_pres: SharedGraph,
-    g_hum: SharedGraph,
-    g_alt: SharedGraph,
-    g_rssi_packet: SharedGraph,
-) {
-    let port_name = port_name.to_string();
-    thread::spawn(move || {
-        let baud_rate = 115_200;
-        println!("Opening serial port {} @ {} baud", port_name, baud_rate);
-        let port = match serialport::new(&port_name, baud_rate)
-            .timeout(Duration::from_secs(100000))
-            .open()
-        {
-            Ok(p) => p,
-            Err(e) => {
-                eprintln!("Failed to open serial port {}: {:?}", port_name, e);
-                return;
-            }
-        };
-        let reader = BufReader::new(port);
-        // println!("Serial reader started on {}", port_name);
-        for line_res in reader.lines() {
-            match line_res {
-                Ok(line) => {
-                    let trimmed = line.trim();
-                    if trimmed.is_empty() {
-                        continue;
-                    }
-                    // Parse the line for all telemetry data
-                    let (
-                        maybe_msgnum,
-                        maybe_rssi,
-                        maybe_temp,
-                        maybe_pres,
-                        maybe_hum,
-                        maybe_alt,
-                        maybe_rssi_packet,
-                    ) = parse_telemetry_line(trimmed);
-
-                    // Update message graph
-                    if let Some(msgnum) = maybe_msgnum {
-                        if let Ok(mut gm) = g_msg.write() {
-                            let x = gm.data.history.back().map(|(x, _)| x + 1.0).unwrap_or(0.0);
-                            gm.data.push_point(x, msgnum as f64);
-                        }
-                    }
-                    // Update RSSI graph
-                    if let Some(rssi) = maybe_rssi {
-                        if let Ok(mut gr) = g_rssi.write() {
-                            let x = gr.data.history.back().map(|(x, _)| x + 1.0).unwrap_or(0.0);
-                            gr.data.push_point(x, rssi);
-                        }
-                    }
-                    // Update temperature graph
-                    if let Some(temp) = maybe_temp {
-                        if let Ok(mut gt) = g_temp.write() {
-                            let x = gt.data.history.back().map(|(x, _)| x + 1.0).unwrap_or(0.0);
-                            gt.data.push_point(x, temp);
-                        }
-                    }
-                    // Update pressure graph
-                    if let Some(pres) = maybe_pres {
-                        if let Ok(mut gp) = g_pres.write() {
-                            let x = gp.data.history.back().map(|(x, _)| x + 1.0).unwrap_or(0.0);
-                            gp.data.push_point(x, pres);
-                        }
-                    }
-                    // Update humidity graph
-                    if let Some(hum) = maybe_hum {
-                        if let Ok(mut gh) = g_hum.write() {
-                            let x = gh.data.history.back().map(|(x, _)| x + 1.0).unwrap_or(0.0);
-                            gh.data.push_point(x, hum);
-                        }
-                    }
-                    // Update altitude graph
-                    if let Some(alt) = maybe_alt {
-                        if let Ok(mut ga) = g_alt.write() {
-                            let x = ga.data.history.back().map(|(x, _)| x + 1.0).unwrap_or(0.0);
-                            ga.data.push_point(x, alt);
-                        }
-                    }
-                    // Update RSSI_PACKET graph
-                    if let Some(rssi_packet) = maybe_rssi_packet {
-                        if let Ok(mut gr) = g_rssi_packet.write() {
-                            let x = gr.data.history.back().map(|(x, _)| x + 1.0).unwrap_or(0.0);
-                            gr.data.push_point(x, rssi_packet);
-                        }
-                    }
-                    thread::sleep(Duration::from_millis(1));
-                }
-                Err(e) => {
-                    eprintln!("Error reading serial data: {:?}", e);
-                    break;
-                }
-            }
+/// Seconds skipped by one replay seek key press.
+const REPLAY_SEEK_STEP: f64 = 10.0;
+
+/// Fraction of the frozen view width moved by one pan key press.
+const PAN_STEP: f64 = 0.25;
+
+/// Frozen view width multiplier for one zoom-out key press.
+const ZOOM_STEP: f64 = 2.0;
+
+/// Baseline change of one `b`/`B` key press, hPa.
+const QNH_STEP: f64 = 0.1;
+
+/// Raw terminal lines scrolled by one PgUp/PgDn key press.
+const TERMINAL_PAGE: isize = 20;
+
+/// Put the inspection cursor at `t` on every graph (or remove it), scrolling
+/// frozen views so the cursor stays visible.
+fn set_cursor(graphs: &[SharedGraph], t: Option<f64>) {
+    for g in graphs {
+        let mut g = g.write().unwrap();
+        g.cursor = t;
+        if let Some(t) = t {
+            g.freeze();
+            g.reveal(t);
         }
-        println!("Serial reader exiting");
-    });
+    }
 }
 
-/// Parse a telemetry line and extract all telemetry data.
-///
-/// Example accepted format:
-/// ----------------------------------------
-/// M 136 R -91.0 T 18.45 P 995.85 H 58.93 A 300.045200
-/// RSSI_PACKET: -89.5 dBm
-/// ACK sent back automatically.
-/// ----------------------------------------
-///
-/// Returns (Option<msgnum>, Option<rssi>, Option<temp>, Option<pres>, Option<hum>, Option<alt>, Option<rssi_packet>)
-fn parse_telemetry_line(
-    line: &str,
-) -> (
-    Option<u64>,
-    Option<f64>,
-    Option<f64>,
-    Option<f64>,
-    Option<f64>,
-    Option<f64>,
-    Option<f64>,
-) {
-    let mut msgnum = None;
-    let mut rssi = None;
-    let mut temp = None;
-    let mut pres = None;
-    let mut hum = None;
-    let mut alt = None;
-    let mut rssi_packet = None;
-
-    // Split the line into parts
-    let lines: Vec<&str> = line.lines().collect();
-
-    // Iterate through each line
-    for l in lines {
-        let trimmed = l.trim();
-
-        // Parse "Received:  136  -91.0  18.45  995.85  58.93  300.045200"
-        //         0          1    2     3      4       5       6
-        if trimmed.starts_with("Received: ") {
-            let parts: Vec<&str> = trimmed.split_whitespace().collect();
-            if parts.len() >= 7 {
-                // Extract message number (e.g., "136")
-                if let Ok(num) = parts[1].parse::<u64>() {
-                    msgnum = Some(num);
-                }
-                // Extract RSSI (e.g., "-91.0")
-                if let Ok(val) = parts[2].parse::<f64>() {
-                    rssi = Some(val);
-                }
-                // Extract temperature (e.g., "18.45")
-                if let Ok(val) = parts[3].parse::<f64>() {
-                    temp = Some(val);
-                }
-                // Extract pressure (e.g., "995.85")
-                if let Ok(val) = parts[4].parse::<f64>() {
-                    pres = Some(val);
-                }
-                // Extract humidity (e.g., "58.93")
-                if let Ok(val) = parts[5].parse::<f64>() {
-                    hum = Some(val);
-                }
-                // Extract altitude (e.g., "300.045200")
-                if let Ok(val) = parts[6].parse::<f64>() {
-                    alt = Some(val);
-                }
-            }
+/// Build the graph + history + info region for channel `idx`; `altimeter` is
+/// given for the altitude channel.
+fn channel_region(
+    graph: &SharedGraph,
+    idx: usize,
+    highlighted: bool,
+    markers: &[TimeMarker],
+    altimeter: Option<&SharedAltimeter>,
+    alarms: &SharedAlarms,
+) -> Node {
+    let al = alarms.read().unwrap();
+    let mut graph_panel = GraphPanel::new(graph.clone());
+    graph_panel.focused = highlighted;
+    graph_panel.markers = markers.to_vec();
+    graph_panel.thresholds = al
+        .thresholds(idx)
+        .into_iter()
+        .map(|(y, active)| Threshold {
+            y,
+            color: if active { Color::LightRed } else { Color::Red },
+        })
+        .collect();
+    let gp = leaf(Box::new(graph_panel) as Box<dyn crate::ui::Panel>);
+    let hist = leaf(Box::new(HistoryPanel::new(graph.clone())) as Box<dyn crate::ui::Panel>);
+    let mut info_panel = InfoPanel::new(graph.clone());
+    if let Some(a) = altimeter {
+        info_panel = info_panel.with_altimeter(a.clone());
+    }
+    info_panel.highlighted = highlighted;
+    info_panel.alarm = al.active_on(idx);
+    info_panel.unacked = al.unacked_on(idx);
+    let info = leaf(Box::new(info_panel) as Box<dyn crate::ui::Panel>);
+
+    group(
+        Direction::Vertical,
+        vec![Constraint::Percentage(70), Constraint::Percentage(30)],
+        vec![
+            gp,
+            group(
+                Direction::Horizontal,
+                vec![Constraint::Percentage(60), Constraint::Percentage(40)],
+                vec![hist, info],
+            ),
+        ],
+    )
+}
+
+pub fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    cli.validate()?;
+    let schema = Schema::load(cli.channels.as_deref())?;
+
+    // Event log shown in the UI instead of printing over the terminal
+    let events = shared_log(200);
+
+    // Shared graphs, one per schema channel (index order = remote `<idx>`)
+    let graphs: Vec<SharedGraph> = schema.build_graphs();
+    let columns = schema.columns();
+    let quality: SharedQuality = Arc::new(RwLock::new(LinkQuality::new(&schema)));
+    let mut pipeline = Pipeline::new(&schema, graphs.clone()).with_quality(quality.clone());
+
+    // Telemetry inputs (`--source`, or the one implied by the legacy flags)
+    let sources = cli.sources();
+    let replaying = sources
+        .iter()
+        .any(|s| matches!(s.spec, SourceSpec::File(_)));
+
+    // GPS ground track (the simulator knows where its ground station is)
+    let simulating = sources.iter().any(|s| s.spec == SourceSpec::Sim);
+    let station = cli.station.or(simulating.then(station_position));
+    let track: SharedTrack = Arc::new(RwLock::new(Track::new(&schema, station)));
+    let show_track = track.read().unwrap().enabled();
+    if show_track {
+        pipeline = pipeline.with_track(track.clone());
+    }
+
+    // Flight phases from the altitude channel
+    let flight: SharedFlight = Arc::new(RwLock::new(FlightTracker::new(&schema)));
+    pipeline = pipeline.with_flight(flight.clone());
+
+    // Altitude baseline: recompute the altitude from pressure once a QNH is set
+    let altimeter: SharedAltimeter = Arc::new(RwLock::new(Altimeter::new(&schema, cli.qnh)));
+    let altitude_idx = altimeter.read().unwrap().channels().map(|(_, a)| a);
+    pipeline = pipeline.with_altimeter(altimeter.clone());
+
+    // Session recording (not for replays, which are recordings already)
+    let mut recorder: Option<SharedRecorder> = None;
+    if !cli.no_record && !replaying {
+        let rec = Arc::new(RwLock::new(Recorder::create(&cli.record_dir, &schema)?));
+        log(
+            &events,
+            Level::Info,
+            format!("recording to {}", rec.read().unwrap().path.display()),
+        );
+        pipeline = pipeline.with_recorder(rec.clone());
+        recorder = Some(rec);
+    }
+
+    // Threshold alarms declared in the schema
+    let alarms: SharedAlarms = Arc::new(RwLock::new(Alarms::new(&schema)));
+    pipeline = pipeline.with_alarms(alarms.clone());
+
+    // Live sample feed for remote subscribers
+    let feed: SharedFeed = Arc::new(RwLock::new(Feed::new(&schema)));
+    pipeline = pipeline.with_feed(feed.clone());
+
+    // Uplink commands, written to whichever receiver is connected
+    let uplink: SharedUplink = Arc::new(RwLock::new(Uplink::new(&schema)));
+    pipeline = pipeline.with_uplink(uplink.clone());
+
+    // Every received line, for the raw terminal
+    let rawlog: SharedRawLog = Arc::new(RwLock::new(RawLog::new()));
+    pipeline = pipeline.with_rawlog(rawlog.clone());
+
+    // Parser error counters (and the bad-packet policy)
+    let diagnostics: SharedDiagnostics = Arc::new(RwLock::new(ParseDiagnostics::new(
+        &schema,
+        cli.reject_bad_packets,
+    )));
+    pipeline = pipeline.with_diagnostics(diagnostics.clone());
+
+    // Kept for commands acting on the ingest side (baseline changes)
+    let control = pipeline.clone();
+
+    // Message numbers from two inputs interleaved would read as constant loss
+    if let Some(counter) = schema.with_role(ChannelRole::Counter).next() {
+        let name = &schema.channels[counter].name;
+        let feeding = sources
+            .iter()
+            .filter(|s| s.channels.is_empty() || s.channels.contains(name))
+            .count();
+        if feeding > 1 {
+            log(
+                &events,
+                Level::Warn,
+                format!(
+                    "{} sources may feed {:?}; map them with --source <spec>=<channel>,...",
+                    feeding, name
+                ),
+            );
         }
-        // Parse "RSSI_PACKET: -89.5 dBm"
-        else if trimmed.starts_with("RSSI_PACKET:") {
-            let parts: Vec<&str> = trimmed.split_whitespace().collect();
-            if parts.len() >= 2 {
-                // Extract RSSI_PACKET (e.g., "-89.5")
-                if let Ok(val) = parts[1].parse::<f64>() {
-                    rssi_packet = Some(val);
-                }
+    }
+
+    // One reader thread per source, each with its own link status
+    let mut links: Vec<SharedLink> = Vec::new();
+    let mut replay: Option<SharedReplay> = None;
+    for arg in &sources {
+        let source: Box<dyn TelemetrySource> = match &arg.spec {
+            SourceSpec::Serial { port, baud } => {
+                let baud = baud.unwrap_or(cli.baud);
+                // Fail fast on a wrong device unless asked to wait for it to appear
+                let opened = match open_serial(port, baud) {
+                    Ok(p) => Some(p),
+                    Err(_) if cli.wait => None,
+                    Err(e) => {
+                        return Err(format!(
+                            "{} (use --port to pick another device, --list-ports to see what is available, --wait to retry until it appears)",
+                            e
+                        )
+                        .into());
+                    }
+                };
+                Box::new(SerialSource::new(port, baud).with_port(opened))
+            }
+            SourceSpec::File(path) => {
+                let r = ReplaySource::load(path, cli.speed)?;
+                replay = Some(r.control());
+                Box::new(r)
             }
+            SourceSpec::Tcp(addr) => Box::new(TcpClientSource::new(addr)),
+            SourceSpec::Udp(addr) => Box::new(UdpSource::new(addr)),
+            SourceSpec::Stdin => Box::new(StdinSource::new()),
+            SourceSpec::Sim => Box::new(SimSource::new(SimConfig {
+                rate_hz: cli.sim_rate,
+                loss: cli.sim_loss / 100.0,
+            })),
+        };
+        let status: SharedLink = Arc::new(RwLock::new(LinkStatus::new(&source.status())));
+        // a replay shows its transport in the title bar instead
+        if !matches!(arg.spec, SourceSpec::File(_)) {
+            links.push(status.clone());
         }
+        let fed = if arg.channels.is_empty() {
+            pipeline.clone()
+        } else {
+            pipeline
+                .clone()
+                .with_channels(&arg.channels)
+                .map_err(|e| format!("--source {}: {}", source.status(), e))?
+        };
+        start_source(source, fed, status, uplink.clone(), events.clone());
     }
-    (msgnum, rssi, temp, pres, hum, alt, rssi_packet)
-}
 
-pub fn run() -> Result<(), Box<dyn Error>> {
-    // Graph configuration
-    let cfg_msg = GraphConfig::new(50, 1_000, (0.0, 1000.0));
-    let cfg_rssi = GraphConfig::new(50, 1_000, (-120.0, 0.0));
-    let cfg_temp = GraphConfig::new(50, 1_000, (-10.0, 25.0));
-    let cfg_pres = GraphConfig::new(50, 1_000, (800.0, 1500.0));
-    let cfg_hum = GraphConfig::new(50, 1_000, (0.0, 100.0));
-    let cfg_alt = GraphConfig::new(50, 1_000, (0.0, 5000.0));
-    let cfg_rssi_packet = GraphConfig::new(50, 1_000, (-120.0, 0.0));
-
-    // Shared graphs
-    let g_msg: SharedGraph = Arc::new(RwLock::new(GraphShared::new(
-        cfg_msg,
-        "Msg #",
-        Color::Magenta,
-        true,
-        0.35,
-    )));
-    let g_rssi: SharedGraph = Arc::new(RwLock::new(GraphShared::new(
-        cfg_rssi,
-        "RSSI ACK (dBm)",
-        Color::Cyan,
-        true,
-        0.5,
-    )));
-    let g_temp: SharedGraph = Arc::new(RwLock::new(GraphShared::new(
-        cfg_temp,
-        "TEMP (°C)",
-        Color::Red,
-        false,
-        0.5,
-    )));
-    let g_pres: SharedGraph = Arc::new(RwLock::new(GraphShared::new(
-        cfg_pres,
-        "PRESSURE (hPa)",
-        Color::Green,
-        true,
-        1.0,
-    )));
-    let g_hum: SharedGraph = Arc::new(RwLock::new(GraphShared::new(
-        cfg_hum,
-        "HUMIDITY (%)",
-        Color::Blue,
-        false,
-        0.5,
-    )));
-    let g_alt: SharedGraph = Arc::new(RwLock::new(GraphShared::new(
-        cfg_alt,
-        "ALTITUDE (m)",
-        Color::LightMagenta,
-        false,
-        0.5,
-    )));
-    let g_rssi_packet: SharedGraph = Arc::new(RwLock::new(GraphShared::new(
-        cfg_rssi_packet,
-        "RSSI PACKET (dBm)",
-        Color::Yellow,
-        true,
-        0.5,
-    )));
+    // Channels with a graph region, in layout order (Tab cycles through these)
+    let shown: Vec<usize> = columns.concat();
 
-    let graphs: Vec<SharedGraph> = vec![
-        g_msg.clone(),
-        g_rssi.clone(),
-        g_temp.clone(),
-        g_pres.clone(),
-        g_hum.clone(),
-        g_alt.clone(),
-        g_rssi_packet.clone(),
-    ];
-
-    // Remote control thread
+    // Remote control threads; focus and shutdown requests come back here
+    let (ui_tx, ui_rx) = mpsc::channel::<UiCommand>();
     {
-        let graphs_for_thread = graphs.clone();
-        thread::spawn(move || remote_server("127.0.0.1:4000", graphs_for_thread));
+        let ctx = Arc::new(RemoteContext {
+            graphs: graphs.clone(),
+            events: events.clone(),
+            quality: quality.clone(),
+            diagnostics: diagnostics.clone(),
+            altimeter: altimeter.clone(),
+            feed: feed.clone(),
+            pipeline: control.clone(),
+            replay: replay.clone(),
+            uplink: uplink.clone(),
+            shown: shown.clone(),
+            ui: ui_tx,
+        });
+        let bind = cli.bind.to_string();
+        let c = ctx.clone();
+        thread::spawn(move || remote_server(&bind, c, Proto::Text));
+        if let Some(addr) = cli.json_bind {
+            let bind = addr.to_string();
+            thread::spawn(move || remote_server(&bind, ctx, Proto::Json));
+        }
     }
 
-    // Start serial reader
-    start_serial_reader(
-        "/dev/ttyACM0",
-        g_msg.clone(),
-        g_rssi.clone(),
-        g_temp.clone(),
-        g_pres.clone(),
-        g_hum.clone(),
-        g_alt.clone(),
-        g_rssi_packet.clone(),
-    );
-
-    // Split graphs into left and right groups
-    let left_graphs = vec![
-        g_msg.clone(),
-        g_rssi.clone(),
-        g_temp.clone(),
-        g_pres.clone(),
-    ];
-    let right_graphs = vec![g_hum.clone(), g_alt.clone(), g_rssi_packet.clone()];
-
     // UI setup
     let mut terminal = ratatui::init();
-    let mut focused = 0usize;
+    crossterm::execute!(std::io::stdout(), crossterm::event::EnableMouseCapture)?;
+    let mut focused = shown[0];
     let smoothing_presets = [0.0, 0.25, 0.5, 0.75, 1.0];
-    let frame_time = Duration::from_millis(100);
+    let frame_time = cli.frame_time();
     let mut running = true;
+    // Alarm raises already announced (log line + bell)
+    let mut announced = 0;
+    // Uplink command line being typed (`u`), and the last command sent
+    let mut console: Option<String> = None;
+    let mut last_command = String::new();
+    // Raw terminal (`t`) in place of the graphs: filter (`/` while editing)
+    // and scrollback anchor
+    let mut show_terminal = false;
+    let mut raw_filter = String::new();
+    let mut raw_editing = false;
+    let mut raw_anchor: Option<u64> = None;
 
     while running {
         let frame_start = std::time::Instant::now();
 
-        // Left children (4 graphs)
-        let mut left_children: Vec<Node> = Vec::new();
-        for i in 0..left_graphs.len() {
-            let gp = leaf(
-                Box::new(GraphPanel::new(left_graphs[i].clone())) as Box<dyn crate::ui::Panel>
-            );
-            let hist =
-                leaf(Box::new(HistoryPanel::new(left_graphs[i].clone()))
-                    as Box<dyn crate::ui::Panel>);
-            let mut info_panel = InfoPanel::new(left_graphs[i].clone());
-            info_panel.highlighted = i == focused;
-            let info = leaf(Box::new(info_panel) as Box<dyn crate::ui::Panel>);
-
-            let region = group(
-                ratatui::layout::Direction::Vertical,
-                vec![
-                    ratatui::layout::Constraint::Percentage(70),
-                    ratatui::layout::Constraint::Percentage(30),
-                ],
-                vec![
-                    gp,
-                    group(
-                        ratatui::layout::Direction::Horizontal,
-                        vec![
-                            ratatui::layout::Constraint::Percentage(60),
-                            ratatui::layout::Constraint::Percentage(40),
-                        ],
-                        vec![hist, info],
-                    ),
-                ],
-            );
-            left_children.push(region);
+        // Requests from remote clients
+        while let Ok(cmd) = ui_rx.try_recv() {
+            match cmd {
+                UiCommand::Focus(idx) => focused = idx,
+                UiCommand::Shutdown => running = false,
+            }
+        }
+        if !running {
+            break;
         }
 
-        // Right children (3 graphs)
-        let mut right_children: Vec<Node> = Vec::new();
-        for i in 0..right_graphs.len() {
-            let gp = leaf(
-                Box::new(GraphPanel::new(right_graphs[i].clone())) as Box<dyn crate::ui::Panel>
-            );
-            let hist =
-                leaf(Box::new(HistoryPanel::new(right_graphs[i].clone()))
-                    as Box<dyn crate::ui::Panel>);
-            let mut info_panel = InfoPanel::new(right_graphs[i].clone());
-            info_panel.highlighted = (i + left_graphs.len()) == focused;
-            let info = leaf(Box::new(info_panel) as Box<dyn crate::ui::Panel>);
-
-            let region = group(
-                ratatui::layout::Direction::Vertical,
-                vec![
-                    ratatui::layout::Constraint::Percentage(70),
-                    ratatui::layout::Constraint::Percentage(30),
-                ],
-                vec![
-                    gp,
-                    group(
-                        ratatui::layout::Direction::Horizontal,
-                        vec![
-                            ratatui::layout::Constraint::Percentage(60),
-                            ratatui::layout::Constraint::Percentage(40),
-                        ],
-                        vec![hist, info],
-                    ),
-                ],
-            );
-            right_children.push(region);
+        // Flight events as vertical lines on every graph
+        let markers: Vec<TimeMarker> = flight
+            .read()
+            .unwrap()
+            .events
+            .iter()
+            .map(|e| TimeMarker {
+                t: e.t,
+                color: phase_color(e.phase),
+            })
+            .collect();
+
+        // One vertical group per layout column, one region per channel
+        let column_nodes: Vec<Node> = columns
+            .iter()
+            .map(|col| {
+                let children: Vec<Node> = col
+                    .iter()
+                    .map(|&idx| {
+                        let alt = (Some(idx) == altitude_idx).then_some(&altimeter);
+                        channel_region(&graphs[idx], idx, idx == focused, &markers, alt, &alarms)
+                    })
+                    .collect();
+                group(
+                    Direction::Vertical,
+                    vec![Constraint::Ratio(1, col.len() as u32); col.len()],
+                    children,
+                )
+            })
+            .collect();
+        let mut column_nodes = column_nodes;
+        if show_track {
+            column_nodes.push(leaf(
+                Box::new(TrackPanel::new(track.clone())) as Box<dyn crate::ui::Panel>
+            ));
         }
 
-        // That's the part of code that set up the `Controls` panel
-        // let extra = leaf(Box::new(ParagraphPanel::new(
-        //     "TAB=Focus  A=Autoscale  S=Smoothing  L=Lock bounds  Q=Quit",
-        //     "Controls",
-        // )) as Box<dyn crate::ui::Panel>);
+        let mut title_panel = TitlePanel::new(&cli.title).with_graphs(graphs.clone());
+        for l in &links {
+            title_panel = title_panel.with_link(l.clone());
+        }
+        if let Some(r) = &replay {
+            title_panel = title_panel.with_replay(r.clone());
+        }
+        if let Some(r) = &recorder {
+            title_panel = title_panel.with_recorder(r.clone());
+        }
 
         // This set up the main interface layout
         let root = group(
             // These constraints are applied vertically to the whole terminal window
-            ratatui::layout::Direction::Vertical,
+            Direction::Vertical,
             vec![
-                // 3 lines
-                ratatui::layout::Constraint::Length(3),
+                // title + status line + borders
+                Constraint::Length(if recorder.is_some() { 4 } else { 3 }),
                 // min to adapt to the sceen
-                ratatui::layout::Constraint::Min(20),
+                Constraint::Min(20),
+                // event log, link quality and flight timeline
+                Constraint::Length(8),
             ],
             vec![
                 // Leaf are basically single panels
                 // This one set up the title
-                // And it take the place of our first vertical constraint (the 3 lines)
-                leaf(
-                    Box::new(TitlePanel::new("Live CanSat Telemetry")) as Box<dyn crate::ui::Panel>
-                ),
-                // This one is kinda self-explanatory
+                // And it take the place of our first vertical constraint
+                leaf(Box::new(title_panel) as Box<dyn crate::ui::Panel>),
+                // Divide the second vertical constraint into equal columns
+                if show_terminal {
+                    group(
+                        Direction::Horizontal,
+                        vec![Constraint::Percentage(70), Constraint::Percentage(30)],
+                        vec![
+                            leaf(Box::new(
+                                TerminalPanel::new(rawlog.clone())
+                                    .with_filter(&raw_filter, raw_editing)
+                                    .with_anchor(raw_anchor),
+                            ) as Box<dyn crate::ui::Panel>),
+                            leaf(Box::new(ParserPanel::new(diagnostics.clone()))
+                                as Box<dyn crate::ui::Panel>),
+                        ],
+                    )
+                } else {
+                    group(
+                        Direction::Horizontal,
+                        vec![Constraint::Ratio(1, column_nodes.len() as u32); column_nodes.len()],
+                        column_nodes,
+                    )
+                },
                 group(
-                    // Divide the second vertical constraint in a horizontal way
-                    ratatui::layout::Direction::Horizontal,
-                    // The right part take 50% and the left 50%
+                    Direction::Horizontal,
                     vec![
-                        ratatui::layout::Constraint::Percentage(50),
-                        ratatui::layout::Constraint::Percentage(50),
+                        Constraint::Percentage(24),
+                        Constraint::Percentage(22),
+                        Constraint::Percentage(18),
+                        Constraint::Percentage(18),
+                        Constraint::Percentage(18),
                     ],
-                    // Now what to put into these panels ?
-                    // Here where puting the actual panels where we layed out everything
                     vec![
-                        // This is the the right part
-                        // It group every graph and give it a space
-                        group(
-                            ratatui::layout::Direction::Vertical,
-                            vec![
-                                ratatui::layout::Constraint::Percentage(25),
-                                ratatui::layout::Constraint::Percentage(25),
-                                ratatui::layout::Constraint::Percentage(25),
-                                ratatui::layout::Constraint::Percentage(25),
-                            ],
-                            left_children,
+                        leaf(Box::new(LogPanel::new(events.clone())) as Box<dyn crate::ui::Panel>),
+                        leaf(Box::new(LinkQualityPanel::new(quality.clone()))
+                            as Box<dyn crate::ui::Panel>),
+                        leaf(
+                            Box::new(FlightPanel::new(flight.clone())) as Box<dyn crate::ui::Panel>
                         ),
-                        group(
-                            ratatui::layout::Direction::Vertical,
-                            vec![
-                                ratatui::layout::Constraint::Percentage(34),
-                                ratatui::layout::Constraint::Percentage(33),
-                                ratatui::layout::Constraint::Percentage(33),
-                            ],
-                            right_children,
+                        leaf(Box::new(AlarmPanel::new(alarms.clone())) as Box<dyn crate::ui::Panel>),
+                        leaf(
+                            Box::new(UplinkPanel::new(uplink.clone()).with_input(console.clone()))
+                                as Box<dyn crate::ui::Panel>,
                         ),
                     ],
                 ),
@@ -598,37 +979,264 @@ pub fn run() -> Result<(), Box<dyn Error>> {
 
         terminal.draw(|f| root.draw(f, f.area()))?;
 
+        // Announce new alarms in the event log and ring the terminal bell
+        {
+            let al = alarms.read().unwrap();
+            if al.raised > announced {
+                for e in al.log.iter().filter(|e| e.id > announced) {
+                    log(
+                        &events,
+                        Level::Warn,
+                        format!("ALARM {} ({:.2})", e.text, e.value),
+                    );
+                }
+                announced = al.raised;
+                let mut out = std::io::stdout();
+                out.write_all(b"\x07")?;
+                out.flush()?;
+            }
+        }
+
         // Keyboard controls
         while crossterm::event::poll(Duration::from_millis(0))? {
-            if let crossterm::event::Event::Key(key) = crossterm::event::read()? {
+            let key = match crossterm::event::read()? {
+                crossterm::event::Event::Key(key) => key,
+                // Click or drag inside a plot: focus it and put the cursor there
+                crossterm::event::Event::Mouse(m) => {
+                    // the terminal replaces the graphs, whose last plot
+                    // areas are stale while it is shown
+                    if !show_terminal
+                        && let crossterm::event::MouseEventKind::Down(
+                            crossterm::event::MouseButton::Left,
+                        )
+                        | crossterm::event::MouseEventKind::Drag(
+                            crossterm::event::MouseButton::Left,
+                        ) = m.kind
+                    {
+                        let hit = graphs.iter().enumerate().find_map(|(i, g)| {
+                            let g = g.read().unwrap();
+                            let t = g.time_at_column(m.column, m.row)?;
+                            Some((i, g.data.nearest(t).map_or(t, |p| p.0)))
+                        });
+                        if let Some((i, t)) = hit {
+                            focused = i;
+                            set_cursor(&graphs, Some(t));
+                        }
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            // Uplink console: every key edits the command line until Enter/Esc
+            if let Some(input) = &mut console {
                 match key.code {
-                    crossterm::event::KeyCode::Char('q') => running = false,
-                    crossterm::event::KeyCode::Tab => focused = (focused + 1) % graphs.len(),
-                    crossterm::event::KeyCode::Char('a') => {
-                        let mut g = graphs[focused].write().unwrap();
-                        g.autoscale = !g.autoscale;
-                        if g.autoscale {
-                            g.locked_bounds = None;
+                    crossterm::event::KeyCode::Enter => {
+                        let text = input.trim().to_string();
+                        console = None;
+                        if !text.is_empty() {
+                            match uplink.write().unwrap().send(&text) {
+                                Ok(id) => log(
+                                    &events,
+                                    Level::Info,
+                                    format!("uplink #{} queued: {}", id, text),
+                                ),
+                                Err(e) => log(&events, Level::Warn, format!("uplink: {}", e)),
+                            }
+                            last_command = text;
                         }
                     }
-                    crossterm::event::KeyCode::Char('s') => {
-                        let mut g = graphs[focused].write().unwrap();
-                        let idx = smoothing_presets
-                            .iter()
-                            .position(|&v| (v - g.smoothing).abs() < 1e-9)
-                            .unwrap_or(0);
-                        g.smoothing = smoothing_presets[(idx + 1) % smoothing_presets.len()];
+                    crossterm::event::KeyCode::Esc => console = None,
+                    crossterm::event::KeyCode::Up => *input = last_command.clone(),
+                    crossterm::event::KeyCode::Backspace => {
+                        input.pop();
+                    }
+                    crossterm::event::KeyCode::Char(c) if input.len() < MAX_COMMAND => {
+                        input.push(c)
                     }
-                    crossterm::event::KeyCode::Char('l') => {
-                        let mut g = graphs[focused].write().unwrap();
-                        if g.locked_bounds.is_some() {
-                            g.locked_bounds = None;
+                    _ => {}
+                }
+                continue;
+            }
+            // Raw terminal filter: typed until Enter (keep) or Esc (clear)
+            if raw_editing {
+                match key.code {
+                    crossterm::event::KeyCode::Enter => raw_editing = false,
+                    crossterm::event::KeyCode::Esc => {
+                        raw_editing = false;
+                        raw_filter.clear();
+                    }
+                    crossterm::event::KeyCode::Backspace => {
+                        raw_filter.pop();
+                    }
+                    crossterm::event::KeyCode::Char(c) => raw_filter.push(c),
+                    _ => {}
+                }
+                raw_anchor = None;
+                continue;
+            }
+            // Raw terminal scrollback
+            if show_terminal {
+                let by = match key.code {
+                    crossterm::event::KeyCode::Up => Some(-1),
+                    crossterm::event::KeyCode::Down => Some(1),
+                    crossterm::event::KeyCode::PageUp => Some(-TERMINAL_PAGE),
+                    crossterm::event::KeyCode::PageDown => Some(TERMINAL_PAGE),
+                    _ => None,
+                };
+                if let Some(by) = by {
+                    raw_anchor = rawlog.read().unwrap().scroll(&raw_filter, raw_anchor, by);
+                    continue;
+                }
+                match key.code {
+                    crossterm::event::KeyCode::Char('/') => {
+                        raw_editing = true;
+                        continue;
+                    }
+                    crossterm::event::KeyCode::End => raw_anchor = None,
+                    _ => {}
+                }
+            }
+            match key.code {
+                crossterm::event::KeyCode::Char('q') => running = false,
+                crossterm::event::KeyCode::Char('t') => show_terminal = !show_terminal,
+                crossterm::event::KeyCode::Char('u') => {
+                    if replay.is_some() {
+                        log(
+                            &events,
+                            Level::Warn,
+                            "uplink: not available while replaying",
+                        );
+                    } else {
+                        console = Some(String::new());
+                    }
+                }
+                crossterm::event::KeyCode::Tab => {
+                    let pos = shown.iter().position(|&i| i == focused).unwrap_or(0);
+                    focused = shown[(pos + 1) % shown.len()];
+                }
+                crossterm::event::KeyCode::Char('a') => {
+                    let mut g = graphs[focused].write().unwrap();
+                    g.autoscale = !g.autoscale;
+                    if g.autoscale {
+                        g.locked_bounds = None;
+                    }
+                }
+                crossterm::event::KeyCode::Char('s') => {
+                    let mut g = graphs[focused].write().unwrap();
+                    let idx = smoothing_presets
+                        .iter()
+                        .position(|&v| (v - g.smoothing).abs() < 1e-9)
+                        .unwrap_or(0);
+                    g.smoothing = smoothing_presets[(idx + 1) % smoothing_presets.len()];
+                }
+                crossterm::event::KeyCode::Char('l') => {
+                    let mut g = graphs[focused].write().unwrap();
+                    if g.locked_bounds.is_some() {
+                        g.locked_bounds = None;
+                    } else {
+                        g.locked_bounds = g.view.current_bounds;
+                    }
+                }
+                // Freeze / scroll-back (all graphs together; ingestion continues)
+                crossterm::event::KeyCode::Char('f') => {
+                    let frozen = graphs.iter().any(|g| g.read().unwrap().frozen.is_some());
+                    for g in &graphs {
+                        let mut g = g.write().unwrap();
+                        if frozen {
+                            g.unfreeze();
+                            g.cursor = None;
                         } else {
-                            g.locked_bounds = g.view.current_bounds;
+                            g.freeze();
+                        }
+                    }
+                }
+                crossterm::event::KeyCode::End => {
+                    for g in &graphs {
+                        let mut g = g.write().unwrap();
+                        g.unfreeze();
+                        g.cursor = None;
+                    }
+                }
+                // Inspection cursor: starts on the newest visible sample
+                crossterm::event::KeyCode::Char('c') => {
+                    let cursor = graphs[focused].read().unwrap().cursor;
+                    let t = match cursor {
+                        Some(_) => None,
+                        None => {
+                            let g = graphs[focused].read().unwrap();
+                            let (_, x1) = g.frozen.unwrap_or_else(|| g.data.x_bounds());
+                            g.data.nearest(x1).map(|p| p.0)
+                        }
+                    };
+                    set_cursor(&graphs, t);
+                }
+                crossterm::event::KeyCode::Esc => set_cursor(&graphs, None),
+                crossterm::event::KeyCode::Left | crossterm::event::KeyCode::Right => {
+                    let dir = if key.code == crossterm::event::KeyCode::Left {
+                        -1
+                    } else {
+                        1
+                    };
+                    let cursor = {
+                        let g = graphs[focused].read().unwrap();
+                        g.cursor.map(|t| g.data.step_from(t, dir).unwrap_or(t))
+                    };
+                    match cursor {
+                        // cursor mode: step through the focused graph's samples
+                        Some(t) => set_cursor(&graphs, Some(t)),
+                        None => {
+                            for g in &graphs {
+                                g.write().unwrap().pan(dir as f64 * PAN_STEP);
+                            }
+                        }
+                    }
+                }
+                crossterm::event::KeyCode::Char(c @ ('+' | '=' | '-')) => {
+                    let factor = if c == '-' { ZOOM_STEP } else { 1.0 / ZOOM_STEP };
+                    for g in &graphs {
+                        g.write().unwrap().zoom(factor);
+                    }
+                }
+                crossterm::event::KeyCode::Char('x') => {
+                    let n = alarms.write().unwrap().ack_all();
+                    if n > 0 {
+                        log(&events, Level::Info, format!("acknowledged {} alarm(s)", n));
+                    }
+                }
+                // Altitude baseline
+                crossterm::event::KeyCode::Char('z') => match control.zero_on_pad(ZERO_SECONDS) {
+                    Ok(()) => log(
+                        &events,
+                        Level::Info,
+                        format!("zeroing altitude on pad over {} s", ZERO_SECONDS),
+                    ),
+                    Err(e) => log(&events, Level::Warn, format!("zero on pad: {}", e)),
+                },
+                crossterm::event::KeyCode::Char(c @ ('b' | 'B')) => {
+                    let delta = if c == 'B' { QNH_STEP } else { -QNH_STEP };
+                    match control.nudge_qnh(delta) {
+                        Ok(q) => log(
+                            &events,
+                            Level::Info,
+                            format!("altitude baseline QNH {:.2} hPa", q),
+                        ),
+                        Err(e) => log(&events, Level::Warn, format!("baseline: {}", e)),
+                    }
+                }
+                // Replay transport (ignored when reading the serial port)
+                crossterm::event::KeyCode::Char(c @ (' ' | '[' | ']' | ',' | '.')) => {
+                    if let Some(r) = &replay {
+                        let mut r = r.write().unwrap();
+                        match c {
+                            ' ' => r.paused = !r.paused,
+                            '[' => r.speed = r.speed.slower(),
+                            ']' => r.speed = r.speed.faster(),
+                            ',' => r.seek_by(-REPLAY_SEEK_STEP),
+                            _ => r.seek_by(REPLAY_SEEK_STEP),
                         }
                     }
-                    _ => {}
                 }
+                _ => {}
             }
         }
 
@@ -642,6 +1250,7 @@ pub fn run() -> Result<(), Box<dyn Error>> {
         }
     }
 
+    let _ = crossterm::execute!(std::io::stdout(), crossterm::event::DisableMouseCapture);
     ratatui::restore();
     Ok(())
 }
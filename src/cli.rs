@@ -0,0 +1,222 @@
+//! src/cli.rs
+//!
+//! Command-line arguments and startup validation.
+//!
+//! Everything that used to be hard-coded in `app::run()` (serial device, baud
+//! rate, control bind address, frame pacing, window title) is configurable here.
+
+use std::error::Error;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use clap::Parser;
+
+use crate::telemetry::altimeter::QNH_RANGE;
+use crate::telemetry::replay::ReplaySpeed;
+use crate::telemetry::source::{SourceArg, SourceSpec};
+
+/// Live CanSat telemetry monitor.
+#[derive(Parser, Debug, Clone)]
+#[command(version, about)]
+pub struct Cli {
+    /// Serial device the LoRa receiver is attached to (e.g. /dev/ttyACM0, /dev/ttyUSB1, COM3).
+    #[arg(short, long, default_value = "/dev/ttyACM0")]
+    pub port: String,
+
+    /// Serial baud rate.
+    #[arg(short, long, default_value_t = 115_200, value_parser = clap::value_parser!(u32).range(1..))]
+    pub baud: u32,
+
+    /// Address the TCP control server binds to.
+    #[arg(long, default_value = "127.0.0.1:4000")]
+    pub bind: SocketAddr,
+
+    /// Second control server address whose clients speak JSON lines from the
+    /// start (text clients can switch with `hello json` instead).
+    #[arg(long, value_name = "ADDR")]
+    pub json_bind: Option<SocketAddr>,
+
+    /// Target duration of one UI frame in milliseconds.
+    #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u64).range(10..=5_000))]
+    pub frame_ms: u64,
+
+    /// Text shown in the title bar.
+    #[arg(short, long, default_value = "Live CanSat Telemetry")]
+    pub title: String,
+
+    /// Channel schema (TOML). Defaults to the built-in copy of `channels.toml`.
+    #[arg(short, long)]
+    pub channels: Option<PathBuf>,
+
+    /// Telemetry input, repeatable to read several at once: serial:<device>[@<baud>],
+    /// tcp:<host>:<port>, udp:[<host>:]<port>, file:<path>, stdin or sim, optionally
+    /// followed by =<channel>,... to feed only those channels.
+    /// Replaces --port, --replay and --simulate.
+    #[arg(long = "source", value_name = "SPEC", value_parser = SourceArg::from_str,
+          conflicts_with_all = ["port", "replay", "simulate"])]
+    pub sources: Vec<SourceArg>,
+
+    /// Replay a file written by the onboard logger instead of reading the serial port.
+    #[arg(short, long, value_name = "FILE")]
+    pub replay: Option<String>,
+
+    /// Initial replay speed: 0.5, 1, 4 or max.
+    #[arg(long, default_value = "1", value_parser = ReplaySpeed::from_str)]
+    pub speed: ReplaySpeed,
+
+    /// Generate a simulated flight instead of reading the serial port.
+    #[arg(long, conflicts_with = "replay")]
+    pub simulate: bool,
+
+    /// Simulated packet rate in Hz.
+    #[arg(long, default_value_t = 2.0)]
+    pub sim_rate: f64,
+
+    /// Simulated base packet loss in percent.
+    #[arg(long, default_value_t = 2.0)]
+    pub sim_loss: f64,
+
+    /// Ground-station position for distance/bearing on the track map, as LAT,LON
+    /// in decimal degrees (the simulator supplies its own).
+    #[arg(long, value_name = "LAT,LON", value_parser = parse_position, allow_hyphen_values = true)]
+    pub station: Option<(f64, f64)>,
+
+    /// Sea-level pressure (QNH) in hPa to recompute the altitude from the
+    /// pressure channel, instead of using the altitude the CanSat transmits.
+    #[arg(long, value_name = "HPA")]
+    pub qnh: Option<f64>,
+
+    /// Directory receiving the per-session CSV recordings.
+    #[arg(long, default_value = "sessions", value_name = "DIR")]
+    pub record_dir: PathBuf,
+
+    /// Drop a whole packet when any of its fields fails to parse, instead of
+    /// using the fields that did.
+    #[arg(long)]
+    pub reject_bad_packets: bool,
+
+    /// Do not record the session to disk.
+    #[arg(long)]
+    pub no_record: bool,
+
+    /// Start even if the serial port cannot be opened yet and keep retrying.
+    #[arg(long)]
+    pub wait: bool,
+
+    /// List the serial ports visible on this machine and exit.
+    #[arg(long, exclusive = true)]
+    pub list_ports: bool,
+}
+
+impl Cli {
+    /// Frame duration derived from `--frame-ms`.
+    pub fn frame_time(&self) -> Duration {
+        Duration::from_millis(self.frame_ms)
+    }
+
+    /// Inputs to read: `--source` entries, or the one implied by `--replay`,
+    /// `--simulate` or `--port`.
+    pub fn sources(&self) -> Vec<SourceArg> {
+        if !self.sources.is_empty() {
+            self.sources.clone()
+        } else if let Some(path) = &self.replay {
+            vec![SourceSpec::File(path.clone()).into()]
+        } else if self.simulate {
+            vec![SourceSpec::Sim.into()]
+        } else {
+            vec![
+                SourceSpec::Serial {
+                    port: self.port.clone(),
+                    baud: None,
+                }
+                .into(),
+            ]
+        }
+    }
+
+    /// Checks that cannot be expressed as clap value parsers.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.port.trim().is_empty() {
+            return Err("--port must not be empty (try --list-ports)".into());
+        }
+        let sources = self.sources();
+        // a replay has its own clock and rewinds, which would garble live data
+        if sources.len() > 1
+            && sources
+                .iter()
+                .any(|s| matches!(s.spec, SourceSpec::File(_)))
+        {
+            return Err("a file source cannot be combined with other sources".into());
+        }
+        if sources
+            .iter()
+            .filter(|s| s.spec == SourceSpec::Stdin)
+            .count()
+            > 1
+        {
+            return Err("stdin can only be read by one source".into());
+        }
+        if !(self.sim_rate > 0.0 && self.sim_rate <= 100.0) {
+            return Err("--sim-rate must be within (0, 100] Hz".into());
+        }
+        if !(0.0..=100.0).contains(&self.sim_loss) {
+            return Err("--sim-loss must be a percentage within 0..=100".into());
+        }
+        if let Some(q) = self.qnh
+            && !QNH_RANGE.contains(&q)
+        {
+            return Err(format!(
+                "--qnh must be within {}..={} hPa",
+                QNH_RANGE.start(),
+                QNH_RANGE.end()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Parse `LAT,LON` in decimal degrees.
+fn parse_position(s: &str) -> Result<(f64, f64), String> {
+    let err = || {
+        format!(
+            "invalid position {:?} (expected LAT,LON e.g. 50.8503,4.3517)",
+            s
+        )
+    };
+    let (lat, lon) = s.split_once(',').ok_or_else(err)?;
+    let lat: f64 = lat.trim().parse().map_err(|_| err())?;
+    let lon: f64 = lon.trim().parse().map_err(|_| err())?;
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+        return Err(format!("position {:?} is out of range", s));
+    }
+    Ok((lat, lon))
+}
+
+/// Print every port reported by `serialport::available_ports` to stdout.
+pub fn list_ports() -> Result<(), Box<dyn Error>> {
+    let ports = serialport::available_ports()
+        .map_err(|e| format!("could not enumerate serial ports: {}", e))?;
+    if ports.is_empty() {
+        println!("No serial ports found.");
+        return Ok(());
+    }
+    for p in ports {
+        let kind = match p.port_type {
+            serialport::SerialPortType::UsbPort(info) => {
+                let product = info.product.unwrap_or_default();
+                let manufacturer = info.manufacturer.unwrap_or_default();
+                format!(
+                    "USB {:04x}:{:04x} {} {}",
+                    info.vid, info.pid, manufacturer, product
+                )
+            }
+            serialport::SerialPortType::PciPort => "PCI".to_string(),
+            serialport::SerialPortType::BluetoothPort => "Bluetooth".to_string(),
+            serialport::SerialPortType::Unknown => "Unknown".to_string(),
+        };
+        println!("{:<24} {}", p.port_name, kind.trim_end());
+    }
+    Ok(())
+}
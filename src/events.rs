@@ -0,0 +1,79 @@
+//! src/events.rs
+//!
+//! Bounded in-memory event log shared between worker threads and the UI.
+//!
+//! Background threads must not write to stdout/stderr while the terminal is in
+//! raw/alternate-screen mode, so they push messages here and the `LogPanel`
+//! renders the most recent ones.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Local};
+
+/// Severity of an event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Warn,
+    Error,
+}
+
+/// One logged message.
+#[derive(Clone, Debug)]
+pub struct Event {
+    pub time: DateTime<Local>,
+    pub level: Level,
+    pub text: String,
+}
+
+/// Ring buffer of the most recent events.
+#[derive(Debug)]
+pub struct EventLog {
+    entries: VecDeque<Event>,
+    capacity: usize,
+}
+
+impl EventLog {
+    /// Create an empty log keeping at most `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Append an event, dropping the oldest one when full.
+    pub fn push(&mut self, level: Level, text: String) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(Event {
+            time: Local::now(),
+            level,
+            text,
+        });
+    }
+
+    /// The `n` most recent events, oldest first.
+    pub fn recent(&self, n: usize) -> impl Iterator<Item = &Event> {
+        self.entries
+            .iter()
+            .skip(self.entries.len().saturating_sub(n))
+    }
+}
+
+/// Alias: Arc<RwLock<EventLog>>
+pub type SharedLog = Arc<RwLock<EventLog>>;
+
+/// Create a shared log with the given capacity.
+pub fn shared_log(capacity: usize) -> SharedLog {
+    Arc::new(RwLock::new(EventLog::new(capacity)))
+}
+
+/// Push a message onto a shared log (no-op if the lock is poisoned).
+pub fn log(log: &SharedLog, level: Level, text: impl Into<String>) {
+    if let Ok(mut l) = log.write() {
+        l.push(level, text.into());
+    }
+}
@@ -5,7 +5,7 @@
 pub mod config;
 pub mod data;
 pub mod shared;
+pub mod time;
 
 /// Re-exports
-pub use config::GraphConfig;
-pub use data::GraphData;
+pub use config::{DataWindow, GraphConfig};
@@ -4,10 +4,68 @@
 //!
 //! Centralized parameters for window lengths, history size, and default ranges.
 
+use std::str::FromStr;
+
+/// Extent of the live sliding window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DataWindow {
+    /// The last `n` samples.
+    Samples(usize),
+    /// Samples from the last `n` seconds.
+    Seconds(f64),
+}
+
+impl DataWindow {
+    /// Short form for titles, e.g. `50 pts` or `60s`.
+    pub fn label(&self) -> String {
+        match self {
+            DataWindow::Samples(n) => format!("{} pts", n),
+            DataWindow::Seconds(s) => format!("{}s", s),
+        }
+    }
+}
+
+impl FromStr for DataWindow {
+    type Err = String;
+
+    /// Parse `50` (samples) or a duration such as `60s`, `2m` or `500ms`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let err = || {
+            format!(
+                "invalid window {:?} (expected a sample count or a duration like 60s, 2m, 500ms)",
+                s
+            )
+        };
+        if let Ok(n) = s.parse::<usize>() {
+            return if n > 0 {
+                Ok(DataWindow::Samples(n))
+            } else {
+                Err(err())
+            };
+        }
+        let (num, scale) = if let Some(v) = s.strip_suffix("ms") {
+            (v, 0.001)
+        } else if let Some(v) = s.strip_suffix('s') {
+            (v, 1.0)
+        } else if let Some(v) = s.strip_suffix('m') {
+            (v, 60.0)
+        } else if let Some(v) = s.strip_suffix('h') {
+            (v, 3600.0)
+        } else {
+            return Err(err());
+        };
+        match num.trim().parse::<f64>() {
+            Ok(v) if v.is_finite() && v > 0.0 => Ok(DataWindow::Seconds(v * scale)),
+            _ => Err(err()),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct GraphConfig {
-    /// Number of data points visible in the live sliding window.
-    pub data_window: usize,
+    /// Samples (or time span) visible in the live sliding window.
+    pub data_window: DataWindow,
 
     /// Maximum number of historical points to retain (bounded memory).
     pub max_history: usize,
@@ -18,7 +76,7 @@ pub struct GraphConfig {
 
 impl GraphConfig {
     /// Create a new `GraphConfig`.
-    pub fn new(data_window: usize, max_history: usize, y_range: (f64, f64)) -> Self {
+    pub fn new(data_window: DataWindow, max_history: usize, y_range: (f64, f64)) -> Self {
         Self {
             data_window,
             max_history,
@@ -30,9 +88,50 @@ impl GraphConfig {
 impl Default for GraphConfig {
     fn default() -> Self {
         Self {
-            data_window: 60,
+            data_window: DataWindow::Samples(60),
             max_history: 2_000,
             y_range: (-1.0, 1.0),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(s: &str) -> Result<DataWindow, String> {
+        s.parse()
+    }
+
+    #[test]
+    fn sample_counts() {
+        assert_eq!(window("50"), Ok(DataWindow::Samples(50)));
+        assert_eq!(window(" 1 "), Ok(DataWindow::Samples(1)));
+        assert!(window("0").is_err());
+        assert!(window("-5").is_err());
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(window("60s"), Ok(DataWindow::Seconds(60.0)));
+        assert_eq!(window("2m"), Ok(DataWindow::Seconds(120.0)));
+        assert_eq!(window("1.5m"), Ok(DataWindow::Seconds(90.0)));
+        assert_eq!(window("500ms"), Ok(DataWindow::Seconds(0.5)));
+        assert_eq!(window("1h"), Ok(DataWindow::Seconds(3600.0)));
+        assert_eq!(window("30 s"), Ok(DataWindow::Seconds(30.0)));
+    }
+
+    #[test]
+    fn invalid_windows() {
+        for s in ["", "s", "0s", "-1m", "10d", "inf s", "NaNs", "ten"] {
+            assert!(window(s).is_err(), "{:?} should be rejected", s);
+        }
+    }
+
+    #[test]
+    fn labels() {
+        assert_eq!(DataWindow::Samples(50).label(), "50 pts");
+        assert_eq!(DataWindow::Seconds(60.0).label(), "60s");
+        assert_eq!(DataWindow::Seconds(0.5).label(), "0.5s");
+    }
+}
@@ -2,10 +2,13 @@
 //!
 //! Sliding-window live points, owned vector snapshots for widget lifetimes,
 //! and bounded history storage.
+//!
+//! Point x values are timestamps in seconds since the mission time origin
+//! (session start for live sources, first record for replays).
 
 use std::collections::VecDeque;
 
-use super::config::GraphConfig;
+use super::config::{DataWindow, GraphConfig};
 
 #[derive(Debug)]
 pub struct GraphData {
@@ -24,34 +27,23 @@ pub struct GraphData {
 }
 
 impl GraphData {
-    /// Create a new GraphData with the provided config.
+    /// Create an empty GraphData with the provided config.
     pub fn new(config: GraphConfig) -> Self {
-        let mid = (config.y_range.0 + config.y_range.1) / 2.0;
-        // pre-fill sliding window with points at the midpoint
-        let points: VecDeque<_> = (0..config.data_window).map(|x| (x as f64, mid)).collect();
-        let data_vec = points.iter().copied().collect();
-        let history = points.clone();
         Self {
-            points,
-            data_vec,
-            history,
+            points: VecDeque::new(),
+            data_vec: Vec::new(),
+            history: VecDeque::new(),
             config,
         }
     }
 
-    /// Push a new point into the sliding window and bounded history.
+    /// Push a new point at time `x` into the sliding window and bounded history.
     ///
-    /// Maintains invariant: points.len() <= config.data_window, history.len() <= config.max_history.
+    /// Maintains invariant: points cover at most `config.data_window` (and never
+    /// more than `config.max_history` samples), history.len() <= config.max_history.
     pub fn push_point(&mut self, x: f64, y: f64) {
-        if self.points.len() == self.config.data_window {
-            // drop oldest visible point
-            self.points.pop_front();
-        }
         self.points.push_back((x, y));
-
-        // keep an owned vector for chart lifetimes
-        self.data_vec.clear();
-        self.data_vec.extend(self.points.iter().copied());
+        self.trim_window(x);
 
         // append to history and bound it
         self.history.push_back((x, y));
@@ -60,15 +52,106 @@ impl GraphData {
         }
     }
 
-    /// x bounds of the current sliding window (first, last)
+    /// Change the live window and refill it from the stored history.
+    pub fn set_window(&mut self, window: DataWindow) {
+        self.config.data_window = window;
+        self.points = self.history.clone();
+        if let Some(&(x, _)) = self.history.back() {
+            self.trim_window(x);
+        } else {
+            self.data_vec.clear();
+        }
+    }
+
+    /// Drop window points that fall out of the window ending at `x`.
+    fn trim_window(&mut self, x: f64) {
+        match self.config.data_window {
+            DataWindow::Samples(n) => {
+                while self.points.len() > n {
+                    self.points.pop_front();
+                }
+            }
+            DataWindow::Seconds(span) => {
+                while self.points.front().is_some_and(|p| p.0 < x - span)
+                    || self.points.len() > self.config.max_history
+                {
+                    self.points.pop_front();
+                }
+            }
+        }
+
+        // keep an owned vector for chart lifetimes
+        self.data_vec.clear();
+        self.data_vec.extend(self.points.iter().copied());
+    }
+
+    /// x bounds of the current sliding window (first, last).
+    ///
+    /// A duration window always spans its full length ending at the newest
+    /// sample, so the axis scrolls steadily even across gaps.
     pub fn x_bounds(&self) -> (f64, f64) {
-        let first = self.points.front().map(|p| p.0).unwrap_or(0.0);
-        let last = self
-            .points
-            .back()
-            .map(|p| p.0)
-            .unwrap_or(first + self.points.len() as f64);
-        (first, last)
+        let last = self.points.back().map(|p| p.0).unwrap_or(0.0);
+        match self.config.data_window {
+            DataWindow::Seconds(span) => {
+                // until a full span has been received, fill from the left
+                let origin = self.history.front().map(|p| p.0).unwrap_or(0.0);
+                if last - span < origin {
+                    (origin, origin + span)
+                } else {
+                    (last - span, last)
+                }
+            }
+            DataWindow::Samples(_) => {
+                let first = self.points.front().map(|p| p.0).unwrap_or(0.0);
+                // keep a non-empty range for a single sample
+                (first, last.max(first + 1.0))
+            }
+        }
+    }
+
+    /// x extent of the stored history (oldest, newest).
+    pub fn history_bounds(&self) -> Option<(f64, f64)> {
+        Some((self.history.front()?.0, self.history.back()?.0))
+    }
+
+    /// History points within `[x0, x1]`, plus the neighbour on each side so
+    /// lines run to the chart edges.
+    pub fn history_range(&self, x0: f64, x1: f64) -> Vec<(f64, f64)> {
+        let start = self.history.partition_point(|p| p.0 < x0).saturating_sub(1);
+        let end = (self.history.partition_point(|p| p.0 <= x1) + 1).min(self.history.len());
+        self.history.range(start..end).copied().collect()
+    }
+
+    /// History sample closest in time to `x`.
+    pub fn nearest(&self, x: f64) -> Option<(f64, f64)> {
+        let i = self.history.partition_point(|p| p.0 < x);
+        let after = self.history.get(i);
+        let before = i.checked_sub(1).and_then(|j| self.history.get(j));
+        match (before, after) {
+            (Some(b), Some(a)) => Some(if x - b.0 <= a.0 - x { *b } else { *a }),
+            (b, a) => b.or(a).copied(),
+        }
+    }
+
+    /// Time of the history sample `steps` samples away from the one nearest
+    /// to `x` (clamped to the stored history).
+    pub fn step_from(&self, x: f64, steps: isize) -> Option<f64> {
+        let (nx, _) = self.nearest(x)?;
+        let i = self.history.partition_point(|p| p.0 < nx);
+        let j = i.saturating_add_signed(steps).min(self.history.len() - 1);
+        self.history.get(j).map(|p| p.0)
+    }
+
+    /// Replace every stored value with `f(x, y)`; points for which `f` returns
+    /// `None` keep their value.
+    pub fn recompute(&mut self, mut f: impl FnMut(f64, f64) -> Option<f64>) {
+        for p in self.history.iter_mut().chain(self.points.iter_mut()) {
+            if let Some(y) = f(p.0, p.1) {
+                p.1 = y;
+            }
+        }
+        self.data_vec.clear();
+        self.data_vec.extend(self.points.iter().copied());
     }
 
     /// (min, max, last) computed over the visible data_vec.
@@ -1,13 +1,22 @@
 //! src/graph/shared.rs
 //!
-//! Shared per-graph state: view-state (autoscale/hysteresis), locking, and metadata.
+//! Shared per-graph state: view-state (autoscale/hysteresis), locking, metadata,
+//! and when the channel last received a sample (stale-data watchdog).
 
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
 use super::config::GraphConfig;
 use super::data::GraphData;
+use ratatui::layout::Rect;
 use ratatui::style::Color;
 
+/// Narrowest X range a frozen view can be zoomed to, seconds.
+const MIN_FROZEN_SPAN: f64 = 1.0;
+
+/// Silence after which a channel counts as stale, unless configured.
+const DEFAULT_STALE_AFTER: Duration = Duration::from_secs(5);
+
 /// Stabilization state for view hysteresis.
 #[derive(Clone, Copy, Debug)]
 pub enum StabilizationState {
@@ -27,6 +36,9 @@ pub struct GraphViewState {
 
     /// Current stabilization phase.
     pub state: StabilizationState,
+
+    /// Screen area of the plot (inside axes) at the last draw, for mouse hits.
+    pub plot_area: Option<Rect>,
 }
 
 impl GraphViewState {
@@ -36,6 +48,7 @@ impl GraphViewState {
             current_bounds: None,
             stable_count: 0,
             state: StabilizationState::Stable,
+            plot_area: None,
         }
     }
 }
@@ -45,10 +58,19 @@ pub struct GraphShared {
     pub data: GraphData,
     pub view: GraphViewState,
     pub name: String,
+    pub unit: String,
     pub color: Color,
     pub autoscale: bool,
     pub smoothing: f64,
     pub locked_bounds: Option<(f64, f64)>,
+    /// X range shown instead of the live window while the view is frozen.
+    pub frozen: Option<(f64, f64)>,
+    /// Inspection cursor time (same instant on every graph).
+    pub cursor: Option<f64>,
+    /// Wall-clock arrival of the latest sample.
+    pub last_rx: Option<Instant>,
+    /// Silence after which the channel is marked stale.
+    pub stale_after: Duration,
     pub shrink_confirm_frames: usize,
     pub shrink_margin_frac: f64,
 }
@@ -58,6 +80,7 @@ impl GraphShared {
     pub fn new(
         cfg: GraphConfig,
         name: &str,
+        unit: &str,
         color: Color,
         autoscale: bool,
         smoothing: f64,
@@ -66,14 +89,119 @@ impl GraphShared {
             data: GraphData::new(cfg.clone()),
             view: GraphViewState::new(),
             name: name.to_string(),
+            unit: unit.to_string(),
             color,
             autoscale,
             smoothing: smoothing.clamp(0.0, 1.0),
             locked_bounds: None,
+            frozen: None,
+            cursor: None,
+            last_rx: None,
+            stale_after: DEFAULT_STALE_AFTER,
             shrink_confirm_frames: 8,
             shrink_margin_frac: 0.20,
         }
     }
+
+    /// Set the silence after which the channel is marked stale.
+    pub fn with_stale_after(mut self, stale_after: Duration) -> Self {
+        self.stale_after = stale_after;
+        self
+    }
+
+    /// Drop all samples and view state, keeping the graph's settings.
+    pub fn reset(&mut self) {
+        self.data = GraphData::new(self.data.config.clone());
+        self.view = GraphViewState::new();
+        self.last_rx = None;
+    }
+
+    /// Store a sample received just now at mission time `x`.
+    pub fn push(&mut self, x: f64, y: f64) {
+        self.data.push_point(x, y);
+        self.last_rx = Some(Instant::now());
+    }
+
+    /// Time since the latest sample arrived (`None` before the first one).
+    pub fn silence(&self) -> Option<Duration> {
+        self.last_rx.map(|t| t.elapsed())
+    }
+
+    /// Time since the latest sample, once it exceeds `stale_after`.
+    pub fn stale(&self) -> Option<Duration> {
+        self.silence().filter(|s| *s > self.stale_after)
+    }
+
+    /// Freeze the view at the current live window (ingestion continues).
+    pub fn freeze(&mut self) {
+        if self.frozen.is_none() {
+            self.frozen = Some(self.data.x_bounds());
+        }
+    }
+
+    /// Return to the live window.
+    pub fn unfreeze(&mut self) {
+        self.frozen = None;
+    }
+
+    /// Shift a frozen view by `frac` of its width (negative = back in time),
+    /// staying within the stored history.
+    pub fn pan(&mut self, frac: f64) {
+        let (Some((x0, x1)), Some((h0, h1))) = (self.frozen, self.data.history_bounds()) else {
+            return;
+        };
+        let width = x1 - x0;
+        let start = (x0 + width * frac).min(h1 - width).max(h0.min(x0));
+        self.frozen = Some((start, start + width));
+    }
+
+    /// Scale a frozen view's width by `factor` around its centre
+    /// (`< 1` zooms in), between `MIN_FROZEN_SPAN` and the history extent.
+    pub fn zoom(&mut self, factor: f64) {
+        let (Some((x0, x1)), Some((h0, h1))) = (self.frozen, self.data.history_bounds()) else {
+            return;
+        };
+        let max_width = (h1 - h0).max(x1 - x0);
+        let width = ((x1 - x0) * factor).clamp(MIN_FROZEN_SPAN.min(max_width), max_width);
+        let center = (x0 + x1) / 2.0;
+        self.frozen = Some((center - width / 2.0, center + width / 2.0));
+    }
+
+    /// Shift a frozen view just enough to bring `x` into it.
+    pub fn reveal(&mut self, x: f64) {
+        if let Some((x0, x1)) = self.frozen {
+            let shift = if x < x0 {
+                x - x0
+            } else if x > x1 {
+                x - x1
+            } else {
+                0.0
+            };
+            self.frozen = Some((x0 + shift, x1 + shift));
+        }
+    }
+
+    /// Time at screen column `col` of the last drawn plot, if inside it.
+    pub fn time_at_column(&self, col: u16, row: u16) -> Option<f64> {
+        let area = self.view.plot_area?;
+        let inside = (area.x..area.x + area.width).contains(&col)
+            && (area.y..area.y + area.height).contains(&row);
+        if !inside || area.width < 2 {
+            return None;
+        }
+        let (x0, x1) = self.frozen.unwrap_or_else(|| self.data.x_bounds());
+        let frac = (col - area.x) as f64 / (area.width - 1) as f64;
+        Some(x0 + frac * (x1 - x0))
+    }
+
+    /// Display title: `name (unit)`, or just `name` when unitless.
+    pub fn label(&self) -> String {
+        if self.unit.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{} ({})", self.name, self.unit)
+        }
+    }
 }
 
 /// Alias: Arc<RwLock<GraphShared>>
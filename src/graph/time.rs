@@ -0,0 +1,20 @@
+//! src/graph/time.rs
+//!
+//! Formatting of graph x values (seconds since the mission time origin).
+
+/// `T+mm:ss` (or `T+h:mm:ss` past an hour) with `decimals` fractional digits.
+pub fn mission_time(t: f64, decimals: usize) -> String {
+    let sign = if t < 0.0 { '-' } else { '+' };
+    let t = t.abs();
+    let scale = 10f64.powi(decimals as i32);
+    let t = (t * scale).round() / scale;
+    let whole = t.trunc() as u64;
+    let (h, m) = (whole / 3600, (whole / 60) % 60);
+    let sec = t - (whole - whole % 60) as f64;
+    let width = if decimals == 0 { 2 } else { decimals + 3 };
+    if h > 0 {
+        format!("T{}{}:{:02}:{:0width$.decimals$}", sign, h, m, sec)
+    } else {
+        format!("T{}{:02}:{:0width$.decimals$}", sign, m, sec)
+    }
+}
@@ -1,13 +1,28 @@
 //! src/main.rs
 //!
-//! Entrypoint delegating to `app::run()`.
+//! Entrypoint: parses the command line and delegates to `app::run()`.
+
+use clap::Parser;
 
 mod app;
+mod cli;
+mod events;
 mod graph;
 mod net;
 mod panels;
+mod schema;
+mod telemetry;
 mod ui;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    app::run()
+fn main() {
+    let cli = cli::Cli::parse();
+    let result = if cli.list_ports {
+        cli::list_ports()
+    } else {
+        app::run(cli)
+    };
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
 }
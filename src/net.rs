@@ -2,4 +2,5 @@
 //!
 //! Network module container.
 
+pub mod protocol;
 pub mod remote;
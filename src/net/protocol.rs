@@ -0,0 +1,183 @@
+//! src/net/protocol.rs
+//!
+//! Wire formats of the remote control server.
+//!
+//! Commands produce a `Reply` or a typed `CmdError`; the client's protocol
+//! decides how they are written:
+//!
+//! - **text** (default): `OK [key=value ...]` or `ERR <message>`, one line
+//!   per command.
+//! - **json** (after `hello json`, or on the `--json-bind` port): one JSON
+//!   object per line. Requests are `{"id": <any>, "cmd": "<command>",
+//!   "args": [...]}` (`args` optional, appended to `cmd`); replies echo the
+//!   id: `{"type":"reply","id":..,"ok":true,"result":{..}}` or
+//!   `{"type":"reply","id":..,"ok":false,"error":{"code":..,"message":..}}`.
+
+use serde_json::{Map, Value, json};
+
+/// Protocol spoken with one client.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Proto {
+    Text,
+    Json,
+}
+
+impl Proto {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Proto::Text => "text",
+            Proto::Json => "json",
+        }
+    }
+}
+
+/// Successful command result.
+pub struct Reply {
+    /// Text after `OK` (may be empty).
+    pub text: String,
+    /// Structured result for JSON clients.
+    pub data: Value,
+}
+
+impl Reply {
+    /// Reply with an explicit text and JSON form.
+    pub fn new(text: String, data: Value) -> Self {
+        Self { text, data }
+    }
+
+    /// Reply whose JSON form is the `key=value` pairs of `text`.
+    pub fn fields(text: String) -> Self {
+        let data = fields(&text);
+        Self { text, data }
+    }
+}
+
+/// Error categories, the `code` of JSON errors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Malformed JSON request.
+    BadRequest,
+    /// Unknown command or wrong number of arguments.
+    UnknownCommand,
+    /// An argument does not parse.
+    BadArgument,
+    /// No graph with that index or name.
+    NoGraph,
+    /// Valid request the application refuses in its current state.
+    Rejected,
+}
+
+impl ErrorKind {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorKind::BadRequest => "bad_request",
+            ErrorKind::UnknownCommand => "unknown_command",
+            ErrorKind::BadArgument => "bad_argument",
+            ErrorKind::NoGraph => "no_graph",
+            ErrorKind::Rejected => "rejected",
+        }
+    }
+}
+
+/// Failed command; `message` is what text clients get after `ERR`.
+#[derive(Debug)]
+pub struct CmdError {
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+impl CmdError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    pub fn no_graph(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::NoGraph, message)
+    }
+
+    pub fn rejected(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Rejected, message)
+    }
+}
+
+/// One decoded JSON request.
+pub struct Request {
+    pub id: Value,
+    /// Command line equivalent to the text protocol's.
+    pub line: String,
+}
+
+/// Decode a JSON request line. On failure, returns the id (when it could be
+/// read) with the error.
+pub fn parse_request(raw: &str) -> Result<Request, (Value, CmdError)> {
+    let bad = |id: Value, msg: String| (id, CmdError::new(ErrorKind::BadRequest, msg));
+    let v: Value = serde_json::from_str(raw).map_err(|e| bad(Value::Null, e.to_string()))?;
+    let Value::Object(obj) = v else {
+        return Err(bad(Value::Null, "request must be an object".into()));
+    };
+    let id = obj.get("id").cloned().unwrap_or(Value::Null);
+    let Some(Value::String(cmd)) = obj.get("cmd") else {
+        return Err(bad(id, "missing \"cmd\" string".into()));
+    };
+    let mut line = cmd.clone();
+    match obj.get("args") {
+        None | Some(Value::Null) => {}
+        Some(Value::Array(args)) => {
+            for a in args {
+                match a {
+                    Value::String(s) => line.push_str(&format!(" {}", s)),
+                    Value::Number(_) | Value::Bool(_) => line.push_str(&format!(" {}", a)),
+                    _ => return Err(bad(id, "args must be strings, numbers or booleans".into())),
+                }
+            }
+        }
+        Some(_) => return Err(bad(id, "\"args\" must be an array".into())),
+    }
+    Ok(Request { id, line })
+}
+
+/// Encode the outcome of a command for `proto` (with the request id for
+/// JSON clients), newline included.
+pub fn encode(proto: Proto, id: &Value, result: &Result<Reply, CmdError>) -> String {
+    match (proto, result) {
+        (Proto::Text, Ok(r)) if r.text.is_empty() => "OK\n".to_string(),
+        (Proto::Text, Ok(r)) => format!("OK {}\n", r.text),
+        (Proto::Text, Err(e)) => format!("ERR {}\n", e.message),
+        (Proto::Json, Ok(r)) => format!(
+            "{}\n",
+            json!({"type": "reply", "id": id, "ok": true, "result": r.data})
+        ),
+        (Proto::Json, Err(e)) => format!(
+            "{}\n",
+            json!({
+                "type": "reply",
+                "id": id,
+                "ok": false,
+                "error": {"code": e.kind.code(), "message": e.message},
+            })
+        ),
+    }
+}
+
+/// JSON object from `key=value` pairs: numbers, booleans and `none` (null)
+/// are typed, anything else stays a string.
+pub fn fields(text: &str) -> Value {
+    let mut map = Map::new();
+    for (k, v) in text.split_whitespace().filter_map(|kv| kv.split_once('=')) {
+        let value = match v {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            "none" => Value::Null,
+            _ => match (v.parse::<i64>(), v.parse::<f64>()) {
+                (Ok(n), _) => json!(n),
+                (_, Ok(n)) => json!(n),
+                _ => Value::String(v.to_string()),
+            },
+        };
+        map.insert(k.to_string(), value);
+    }
+    Value::Object(map)
+}
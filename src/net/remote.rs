@@ -1,21 +1,90 @@
 //! src/net/remote.rs
 //!
-//! Tiny line-based TCP control server for remote bindings.
+//! Tiny line-based TCP control server for remote bindings, speaking plain
+//! text or JSON lines (see `protocol`).
 
 use std::io::{BufRead, BufReader, Write};
 use std::net::{Shutdown, TcpListener, TcpStream};
-use std::sync::Arc;
+use std::str::FromStr;
+use std::sync::{Arc, mpsc};
 use std::thread;
 
+use ratatui::style::Color;
+use serde_json::{Map, Value, json};
+
+use super::protocol::{CmdError, ErrorKind, Proto, Reply, encode, fields, parse_request};
+use crate::events::{Level, SharedLog, log};
+use crate::graph::config::DataWindow;
 use crate::graph::shared::{GraphGuard, SharedGraph};
+use crate::telemetry::Pipeline;
+use crate::telemetry::altimeter::{SharedAltimeter, ZERO_SECONDS};
+use crate::telemetry::diag::SharedDiagnostics;
+use crate::telemetry::feed::{FeedFormat, SharedFeed};
+use crate::telemetry::quality::SharedQuality;
+use crate::telemetry::replay::SharedReplay;
+use crate::telemetry::uplink::{SharedUplink, UplinkState};
+
+/// Requests for state owned by the UI loop, applied on its next frame.
+#[derive(Debug)]
+pub enum UiCommand {
+    /// Focus graph `idx`.
+    Focus(usize),
+    /// Quit the application.
+    Shutdown,
+}
+
+/// Shared state reachable from remote commands.
+pub struct RemoteContext {
+    pub graphs: Vec<SharedGraph>,
+    pub events: SharedLog,
+    pub quality: SharedQuality,
+    /// Parser error counters (`parse`).
+    pub diagnostics: SharedDiagnostics,
+    pub altimeter: SharedAltimeter,
+    /// Sample feed for `subscribe`.
+    pub feed: SharedFeed,
+    /// Handle for commands that act on the ingest side (altitude baseline).
+    pub pipeline: Pipeline,
+    /// Replay transport, when replaying a file (`pause`/`resume`).
+    pub replay: Option<SharedReplay>,
+    /// Command queue to the CanSat (`send`).
+    pub uplink: SharedUplink,
+    /// Graphs that have a region on screen and can take the focus.
+    pub shown: Vec<usize>,
+    /// Requests for the UI loop (`focus`, `shutdown`).
+    pub ui: mpsc::Sender<UiCommand>,
+}
+
+/// Lines queued per client (replies and pushed samples); a subscriber that
+/// falls further behind loses samples.
+const CLIENT_QUEUE: usize = 4096;
+
+/// History points returned by `get` by default, and at most.
+const GET_POINTS: usize = 10;
+const GET_MAX_POINTS: usize = 1000;
 
-/// Start the remote TCP server and spawn a handler thread per client.
-pub fn remote_server(addr: &str, graphs: Vec<SharedGraph>) {
-    let graphs = Arc::new(graphs);
+/// Commands listed by `uplink`, newest first.
+const UPLINK_HISTORY: usize = 10;
+
+/// Start a remote TCP server whose clients start out speaking `proto`, and
+/// spawn a handler thread per client.
+pub fn remote_server(addr: &str, ctx: Arc<RemoteContext>, proto: Proto) {
+    let events = ctx.events.clone();
     let listener = match TcpListener::bind(addr) {
-        Ok(l) => l,
+        Ok(l) => {
+            log(
+                &events,
+                Level::Info,
+                format!("remote control ({}) listening on {}", proto.as_str(), addr),
+            );
+            l
+        }
         Err(e) => {
-            eprintln!("remote_server: bind error {} on {}", e, addr);
+            log(
+                &events,
+                Level::Error,
+                format!("remote_server: bind error {} on {}", e, addr),
+            );
             return;
         }
     };
@@ -23,25 +92,55 @@ pub fn remote_server(addr: &str, graphs: Vec<SharedGraph>) {
     for stream in listener.incoming() {
         match stream {
             Ok(s) => {
-                let g = graphs.clone();
-                thread::spawn(move || handle_remote_client(s, g));
+                let c = ctx.clone();
+                thread::spawn(move || handle_remote_client(s, c, proto));
             }
             Err(e) => {
-                eprintln!("remote_server: accept error: {}", e);
+                log(
+                    &events,
+                    Level::Warn,
+                    format!("remote_server: accept error: {}", e),
+                );
             }
         }
     }
 }
 
-/// Handle a single client; simple whitespace-split ASCII commands.
+/// Handle a single client speaking `proto` (text clients may switch with
+/// `hello json`).
 ///
 /// Commands:
+/// - `hello [text|json]`
+/// - `list`
 /// - `toggle autoscale <idx>`
 /// - `set smoothing <idx> <val>`
+/// - `set bounds <idx> <min> <max>`
+/// - `set window <idx> <n|duration>`
+/// - `set color <idx> <color>`
 /// - `lock <idx>`
 /// - `unlock <idx>`
+/// - `focus <idx>`
+/// - `clear <idx|all>`
+/// - `pause`
+/// - `resume`
+/// - `stats`
+/// - `stale [idx]`
+/// - `set qnh <hPa>`
+/// - `zero [seconds]`
+/// - `baseline`
+/// - `subscribe <idx|name|all> [text|json]`
+/// - `unsubscribe [idx|name|all]`
+/// - `get <idx|name> [n]`
+/// - `send <text>`
+/// - `uplink`
+/// - `shutdown`
 /// - `quit`
-pub fn handle_remote_client(mut s: TcpStream, graphs: Arc<Vec<SharedGraph>>) {
+///
+/// Every `<idx>` may also be a graph name (case-insensitive, spaces allowed).
+///
+/// Replies and pushed samples share one queue drained by a writer thread, so
+/// a reply is never interleaved with a half-written sample line.
+pub fn handle_remote_client(s: TcpStream, ctx: Arc<RemoteContext>, mut proto: Proto) {
     let _peer = s
         .peer_addr()
         .map(|a| a.to_string())
@@ -49,6 +148,17 @@ pub fn handle_remote_client(mut s: TcpStream, graphs: Arc<Vec<SharedGraph>>) {
     let mut rdr = BufReader::new(s.try_clone().unwrap());
     let mut line = String::new();
 
+    let (tx, rx) = mpsc::sync_channel::<String>(CLIENT_QUEUE);
+    let mut out = s.try_clone().unwrap();
+    let writer = thread::spawn(move || {
+        for l in rx {
+            if out.write_all(l.as_bytes()).is_err() {
+                break;
+            }
+        }
+    });
+    let client = ctx.feed.write().unwrap().connect(tx.clone());
+
     loop {
         line.clear();
         if rdr.read_line(&mut line).is_err() {
@@ -61,89 +171,528 @@ pub fn handle_remote_client(mut s: TcpStream, graphs: Arc<Vec<SharedGraph>>) {
         if raw.is_empty() {
             continue;
         }
-        let parts: Vec<_> = raw.split_whitespace().collect();
+        let (id, cmd) = match proto {
+            Proto::Text => (Value::Null, raw.to_string()),
+            Proto::Json => match parse_request(raw) {
+                Ok(req) => (req.id, req.line),
+                Err((id, e)) => {
+                    let _ = tx.send(encode(proto, &id, &Err(e)));
+                    continue;
+                }
+            },
+        };
+        let parts: Vec<_> = cmd.split_whitespace().collect();
         if parts.is_empty() {
-            let _ = s.write_all(b"ERR empty\n");
+            let e = CmdError::new(ErrorKind::UnknownCommand, "empty");
+            let _ = tx.send(encode(proto, &id, &Err(e)));
             continue;
         }
 
-        let mut reply = "OK\n".to_string();
         match parts[0].to_lowercase().as_str() {
-            "toggle" if parts.len() == 3 && parts[1].eq_ignore_ascii_case("autoscale") => {
-                if let Ok(idx) = parts[2].parse::<usize>() {
-                    if let Some(gs) = graphs.get(idx) {
-                        let mut g: GraphGuard<'_> = gs.write().unwrap();
-                        g.autoscale = !g.autoscale;
-                        if g.autoscale {
-                            g.locked_bounds = None;
-                        }
-                    } else {
-                        reply = format!("ERR no graph {}\n", idx);
-                    }
-                } else {
-                    reply = "ERR idx\n".to_string();
+            "hello" if parts.len() <= 2 => {
+                let next = match parts.get(1).map(|p| p.to_lowercase()) {
+                    None => Some(proto),
+                    Some(p) if p == "text" => Some(Proto::Text),
+                    Some(p) if p == "json" => Some(Proto::Json),
+                    Some(_) => None,
+                };
+                let result = match next {
+                    Some(next) => Ok(Reply::fields(format!("proto={}", next.as_str()))),
+                    None => Err(CmdError::new(ErrorKind::BadArgument, "proto")),
+                };
+                // the reply still uses the old protocol
+                let _ = tx.send(encode(proto, &id, &result));
+                proto = next.unwrap_or(proto);
+            }
+            "quit" => {
+                let _ = tx.send(encode(proto, &id, &Ok(Reply::fields("bye".into()))));
+                break;
+            }
+            _ => {
+                let result = execute(&ctx, client, proto, &parts);
+                if tx.send(encode(proto, &id, &result)).is_err() {
+                    break;
                 }
             }
+        }
+    }
+
+    // the feed holds a sender too; the writer stops once both are gone
+    ctx.feed.write().unwrap().disconnect(client);
+    drop(tx);
+    let _ = writer.join();
+    let _ = s.shutdown(Shutdown::Both);
+}
+
+/// Run one command for feed client `client`.
+fn execute(
+    ctx: &RemoteContext,
+    client: u64,
+    proto: Proto,
+    parts: &[&str],
+) -> Result<Reply, CmdError> {
+    let graphs = &ctx.graphs;
+    match parts[0].to_lowercase().as_str() {
+        "list" if parts.len() == 1 => {
+            let mut text = Vec::new();
+            let mut data = Vec::new();
+            for (idx, gs) in graphs.iter().enumerate() {
+                let g = gs.read().unwrap();
+                let bounds = g.view.current_bounds;
+                let range = |b: Option<(f64, f64)>| {
+                    b.map_or("none".to_string(), |(lo, hi)| format!("{}..{}", lo, hi))
+                };
+                text.push(format!(
+                    "{} name={:?} unit={:?} color={} bounds={} autoscale={} smoothing={} locked={}",
+                    idx,
+                    g.name,
+                    g.unit,
+                    g.color,
+                    range(bounds),
+                    g.autoscale,
+                    g.smoothing,
+                    range(g.locked_bounds)
+                ));
+                data.push(json!({
+                    "idx": idx,
+                    "name": g.name,
+                    "unit": g.unit,
+                    "color": g.color.to_string(),
+                    "bounds": bounds.map(|(lo, hi)| [lo, hi]),
+                    "autoscale": g.autoscale,
+                    "smoothing": g.smoothing,
+                    "locked": g.locked_bounds.map(|(lo, hi)| [lo, hi]),
+                }));
+            }
+            Ok(Reply::new(text.join(" | "), json!({ "graphs": data })))
+        }
+
+        "toggle" if parts.len() >= 3 && parts[1].eq_ignore_ascii_case("autoscale") => {
+            let idx = graph_arg(graphs, &parts[2..])?;
+            let mut g: GraphGuard<'_> = graphs[idx].write().unwrap();
+            g.autoscale = !g.autoscale;
+            if g.autoscale {
+                g.locked_bounds = None;
+            }
+            Ok(Reply::new(
+                String::new(),
+                json!({"idx": idx, "autoscale": g.autoscale}),
+            ))
+        }
+
+        "set" if parts.len() >= 4 && parts[1].eq_ignore_ascii_case("smoothing") => {
+            let (target, val) = parts[2..].split_at(parts.len() - 3);
+            let idx = graph_arg(graphs, target)?;
+            let val = value_arg(val[0])?;
+            let mut g: GraphGuard<'_> = graphs[idx].write().unwrap();
+            g.smoothing = val.clamp(0.0, 1.0);
+            Ok(Reply::new(
+                String::new(),
+                json!({"idx": idx, "smoothing": g.smoothing}),
+            ))
+        }
+
+        "set" if parts.len() >= 5 && parts[1].eq_ignore_ascii_case("bounds") => {
+            let (target, vals) = parts[2..].split_at(parts.len() - 4);
+            let idx = graph_arg(graphs, target)?;
+            let (lo, hi) = (value_arg(vals[0])?, value_arg(vals[1])?);
+            if !(lo.is_finite() && hi.is_finite() && lo < hi) {
+                return Err(CmdError::new(
+                    ErrorKind::BadArgument,
+                    "min must be below max",
+                ));
+            }
+            graphs[idx].write().unwrap().locked_bounds = Some((lo, hi));
+            Ok(Reply::new(
+                String::new(),
+                json!({"idx": idx, "locked": [lo, hi]}),
+            ))
+        }
+
+        "set" if parts.len() >= 4 && parts[1].eq_ignore_ascii_case("window") => {
+            let (target, val) = parts[2..].split_at(parts.len() - 3);
+            let idx = graph_arg(graphs, target)?;
+            let window = DataWindow::from_str(val[0])
+                .map_err(|e| CmdError::new(ErrorKind::BadArgument, e))?;
+            graphs[idx].write().unwrap().data.set_window(window);
+            Ok(Reply::new(
+                String::new(),
+                json!({"idx": idx, "window": window.label()}),
+            ))
+        }
+
+        "set" if parts.len() >= 4 && parts[1].eq_ignore_ascii_case("color") => {
+            let (target, val) = parts[2..].split_at(parts.len() - 3);
+            let idx = graph_arg(graphs, target)?;
+            let color = Color::from_str(val[0]).map_err(|_| {
+                CmdError::new(ErrorKind::BadArgument, format!("unknown color {}", val[0]))
+            })?;
+            graphs[idx].write().unwrap().color = color;
+            Ok(Reply::new(
+                String::new(),
+                json!({"idx": idx, "color": color.to_string()}),
+            ))
+        }
+
+        "lock" if parts.len() >= 2 => {
+            let idx = graph_arg(graphs, &parts[1..])?;
+            let mut g: GraphGuard<'_> = graphs[idx].write().unwrap();
+            let (lo, hi) = g
+                .view
+                .current_bounds
+                .ok_or_else(|| CmdError::rejected("no_bounds"))?;
+            g.locked_bounds = Some((lo, hi));
+            Ok(Reply::new(
+                String::new(),
+                json!({"idx": idx, "locked": [lo, hi]}),
+            ))
+        }
+
+        "unlock" if parts.len() >= 2 => {
+            let idx = graph_arg(graphs, &parts[1..])?;
+            graphs[idx].write().unwrap().locked_bounds = None;
+            Ok(Reply::new(
+                String::new(),
+                json!({"idx": idx, "locked": null}),
+            ))
+        }
+
+        "focus" if parts.len() >= 2 => {
+            let idx = graph_arg(graphs, &parts[1..])?;
+            if !ctx.shown.contains(&idx) {
+                return Err(CmdError::rejected(format!("graph {} is not shown", idx)));
+            }
+            ctx.ui
+                .send(UiCommand::Focus(idx))
+                .map_err(|_| CmdError::rejected("ui closed"))?;
+            Ok(Reply::new(String::new(), json!({ "focused": idx })))
+        }
 
-            "set" if parts.len() == 4 && parts[1].eq_ignore_ascii_case("smoothing") => {
-                if let Ok(idx) = parts[2].parse::<usize>() {
-                    if let Ok(val) = parts[3].parse::<f64>() {
-                        if let Some(gs) = graphs.get(idx) {
-                            let mut g: GraphGuard<'_> = gs.write().unwrap();
-                            g.smoothing = val.clamp(0.0, 1.0);
-                        } else {
-                            reply = format!("ERR no graph {}\n", idx);
-                        }
-                    } else {
-                        reply = "ERR val\n".to_string();
-                    }
+        "clear" if parts.len() >= 2 => {
+            let channel = channel_arg(graphs, &parts[1..].join(" ")).map_err(CmdError::no_graph)?;
+            let cleared: Vec<usize> = match channel {
+                Some(idx) => vec![idx],
+                None => (0..graphs.len()).collect(),
+            };
+            for &idx in &cleared {
+                graphs[idx].write().unwrap().reset();
+            }
+            log(
+                &ctx.events,
+                Level::Info,
+                format!("remote: cleared the history of {} graph(s)", cleared.len()),
+            );
+            Ok(Reply::new(String::new(), json!({ "cleared": cleared })))
+        }
+
+        "pause" | "resume" if parts.len() == 1 => {
+            let pause = parts[0].eq_ignore_ascii_case("pause");
+            for g in graphs {
+                let mut g = g.write().unwrap();
+                if pause {
+                    g.freeze();
                 } else {
-                    reply = "ERR idx\n".to_string();
+                    g.unfreeze();
+                    g.cursor = None;
                 }
             }
+            if let Some(r) = &ctx.replay {
+                r.write().unwrap().paused = pause;
+            }
+            Ok(Reply::new(String::new(), json!({ "paused": pause })))
+        }
 
-            "lock" if parts.len() == 2 => {
-                if let Ok(idx) = parts[1].parse::<usize>() {
-                    if let Some(gs) = graphs.get(idx) {
-                        let mut g: GraphGuard<'_> = gs.write().unwrap();
-                        if let Some(cb) = g.view.current_bounds {
-                            g.locked_bounds = Some(cb);
-                        } else {
-                            reply = "ERR no_bounds\n".to_string();
-                        }
-                    } else {
-                        reply = format!("ERR no graph {}\n", idx);
-                    }
-                } else {
-                    reply = "ERR idx\n".to_string();
+        "send" if parts.len() >= 2 => {
+            if ctx.replay.is_some() {
+                return Err(CmdError::rejected("no uplink while replaying"));
+            }
+            let text = parts[1..].join(" ");
+            let mut up = ctx.uplink.write().unwrap();
+            let id = up
+                .send(&text)
+                .map_err(|e| CmdError::new(ErrorKind::BadArgument, e))?;
+            let c = up.commands.iter().find(|c| c.id == id).unwrap();
+            log(
+                &ctx.events,
+                Level::Info,
+                format!("remote: uplink #{} queued: {}", id, text),
+            );
+            Ok(Reply::new(
+                format!("id={} state={} frame={}", id, c.state.as_str(), c.frame),
+                json!({"id": id, "state": c.state.as_str(), "frame": c.frame}),
+            ))
+        }
+
+        "uplink" if parts.len() == 1 => {
+            let up = ctx.uplink.read().unwrap();
+            let mut text = vec![format!(
+                "connected={} queued={}",
+                up.connected(),
+                up.queued()
+            )];
+            let mut data = Vec::new();
+            for c in up.commands.iter().rev().take(UPLINK_HISTORY) {
+                let packet = match c.state {
+                    UplinkState::Confirmed(n) => Some(n),
+                    _ => None,
+                };
+                let mut entry = format!("{} state={}", c.id, c.state.as_str());
+                if let Some(n) = packet {
+                    entry.push_str(&format!(" packet={}", n));
                 }
+                entry.push_str(&format!(" frame={}", c.frame));
+                text.push(entry);
+                data.push(json!({
+                    "id": c.id,
+                    "text": c.text,
+                    "frame": c.frame,
+                    "state": c.state.as_str(),
+                    "packet": packet,
+                }));
             }
+            Ok(Reply::new(
+                text.join(" | "),
+                json!({"connected": up.connected(), "queued": up.queued(), "commands": data}),
+            ))
+        }
 
-            "unlock" if parts.len() == 2 => {
-                if let Ok(idx) = parts[1].parse::<usize>() {
-                    if let Some(gs) = graphs.get(idx) {
-                        let mut g: GraphGuard<'_> = gs.write().unwrap();
-                        g.locked_bounds = None;
-                    } else {
-                        reply = format!("ERR no graph {}\n", idx);
-                    }
-                } else {
-                    reply = "ERR idx\n".to_string();
+        "shutdown" if parts.len() == 1 => {
+            log(&ctx.events, Level::Info, "remote: shutdown requested");
+            ctx.ui
+                .send(UiCommand::Shutdown)
+                .map_err(|_| CmdError::rejected("ui closed"))?;
+            Ok(Reply::new(String::new(), json!({ "shutdown": true })))
+        }
+
+        "stats" if parts.len() == 1 => {
+            let q = ctx.quality.read().unwrap();
+            Ok(Reply::fields(q.summary()))
+        }
+
+        "parse" if parts.len() == 1 => {
+            let d = ctx.diagnostics.read().unwrap();
+            let errors: Map<String, Value> = d
+                .field_errors
+                .iter()
+                .enumerate()
+                .filter(|(_, n)| **n > 0)
+                .map(|(idx, n)| (d.name(idx).to_string(), json!(n)))
+                .collect();
+            let last_bad = d.last_bad.as_ref().map(|b| {
+                json!({
+                    "time": b.time.to_rfc3339(),
+                    "reason": b.reason,
+                    "line": b.text,
+                })
+            });
+            let mut text = d.summary();
+            for (idx, n) in d.field_errors.iter().enumerate() {
+                if *n > 0 {
+                    text.push_str(&format!(" errors[{}]={}", idx, n));
                 }
             }
+            if let Some(b) = &d.last_bad {
+                text.push_str(&format!(" | last: {}: {}", b.reason, b.text));
+            }
+            let mut data = fields(&d.summary());
+            data["errors"] = Value::Object(errors);
+            data["last_bad"] = last_bad.into();
+            Ok(Reply::new(text, data))
+        }
 
-            "quit" => {
-                reply = "OK bye\n".to_string();
-                let _ = s.write_all(reply.as_bytes());
-                break;
+        "parse" if parts.len() == 3 && parts[1].eq_ignore_ascii_case("reject") => {
+            let reject = match parts[2].to_ascii_lowercase().as_str() {
+                "on" | "true" => true,
+                "off" | "false" => false,
+                _ => return Err(CmdError::new(ErrorKind::BadArgument, "on|off")),
+            };
+            ctx.diagnostics.write().unwrap().reject = reject;
+            log(
+                &ctx.events,
+                Level::Info,
+                format!(
+                    "remote: {} malformed packets",
+                    if reject { "rejecting" } else { "accepting" }
+                ),
+            );
+            Ok(Reply::fields(format!("reject={}", reject)))
+        }
+
+        "stale" if parts.len() == 1 => {
+            let mut text = Vec::new();
+            let mut data = Map::new();
+            for (idx, gs) in graphs.iter().enumerate() {
+                if let Some(s) = gs.read().unwrap().stale() {
+                    let secs = (s.as_secs_f64() * 10.0).round() / 10.0;
+                    text.push(format!("{}={:.1}", idx, secs));
+                    data.insert(idx.to_string(), json!(secs));
+                }
             }
+            Ok(Reply::new(text.join(" "), json!({ "stale": data })))
+        }
 
-            _ => {
-                reply = format!("ERR unknown {}\n", parts.join(" "));
+        "stale" if parts.len() >= 2 => {
+            let idx = graph_arg(graphs, &parts[1..])?;
+            let g = graphs[idx].read().unwrap();
+            let age = g
+                .silence()
+                .map_or("none".to_string(), |s| format!("{:.1}", s.as_secs_f64()));
+            Ok(Reply::fields(format!(
+                "idx={} stale={} age={} timeout={:.1}",
+                idx,
+                g.stale().is_some(),
+                age,
+                g.stale_after.as_secs_f64()
+            )))
+        }
+
+        "set" if parts.len() == 3 && parts[1].eq_ignore_ascii_case("qnh") => {
+            let qnh = value_arg(parts[2])?;
+            ctx.pipeline.set_qnh(qnh).map_err(CmdError::rejected)?;
+            log(
+                &ctx.events,
+                Level::Info,
+                format!("remote: altitude baseline set to QNH {:.2} hPa", qnh),
+            );
+            Ok(Reply::new(String::new(), json!({ "qnh": qnh })))
+        }
+
+        "zero" if parts.len() <= 2 => {
+            let sec = match parts.get(1) {
+                Some(v) => value_arg(v)?,
+                None => ZERO_SECONDS,
+            };
+            ctx.pipeline.zero_on_pad(sec).map_err(CmdError::rejected)?;
+            log(
+                &ctx.events,
+                Level::Info,
+                format!("remote: zeroing altitude on pad over {} s", sec),
+            );
+            Ok(Reply::new(String::new(), json!({ "seconds": sec })))
+        }
+
+        "baseline" if parts.len() == 1 => {
+            let a = ctx.altimeter.read().unwrap();
+            Ok(Reply::fields(a.summary()))
+        }
+
+        "subscribe" if parts.len() >= 2 => {
+            let mut args = &parts[1..];
+            let mut format = match proto {
+                Proto::Text => FeedFormat::Text,
+                Proto::Json => FeedFormat::Json,
+            };
+            if let [rest @ .., last] = args
+                && !rest.is_empty()
+            {
+                if last.eq_ignore_ascii_case("json") {
+                    format = FeedFormat::Json;
+                    args = rest;
+                } else if last.eq_ignore_ascii_case("text") {
+                    format = FeedFormat::Text;
+                    args = rest;
+                }
+            }
+            let channel = channel_arg(graphs, &args.join(" ")).map_err(CmdError::no_graph)?;
+            let mut feed = ctx.feed.write().unwrap();
+            feed.subscribe(client, channel, format);
+            let subs = feed.subscriptions(client);
+            let text = match &subs {
+                None => "all".to_string(),
+                Some(v) => v
+                    .iter()
+                    .map(|i| i.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            };
+            Ok(Reply::new(
+                format!("subscribed={} format={}", text, format.as_str()),
+                json!({
+                    "subscribed": subs.map_or(json!("all"), |v| json!(v)),
+                    "format": format.as_str(),
+                }),
+            ))
+        }
+
+        "unsubscribe" => {
+            let channel = if parts.len() == 1 {
+                None
+            } else {
+                channel_arg(graphs, &parts[1..].join(" ")).map_err(CmdError::no_graph)?
+            };
+            let dropped = ctx.feed.write().unwrap().unsubscribe(client, channel);
+            Ok(Reply::fields(format!("dropped={}", dropped)))
+        }
+
+        "get" if parts.len() >= 2 => {
+            let mut args = &parts[1..];
+            let mut n = GET_POINTS;
+            if let [rest @ .., last] = args
+                && !rest.is_empty()
+                && let Ok(v) = last.parse::<usize>()
+            {
+                n = v.min(GET_MAX_POINTS);
+                args = rest;
+            }
+            let idx = channel_arg(graphs, &args.join(" "))
+                .map_err(CmdError::no_graph)?
+                .ok_or_else(|| CmdError::new(ErrorKind::BadArgument, "get needs one channel"))?;
+            let g = graphs[idx].read().unwrap();
+            let mut text = format!("idx={} n={}", idx, g.data.history.len());
+            let mut data = json!({"idx": idx, "name": g.name, "n": g.data.history.len()});
+            if !g.data.data_vec.is_empty() {
+                let (mn, mx, last) = g.data.stats();
+                text.push_str(&format!(" min={} max={} last={}", mn, mx, last));
+                data["min"] = json!(mn);
+                data["max"] = json!(mx);
+                data["last"] = json!(last);
             }
+            let skip = g.data.history.len().saturating_sub(n);
+            let points: Vec<(f64, f64)> = g.data.history.iter().skip(skip).copied().collect();
+            let list: Vec<String> = points
+                .iter()
+                .map(|(t, y)| format!("{:.3}:{}", t, y))
+                .collect();
+            text.push_str(&format!(" points={}", list.join(",")));
+            data["points"] = json!(points);
+            Ok(Reply::new(text, data))
         }
-        let _ = s.write_all(reply.as_bytes());
+
+        _ => Err(CmdError::new(
+            ErrorKind::UnknownCommand,
+            format!("unknown {}", parts.join(" ")),
+        )),
     }
+}
 
-    let _ = s.shutdown(Shutdown::Both);
+/// Resolve the words of a single-graph argument (an index or a name).
+fn graph_arg(graphs: &[SharedGraph], words: &[&str]) -> Result<usize, CmdError> {
+    channel_arg(graphs, &words.join(" "))
+        .map_err(CmdError::no_graph)?
+        .ok_or_else(|| CmdError::new(ErrorKind::BadArgument, "needs one graph, not all"))
+}
+
+/// Parse a numeric value argument.
+fn value_arg(arg: &str) -> Result<f64, CmdError> {
+    arg.parse()
+        .map_err(|_| CmdError::new(ErrorKind::BadArgument, "val"))
+}
+
+/// Resolve a channel argument: an index, a graph name (case-insensitive), or
+/// `all` (`None`).
+fn channel_arg(graphs: &[SharedGraph], arg: &str) -> Result<Option<usize>, String> {
+    if arg.eq_ignore_ascii_case("all") {
+        return Ok(None);
+    }
+    if let Ok(idx) = arg.parse::<usize>() {
+        return match graphs.get(idx) {
+            Some(_) => Ok(Some(idx)),
+            None => Err(format!("no graph {}", idx)),
+        };
+    }
+    graphs
+        .iter()
+        .position(|g| g.read().unwrap().name.eq_ignore_ascii_case(arg))
+        .map(Some)
+        .ok_or_else(|| format!("no graph named {}", arg))
 }
@@ -2,14 +2,28 @@
 //!
 //! Top-level panels module and re-exports.
 
+pub mod alarm;
+pub mod diag;
+pub mod flight;
 pub mod graph;
 pub mod history;
 pub mod info;
-pub mod paragraph;
+pub mod link;
+pub mod log;
+pub mod map;
+pub mod terminal;
 pub mod title;
+pub mod uplink;
 
-pub use graph::GraphPanel;
+pub use alarm::AlarmPanel;
+pub use diag::ParserPanel;
+pub use flight::FlightPanel;
+pub use graph::{GraphPanel, Threshold, TimeMarker};
 pub use history::HistoryPanel;
 pub use info::InfoPanel;
-pub use paragraph::ParagraphPanel;
+pub use link::LinkQualityPanel;
+pub use log::LogPanel;
+pub use map::TrackPanel;
+pub use terminal::TerminalPanel;
 pub use title::TitlePanel;
+pub use uplink::UplinkPanel;
@@ -0,0 +1,100 @@
+//! src/panels/alarm.rs
+//!
+//! Alarm log panel: every raised alarm newest first, with the value that
+//! raised it and whether it is still active. Entries stay highlighted until
+//! acknowledged (`x`).
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::graph::time::mission_time;
+use crate::telemetry::alarm::SharedAlarms;
+
+/// Half-period of the alarm flashing, milliseconds.
+const BLINK_MS: u128 = 500;
+
+/// Phase of the alarm flashing, shared by every panel so they flash together.
+pub fn blink_on() -> bool {
+    let ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis());
+    (ms / BLINK_MS).is_multiple_of(2)
+}
+
+/// Read-only view of the shared alarm state.
+pub struct AlarmPanel {
+    pub alarms: SharedAlarms,
+}
+
+impl AlarmPanel {
+    pub fn new(alarms: SharedAlarms) -> Self {
+        Self { alarms }
+    }
+}
+
+impl crate::ui::Panel for AlarmPanel {
+    fn draw(&self, f: &mut Frame<'_>, area: Rect) {
+        let al = self.alarms.read().unwrap();
+        let unacked = al.unacked();
+        let mut title = vec![Span::raw("Alarms")];
+        if unacked > 0 {
+            let mut style = Style::default().fg(Color::White).bg(Color::Red);
+            if blink_on() {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            title.push(Span::styled(format!(" {} new, x = ack ", unacked), style));
+        }
+        let block = Block::default()
+            .title(Line::from(title))
+            .borders(Borders::ALL);
+
+        if al.log.is_empty() {
+            let hint = if al.enabled() {
+                "no alarms"
+            } else {
+                "no alarm rules in the schema"
+            };
+            let p = Paragraph::new(hint)
+                .style(Style::default().fg(Color::DarkGray))
+                .block(block);
+            f.render_widget(p, area);
+            return;
+        }
+
+        let lines: Vec<Line> = al
+            .log
+            .iter()
+            .rev()
+            .take(area.height.saturating_sub(2) as usize)
+            .map(|e| {
+                let (status, color) = match e.cleared {
+                    None => ("ACTIVE".to_string(), Color::LightRed),
+                    Some(t) => (format!("clear {}", mission_time(t, 0)), Color::Yellow),
+                };
+                let mut style = Style::default().fg(color);
+                if e.acked {
+                    style = style.fg(if e.cleared.is_some() {
+                        Color::DarkGray
+                    } else {
+                        Color::Red
+                    });
+                } else {
+                    style = style.add_modifier(Modifier::BOLD);
+                }
+                Line::from(vec![
+                    Span::styled(if e.acked { "  " } else { "● " }, style),
+                    Span::styled(format!("{} {:<14} ", mission_time(e.t, 1), status), style),
+                    Span::styled(format!("{} ({:.2})", e.text, e.value), style),
+                ])
+            })
+            .collect();
+        f.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
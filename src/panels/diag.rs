@@ -0,0 +1,101 @@
+//! src/panels/diag.rs
+//!
+//! Parser diagnostics panel: malformed-line counters and rate, parse errors
+//! per field, and the last offending line (next to the raw terminal).
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+
+use crate::telemetry::diag::SharedDiagnostics;
+use crate::telemetry::quality::WINDOW;
+
+/// Read-only view of the parser counters.
+pub struct ParserPanel {
+    pub diagnostics: SharedDiagnostics,
+}
+
+impl ParserPanel {
+    pub fn new(diagnostics: SharedDiagnostics) -> Self {
+        Self { diagnostics }
+    }
+}
+
+impl crate::ui::Panel for ParserPanel {
+    fn draw(&self, f: &mut Frame<'_>, area: Rect) {
+        let d = self.diagnostics.read().unwrap();
+        let mut title = vec![Span::raw("Parser")];
+        if d.reject {
+            title.push(Span::styled(
+                " REJECTING BAD PACKETS ",
+                Style::default().fg(Color::Black).bg(Color::Yellow),
+            ));
+        }
+        let block = Block::default()
+            .title(Line::from(title))
+            .borders(Borders::ALL);
+
+        let rate_color = if d.bad_pct() > 0.0 {
+            Color::Yellow
+        } else {
+            Color::Green
+        };
+        let mut lines = vec![
+            Line::from(format!(
+                "lines {}  malformed {}  short {}  rejected {}",
+                d.lines, d.malformed, d.short, d.rejected
+            )),
+            Line::from(vec![
+                Span::raw(format!("last {} s: ", WINDOW.as_secs())),
+                Span::styled(
+                    format!("{:.1}% bad, {:.1}/min", d.bad_pct(), d.bad_per_min()),
+                    Style::default().fg(rate_color),
+                ),
+            ]),
+            Line::raw(""),
+        ];
+        let errors: Vec<(usize, u64)> = d
+            .field_errors
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| **n > 0)
+            .map(|(idx, n)| (idx, *n))
+            .collect();
+        if errors.is_empty() {
+            lines.push(Line::styled(
+                "no field errors",
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        for (idx, n) in errors {
+            lines.push(Line::from(vec![
+                Span::raw(format!("{:<20}", d.name(idx))),
+                Span::styled(format!("{:>6}", n), Style::default().fg(Color::Yellow)),
+            ]));
+        }
+        if let Some(b) = &d.last_bad {
+            lines.push(Line::raw(""));
+            lines.push(Line::from(vec![
+                Span::styled(
+                    b.time.format("%H:%M:%S%.3f ").to_string(),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw(b.reason.clone()),
+            ]));
+            lines.push(Line::styled(
+                b.text.clone(),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        f.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .wrap(Wrap { trim: false }),
+            area,
+        );
+    }
+}
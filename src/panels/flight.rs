@@ -0,0 +1,103 @@
+//! src/panels/flight.rs
+//!
+//! Flight timeline panel: current phase, altitude and vertical speed, the
+//! flight summary (maximum altitude, descent rate), and the phase events
+//! newest first.
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::graph::time::mission_time;
+use crate::telemetry::flight::{FlightPhase, SharedFlight};
+
+/// Read-only view of the shared flight-state machine.
+pub struct FlightPanel {
+    pub flight: SharedFlight,
+}
+
+impl FlightPanel {
+    pub fn new(flight: SharedFlight) -> Self {
+        Self { flight }
+    }
+}
+
+/// Color used for a phase, here and for the event lines on the graphs.
+pub fn phase_color(phase: FlightPhase) -> Color {
+    match phase {
+        FlightPhase::Pad => Color::Gray,
+        FlightPhase::Ascent => Color::LightGreen,
+        FlightPhase::Apogee => Color::LightYellow,
+        FlightPhase::Descent => Color::LightBlue,
+        FlightPhase::Landed => Color::LightMagenta,
+    }
+}
+
+impl crate::ui::Panel for FlightPanel {
+    fn draw(&self, f: &mut Frame<'_>, area: Rect) {
+        let fl = self.flight.read().unwrap();
+        let block = Block::default().title("Flight").borders(Borders::ALL);
+
+        if !fl.enabled() {
+            let p = Paragraph::new("no channel with role = \"altitude\" in the schema")
+                .style(Style::default().fg(Color::DarkGray))
+                .block(block);
+            f.render_widget(p, area);
+            return;
+        }
+
+        let ground = fl.ground.unwrap_or(0.0);
+        let mut now = vec![Span::styled(
+            format!("● {}", fl.phase.as_str()),
+            Style::default()
+                .fg(phase_color(fl.phase))
+                .add_modifier(Modifier::BOLD),
+        )];
+        if let Some(alt) = fl.altitude {
+            now.push(Span::raw(format!(
+                "  alt {:.0} m (AGL {:.0})",
+                alt,
+                alt - ground
+            )));
+        }
+        if let Some(vs) = fl.vertical_speed {
+            now.push(Span::raw(format!("  v {:+.1} m/s", vs)));
+        }
+
+        let mut summary = Vec::new();
+        if let Some((t, m)) = fl.max_altitude {
+            summary.push(format!(
+                "max {:.0} m AGL @ {}",
+                m - ground,
+                mission_time(t, 1)
+            ));
+        }
+        if let Some(rate) = fl.descent_rate {
+            summary.push(format!("descent {:.1} m/s", rate));
+        }
+
+        let mut lines = vec![Line::from(now)];
+        if !summary.is_empty() {
+            lines.push(Line::from(summary.join("  ")));
+        }
+        for e in fl.events.iter().rev() {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{}  ", mission_time(e.t, 1)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::styled(
+                    format!("{:<8}", e.phase.as_str()),
+                    Style::default().fg(phase_color(e.phase)),
+                ),
+                Span::raw(format!(" {:.0} m", e.altitude)),
+            ]));
+        }
+
+        f.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
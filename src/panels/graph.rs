@@ -2,6 +2,13 @@
 //!
 //! Graph panel: renders the live chart, stats row, and optional locked-bounds lines.
 //!
+//! Mission events (flight phases) are drawn as colored vertical lines, alarm
+//! thresholds as horizontal ones. A channel that stopped receiving samples is
+//! dimmed and badged `NO DATA <n>s`, so its last value does not look current.
+//! With an inspection cursor set, the stats row shows this channel's value at
+//! the cursor instant and the chart draws a vertical cursor line; the focused
+//! graph also marks the nearest sample.
+//!
 //! This panel keeps rendering-only logic here, computing target bounds, interpolating
 //! view bounds with smoothing, and preparing datasets for the chart widget.
 
@@ -10,34 +17,61 @@ use ratatui::{
     layout::Rect,
     style::{Color, Modifier, Style},
     symbols,
+    text::{Line, Span},
     widgets::{Axis, Block, Borders, Chart, Dataset, Paragraph},
 };
 
 use crate::graph::shared::SharedGraph;
+use crate::graph::time::mission_time;
+
+/// A vertical line at a mission time (e.g. a flight-phase event).
+#[derive(Clone, Copy, Debug)]
+pub struct TimeMarker {
+    pub t: f64,
+    pub color: Color,
+}
+
+/// A horizontal line at a value (e.g. an alarm threshold).
+#[derive(Clone, Copy, Debug)]
+pub struct Threshold {
+    pub y: f64,
+    pub color: Color,
+}
 
 /// A lightweight wrapper around the shared graph state used for rendering.
 pub struct GraphPanel {
     pub shared: SharedGraph,
+    /// Whether this graph has keyboard focus (draws the cursor sample marker).
+    pub focused: bool,
+    /// Event lines drawn across the plot.
+    pub markers: Vec<TimeMarker>,
+    /// Level lines drawn across the plot.
+    pub thresholds: Vec<Threshold>,
 }
 
 impl GraphPanel {
     /// Create a new GraphPanel for a shared graph.
     pub fn new(shared: SharedGraph) -> Self {
-        Self { shared }
+        Self {
+            shared,
+            focused: false,
+            markers: Vec::new(),
+            thresholds: Vec::new(),
+        }
     }
 
-    /// Compute a target (ymin, ymax) for the current visible data (with padding).
+    /// Compute a target (ymin, ymax) for the visible points (with padding).
     ///
     /// # Arguments
-    /// * `data` - reference to `GraphData`.
+    /// * `slice` - the points currently shown (live window or frozen range).
+    /// * `fallback` - the configured y-range.
     ///
     /// # Returns
-    /// A `(min, max)` pair with padding applied. Falls back to `data.config.y_range`
+    /// A `(min, max)` pair with padding applied. Falls back to `fallback`
     /// when data is absent or non-finite.
-    fn compute_target_bounds(data: &crate::graph::GraphData) -> (f64, f64) {
-        let slice = data.data_vec.as_slice();
+    fn compute_target_bounds(slice: &[(f64, f64)], fallback: (f64, f64)) -> (f64, f64) {
         if slice.is_empty() {
-            return data.config.y_range;
+            return fallback;
         }
         let mut mn = f64::INFINITY;
         let mut mx = f64::NEG_INFINITY;
@@ -50,7 +84,7 @@ impl GraphPanel {
             }
         }
         if !mn.is_finite() || !mx.is_finite() {
-            return data.config.y_range;
+            return fallback;
         }
         if (mx - mn).abs() < f64::EPSILON {
             // data is essentially flat: add absolute padding to show a visible line
@@ -100,9 +134,31 @@ impl crate::ui::Panel for GraphPanel {
             .split(area);
 
         let mut g = self.shared.write().unwrap();
+        let stale = g.stale();
+        let color = if stale.is_some() {
+            Color::DarkGray
+        } else {
+            g.color
+        };
+
+        // Points on screen: the live window, or a history range when frozen
+        let (series_owned, (xmin, xmax)) = match g.frozen {
+            Some((x0, x1)) => (g.data.history_range(x0, x1), (x0, x1)),
+            None => (g.data.data_vec.clone(), g.data.x_bounds()),
+        };
+        let visible: Vec<(f64, f64)> = series_owned
+            .iter()
+            .copied()
+            .filter(|p| (xmin..=xmax).contains(&p.0))
+            .collect();
 
         // Stats row (min, max, last)
-        let (mn, mx, last) = g.data.stats();
+        let (_, _, last) = g.data.stats();
+        let (mn, mx) = visible
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &(_, y)| {
+                (lo.min(y), hi.max(y))
+            });
         // Compute global min/max from history
         let mut global_min = f64::INFINITY;
         let mut global_max = f64::NEG_INFINITY;
@@ -114,20 +170,47 @@ impl crate::ui::Panel for GraphPanel {
                 global_max = y;
             }
         }
-        // Display global min/max in the stats row
-        let stats_text = format!(
-            "Min: {:.3}  Max: {:.3}  Last: {:.3}",
-            global_min, global_max, last
-        );
-        let stats_par =
-            Paragraph::new(stats_text).block(Block::default().title("Stats").borders(Borders::ALL));
+        // Display global min/max in the stats row...
+        // ...or this channel's value at the inspection cursor
+        let cursor_sample = g.cursor.and_then(|t| g.data.nearest(t));
+        let stats_par = match (g.cursor, cursor_sample) {
+            (Some(t), Some((sx, sy))) => {
+                let style = if self.focused {
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                let text = format!(
+                    "{}  →  {}  (sample {})",
+                    mission_time(t, 1),
+                    format!("{:.3} {}", sy, g.unit).trim_end(),
+                    mission_time(sx, 2)
+                );
+                Paragraph::new(Span::styled(text, style))
+                    .block(Block::default().title("Cursor").borders(Borders::ALL))
+            }
+            (Some(t), None) => Paragraph::new(format!("{}  →  no data", mission_time(t, 1)))
+                .block(Block::default().title("Cursor").borders(Borders::ALL)),
+            (None, _) => Paragraph::new(format!(
+                "Min: {:.3}  Max: {:.3}  Last: {:.3}",
+                global_min, global_max, last
+            ))
+            .style(Style::default().fg(if stale.is_some() {
+                Color::DarkGray
+            } else {
+                Color::Reset
+            }))
+            .block(Block::default().title("Stats").borders(Borders::ALL)),
+        };
         f.render_widget(stats_par, chunks[0]);
 
         // determine target bounds (respect locked bounds first)
         let target_bounds = if let Some(bounds) = g.locked_bounds {
             bounds
         } else if g.autoscale {
-            GraphPanel::compute_target_bounds(&g.data)
+            GraphPanel::compute_target_bounds(&visible, g.data.config.y_range)
         } else {
             g.data.config.y_range
         };
@@ -179,16 +262,14 @@ impl crate::ui::Panel for GraphPanel {
 
         // Keep dataset vectors alive until Chart::new() uses them
         let (ymin, ymax) = g.view.current_bounds.unwrap_or(g.data.config.y_range);
-        let (xmin, xmax) = g.data.x_bounds();
-        let series_owned = g.data.data_vec.clone();
 
         let mut datasets: Vec<Dataset> = Vec::new();
         datasets.push(
             Dataset::default()
-                .name(g.name.clone())
+                .name(g.label())
                 .marker(symbols::Marker::Braille)
                 .graph_type(ratatui::widgets::GraphType::Line)
-                .style(Style::default().fg(g.color))
+                .style(Style::default().fg(color))
                 .data(series_owned.as_slice()),
         );
 
@@ -217,6 +298,66 @@ impl crate::ui::Panel for GraphPanel {
             }
         }
 
+        // Event lines inside the visible time range
+        let marker_lines: Vec<(Vec<(f64, f64)>, Color)> = self
+            .markers
+            .iter()
+            .filter(|m| (xmin..=xmax).contains(&m.t))
+            .map(|m| (vec![(m.t, ymin), (m.t, ymax)], m.color))
+            .collect();
+        for (line, color) in &marker_lines {
+            datasets.push(
+                Dataset::default()
+                    .marker(symbols::Marker::Braille)
+                    .graph_type(ratatui::widgets::GraphType::Line)
+                    .style(Style::default().fg(*color))
+                    .data(line.as_slice()),
+            );
+        }
+
+        // Threshold lines inside the visible value range
+        let threshold_lines: Vec<(Vec<(f64, f64)>, Color)> = self
+            .thresholds
+            .iter()
+            .filter(|th| (ymin..=ymax).contains(&th.y))
+            .map(|th| (vec![(xmin, th.y), (xmax, th.y)], th.color))
+            .collect();
+        for (line, color) in &threshold_lines {
+            datasets.push(
+                Dataset::default()
+                    .marker(symbols::Marker::Braille)
+                    .graph_type(ratatui::widgets::GraphType::Line)
+                    .style(Style::default().fg(*color))
+                    .data(line.as_slice()),
+            );
+        }
+
+        // Inspection cursor: vertical line, plus the nearest sample when focused
+        let cursor_line = g.cursor.map(|t| vec![(t, ymin), (t, ymax)]);
+        if let Some(ref cl) = cursor_line {
+            datasets.push(
+                Dataset::default()
+                    .marker(symbols::Marker::Braille)
+                    .graph_type(ratatui::widgets::GraphType::Line)
+                    .style(Style::default().fg(Color::Gray))
+                    .data(cl.as_slice()),
+            );
+        }
+        let cursor_point = cursor_sample.filter(|_| self.focused).map(|p| vec![p]);
+        if let Some(ref cp) = cursor_point {
+            datasets.push(
+                Dataset::default()
+                    .marker(symbols::Marker::Block)
+                    .graph_type(ratatui::widgets::GraphType::Scatter)
+                    .style(
+                        Style::default()
+                            .fg(Color::White)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                    .data(cp.as_slice()),
+            );
+        }
+
         // y-axis labels
         let mut y_labels: Vec<String> = Vec::with_capacity(5);
         let span = (ymax - ymin).max(1e-9);
@@ -225,9 +366,49 @@ impl crate::ui::Panel for GraphPanel {
             y_labels.push(format!("{:.3}", v));
         }
 
+        // x-axis labels: mission time at both ends and the middle
+        let decimals = if xmax - xmin < 10.0 { 1 } else { 0 };
+        let x_labels: Vec<String> = [xmin, (xmin + xmax) / 2.0, xmax]
+            .iter()
+            .map(|&t| mission_time(t, decimals))
+            .collect();
+
+        // Remember where the plot lands so mouse clicks can be mapped to time
+        let inner = chunks[1].inner(ratatui::layout::Margin::new(1, 1));
+        let label_w = y_labels
+            .iter()
+            .chain(x_labels.first())
+            .map(|l| l.chars().count() as u16)
+            .max()
+            .unwrap_or(0);
+        g.view.plot_area = Some(Rect {
+            x: inner.x + label_w + 1,
+            y: inner.y,
+            width: inner.width.saturating_sub(label_w + 1),
+            height: inner.height.saturating_sub(2),
+        });
+
+        let mut title = vec![Span::raw(match g.frozen {
+            Some(_) => format!("{}  [FROZEN]", g.label()),
+            None => format!("{}  [{}]", g.label(), g.data.config.data_window.label()),
+        })];
+        if let Some(s) = stale {
+            title.push(Span::raw(" "));
+            title.push(Span::styled(
+                format!(" NO DATA {}s ", s.as_secs()),
+                Style::default()
+                    .fg(Color::White)
+                    .bg(Color::Red)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
         let chart = Chart::new(datasets)
-            .block(Block::default().title(g.name.clone()).borders(Borders::ALL))
-            .x_axis(Axis::default().bounds([xmin, xmax]))
+            .block(
+                Block::default()
+                    .title(Line::from(title))
+                    .borders(Borders::ALL),
+            )
+            .x_axis(Axis::default().bounds([xmin, xmax]).labels(x_labels))
             .y_axis(Axis::default().bounds([ymin, ymax]).labels(y_labels));
 
         f.render_widget(chart, chunks[1]);
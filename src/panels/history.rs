@@ -11,6 +11,7 @@ use ratatui::{
 };
 
 use crate::graph::shared::SharedGraph;
+use crate::graph::time::mission_time;
 
 /// Shows the most recent entries of the shared graph's bounded history.
 pub struct HistoryPanel {
@@ -28,7 +29,11 @@ impl crate::ui::Panel for HistoryPanel {
     fn draw(&self, f: &mut Frame<'_>, area: Rect) {
         let g = self.shared.read().unwrap();
         let height = area.height as usize;
-        let hlen = g.data.history.len();
+        // when frozen, end the list at the right edge of the frozen view
+        let hlen = match g.frozen {
+            Some((_, x1)) => g.data.history.partition_point(|p| p.0 <= x1),
+            None => g.data.history.len(),
+        };
         let start = hlen.saturating_sub(height);
         let last_index = hlen.saturating_sub(1);
 
@@ -39,6 +44,7 @@ impl crate::ui::Panel for HistoryPanel {
             .iter()
             .enumerate()
             .skip(start)
+            .take(hlen - start)
             .map(|(i, &&(x, y))| {
                 let is_latest = i == last_index;
                 let xs = if is_latest {
@@ -52,8 +58,8 @@ impl crate::ui::Panel for HistoryPanel {
                     Style::default().fg(Color::Cyan)
                 };
                 Line::from(vec![
-                    Span::styled("x: ", Style::default().fg(Color::Yellow)),
-                    Span::styled(format!("{:>6.0}", x), xs),
+                    Span::styled("t: ", Style::default().fg(Color::Yellow)),
+                    Span::styled(format!("{:>10}", mission_time(x, 1)), xs),
                     Span::raw(", "),
                     Span::styled("y: ", Style::default().fg(Color::Yellow)),
                     Span::styled(format!("{:.3}", y), ys),
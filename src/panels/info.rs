@@ -1,6 +1,9 @@
 //! src/panels/info.rs
 //!
 //! Graph info panel: shows current stabilization state, bounds, and toggles.
+//! The altitude channel's panel also shows the altimeter baseline in effect.
+//! The border turns red while the channel is in alarm, flashing until the
+//! alarm is acknowledged.
 
 use ratatui::{
     Frame,
@@ -11,11 +14,19 @@ use ratatui::{
 };
 
 use crate::graph::shared::SharedGraph;
+use crate::graph::time::mission_time;
+use crate::panels::alarm::blink_on;
+use crate::telemetry::altimeter::{Baseline, SharedAltimeter};
 
 /// Read-only info panel; `highlighted` affects border style.
 pub struct InfoPanel {
     pub shared: SharedGraph,
     pub highlighted: bool,
+    pub altimeter: Option<SharedAltimeter>,
+    /// An alarm of this channel is active.
+    pub alarm: bool,
+    /// An alarm of this channel awaits acknowledgement.
+    pub unacked: bool,
 }
 
 impl InfoPanel {
@@ -23,8 +34,17 @@ impl InfoPanel {
         Self {
             shared,
             highlighted: false,
+            altimeter: None,
+            alarm: false,
+            unacked: false,
         }
     }
+
+    /// Show the altimeter baseline (for the altitude channel).
+    pub fn with_altimeter(mut self, altimeter: SharedAltimeter) -> Self {
+        self.altimeter = Some(altimeter);
+        self
+    }
 }
 
 impl crate::ui::Panel for InfoPanel {
@@ -44,9 +64,9 @@ impl crate::ui::Panel for InfoPanel {
             ""
         };
 
-        let lines = vec![
+        let mut lines = vec![
             Line::from(vec![
-                Span::styled(&g.name, Style::default().add_modifier(Modifier::BOLD)),
+                Span::styled(g.label(), Style::default().add_modifier(Modifier::BOLD)),
                 Span::raw(format!(
                     "{}  autoscale={}  smoothing={:.2}",
                     lock_text, g.autoscale, g.smoothing
@@ -58,7 +78,53 @@ impl crate::ui::Panel for InfoPanel {
             ))]),
         ];
 
-        let mut block = Block::default().title("Info").borders(Borders::ALL);
+        if let Some(alt) = &self.altimeter {
+            let a = alt.read().unwrap();
+            let baseline = match a.baseline {
+                Baseline::Transmitted => "baseline: as transmitted".to_string(),
+                Baseline::Manual(q) => format!("baseline: QNH {:.2} hPa", q),
+                Baseline::Pad(q) => format!("baseline: QNH {:.2} hPa (pad)", q),
+            };
+            let mut spans = vec![Span::styled(baseline, Style::default().fg(Color::Cyan))];
+            if let Some(z) = &a.zeroing {
+                let t = a.last_pressure.map_or(0.0, |(t, _)| t);
+                spans.push(Span::styled(
+                    format!("  zeroing {:.0}/{:.0}s", z.elapsed(t), z.seconds),
+                    Style::default().fg(Color::Yellow),
+                ));
+            }
+            lines.insert(1, Line::from(spans));
+        }
+
+        // FROZEN/LIVE indicator in the title so it is visible at any height
+        let indicator = match g.frozen {
+            Some((x0, x1)) => {
+                lines.insert(
+                    0,
+                    Line::from(format!(
+                        "view {} … {}",
+                        mission_time(x0, 1),
+                        mission_time(x1, 1)
+                    )),
+                );
+                Span::styled(
+                    " ❚❚ FROZEN ",
+                    Style::default()
+                        .fg(Color::Black)
+                        .bg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )
+            }
+            None => Span::styled(
+                " ● LIVE ",
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        };
+        let title = Line::from(vec![Span::raw("Info"), indicator]);
+
+        let mut block = Block::default().title(title).borders(Borders::ALL);
         if self.highlighted {
             block = block.style(
                 Style::default()
@@ -66,6 +132,15 @@ impl crate::ui::Panel for InfoPanel {
                     .add_modifier(Modifier::BOLD),
             );
         }
+        if self.alarm || self.unacked {
+            let flash = self.unacked && blink_on();
+            block = block.border_style(
+                Style::default()
+                    .fg(if flash { Color::White } else { Color::Red })
+                    .bg(if flash { Color::Red } else { Color::Reset })
+                    .add_modifier(Modifier::BOLD),
+            );
+        }
 
         f.render_widget(Paragraph::new(lines).block(block), area);
     }
@@ -0,0 +1,103 @@
+//! src/panels/link.rs
+//!
+//! Link-quality panel: packet accounting, rolling loss/rate, and loss per
+//! RSSI bucket for every RSSI channel.
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::telemetry::quality::{SharedQuality, WINDOW};
+
+/// Read-only view of the shared link-quality statistics.
+pub struct LinkQualityPanel {
+    pub quality: SharedQuality,
+}
+
+impl LinkQualityPanel {
+    pub fn new(quality: SharedQuality) -> Self {
+        Self { quality }
+    }
+}
+
+/// Color for a loss percentage.
+fn loss_color(pct: f64) -> Color {
+    if pct < 5.0 {
+        Color::Green
+    } else if pct < 20.0 {
+        Color::Yellow
+    } else {
+        Color::Red
+    }
+}
+
+impl crate::ui::Panel for LinkQualityPanel {
+    fn draw(&self, f: &mut Frame<'_>, area: Rect) {
+        let q = self.quality.read().unwrap();
+        let block = Block::default().title("Link quality").borders(Borders::ALL);
+
+        if !q.enabled() {
+            let p = Paragraph::new("no channel with role = \"counter\" in the schema")
+                .style(Style::default().fg(Color::DarkGray))
+                .block(block);
+            f.render_widget(p, area);
+            return;
+        }
+
+        let total = q.total_loss_pct();
+        let rolling = q.rolling_loss_pct();
+        let mut lines = vec![
+            Line::from(vec![
+                Span::raw(format!("rx {} / {} expected  ", q.received, q.expected())),
+                Span::styled(
+                    format!("missing {} ({:.1}%)", q.missing, total),
+                    Style::default().fg(loss_color(total)),
+                ),
+                Span::raw(format!(
+                    "  dup {}  late {}  resets {}",
+                    q.duplicates, q.out_of_order, q.resets
+                )),
+            ]),
+            Line::from(vec![
+                Span::raw(format!("last {}s: ", WINDOW.as_secs())),
+                Span::styled(
+                    format!("loss {:.1}%", rolling),
+                    Style::default()
+                        .fg(loss_color(rolling))
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(format!(
+                    "  rate {:.2} Hz  last #{}",
+                    q.rate_hz(),
+                    q.last_counter
+                        .map(|n| n.to_string())
+                        .unwrap_or_else(|| "-".into())
+                )),
+            ]),
+        ];
+
+        for r in &q.rssi {
+            let mut spans = vec![Span::raw(format!(
+                "{} mean {}  loss@dBm",
+                r.name,
+                r.mean()
+                    .map(|m| format!("{:.1}", m))
+                    .unwrap_or_else(|| "-".into())
+            ))];
+            for (lo, b) in r.buckets.iter().rev() {
+                let pct = b.loss_pct();
+                spans.push(Span::styled(
+                    format!(" {}:{:.0}%", lo, pct),
+                    Style::default().fg(loss_color(pct)),
+                ));
+            }
+            lines.push(Line::from(spans));
+        }
+
+        f.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
@@ -0,0 +1,54 @@
+//! src/panels/log.rs
+//!
+//! Log panel: the most recent entries of the shared event log.
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::events::{Level, SharedLog};
+
+/// Shows as many recent events as fit in the area, newest at the bottom.
+pub struct LogPanel {
+    pub log: SharedLog,
+}
+
+impl LogPanel {
+    pub fn new(log: SharedLog) -> Self {
+        Self { log }
+    }
+}
+
+impl crate::ui::Panel for LogPanel {
+    fn draw(&self, f: &mut Frame<'_>, area: Rect) {
+        let log = self.log.read().unwrap();
+        let rows = area.height.saturating_sub(2) as usize;
+
+        let lines: Vec<Line> = log
+            .recent(rows)
+            .map(|e| {
+                let (tag, color) = match e.level {
+                    Level::Info => ("INFO ", Color::Green),
+                    Level::Warn => ("WARN ", Color::Yellow),
+                    Level::Error => ("ERROR", Color::Red),
+                };
+                Line::from(vec![
+                    Span::styled(
+                        e.time.format("%H:%M:%S ").to_string(),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::styled(tag, Style::default().fg(color)),
+                    Span::raw(" "),
+                    Span::raw(e.text.clone()),
+                ])
+            })
+            .collect();
+
+        let block = Block::default().title("Log").borders(Borders::ALL);
+        f.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
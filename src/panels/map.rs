@@ -0,0 +1,202 @@
+//! src/panels/map.rs
+//!
+//! Track panel: the CanSat ground track on a local map centred on the launch
+//! site, with the launch point, ground station, current position, and a trail
+//! that fades with age. Below the map: the current coordinates (for the
+//! recovery team), plus distance and bearing from the ground station.
+
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols::Marker,
+    text::{Line, Span},
+    widgets::{
+        Block, Borders, Paragraph,
+        canvas::{Canvas, Points},
+    },
+};
+
+use crate::telemetry::track::{SharedTrack, bearing_deg, compass, distance_m, local_xy};
+
+/// Trail age bands (seconds behind the newest fix) and their colors, newest first.
+const TRAIL_BANDS: [(f64, Color); 3] = [
+    (30.0, Color::LightYellow),
+    (120.0, Color::Yellow),
+    (f64::INFINITY, Color::DarkGray),
+];
+
+/// Smallest half-width of the map, metres.
+const MIN_RANGE: f64 = 100.0;
+
+/// Read-only view of the shared track.
+pub struct TrackPanel {
+    pub track: SharedTrack,
+}
+
+impl TrackPanel {
+    pub fn new(track: SharedTrack) -> Self {
+        Self { track }
+    }
+}
+
+/// Format a coordinate with hemisphere, e.g. `50.850312N`.
+fn coord(v: f64, pos: char, neg: char) -> String {
+    format!("{:.6}{}", v.abs(), if v < 0.0 { neg } else { pos })
+}
+
+/// Human distance: metres below 1 km, kilometres above.
+fn human_distance(m: f64) -> String {
+    if m < 1_000.0 {
+        format!("{:.0} m", m)
+    } else {
+        format!("{:.2} km", m / 1_000.0)
+    }
+}
+
+impl crate::ui::Panel for TrackPanel {
+    fn draw(&self, f: &mut Frame<'_>, area: Rect) {
+        let tr = self.track.read().unwrap();
+        let block = Block::default().title("Track").borders(Borders::ALL);
+
+        let (Some(origin), Some(current)) = (tr.launch, tr.current()) else {
+            let hint = if tr.enabled() {
+                "waiting for the first GPS fix…"
+            } else {
+                "no channels with role = \"latitude\" / \"longitude\" in the schema"
+            };
+            let p = Paragraph::new(hint)
+                .style(Style::default().fg(Color::DarkGray))
+                .block(block);
+            f.render_widget(p, area);
+            return;
+        };
+
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(3)])
+            .split(inner);
+
+        // Local metres around the launch site
+        let trail: Vec<(f64, f64, f64)> = tr
+            .points
+            .iter()
+            .map(|p| {
+                let (x, y) = local_xy(origin, (p.lat, p.lon));
+                (current.t - p.t, x, y)
+            })
+            .collect();
+        let here = local_xy(origin, (current.lat, current.lon));
+        let station = tr.station.map(|s| local_xy(origin, s));
+
+        // Fit everything, keeping metres square on screen (cells are ~1:2)
+        let reach = trail
+            .iter()
+            .map(|&(_, x, y)| x.abs().max(y.abs()))
+            .chain(station.map(|(x, y)| x.abs().max(y.abs())))
+            .fold(MIN_RANGE, f64::max)
+            * 1.15;
+        let map = rows[0];
+        let aspect = map.width as f64 / (2.0 * map.height.max(1) as f64);
+        let (rx, ry) = if aspect >= 1.0 {
+            (reach * aspect, reach)
+        } else {
+            (reach, reach / aspect)
+        };
+
+        let canvas = Canvas::default()
+            .marker(Marker::Braille)
+            .x_bounds([-rx, rx])
+            .y_bounds([-ry, ry])
+            .paint(|ctx| {
+                // oldest band first so newer points draw on top
+                let mut lower = 0.0;
+                let mut bands = Vec::new();
+                for &(upper, color) in &TRAIL_BANDS {
+                    let coords: Vec<(f64, f64)> = trail
+                        .iter()
+                        .filter(|(age, _, _)| *age >= lower && *age < upper)
+                        .map(|&(_, x, y)| (x, y))
+                        .collect();
+                    bands.push((coords, color));
+                    lower = upper;
+                }
+                for (coords, color) in bands.iter().rev() {
+                    ctx.draw(&Points {
+                        coords,
+                        color: *color,
+                    });
+                }
+                ctx.layer();
+                ctx.print(
+                    0.0,
+                    0.0,
+                    Span::styled("▲", Style::default().fg(Color::Green)),
+                );
+                if let Some((x, y)) = station {
+                    ctx.print(x, y, Span::styled("⌂", Style::default().fg(Color::Cyan)));
+                }
+                ctx.print(
+                    here.0,
+                    here.1,
+                    Span::styled(
+                        "✕",
+                        Style::default()
+                            .fg(Color::LightRed)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                );
+            });
+        f.render_widget(canvas, map);
+
+        // Readout
+        let age = tr
+            .last_fix
+            .map(|t| format!("{:.0}s ago", t.elapsed().as_secs_f64()))
+            .unwrap_or_default();
+        let pos = (current.lat, current.lon);
+        let (reference, name) = match tr.station {
+            Some(s) => (s, "station"),
+            None => (origin, "launch"),
+        };
+        let dist = distance_m(reference, pos);
+        let brg = bearing_deg(reference, pos);
+        let lines = vec![
+            Line::from(vec![
+                Span::styled("✕ ", Style::default().fg(Color::LightRed)),
+                Span::styled(
+                    format!(
+                        "{} {}",
+                        coord(current.lat, 'N', 'S'),
+                        coord(current.lon, 'E', 'W')
+                    ),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(format!("  fix {}", age), Style::default().fg(Color::Gray)),
+            ]),
+            Line::from(format!(
+                "from {}: {}  bearing {:03.0}° {}",
+                name,
+                human_distance(dist),
+                brg,
+                compass(brg)
+            )),
+            Line::from(vec![
+                Span::styled("▲", Style::default().fg(Color::Green)),
+                Span::raw(format!(
+                    " launch {}  ",
+                    human_distance(distance_m(origin, pos))
+                )),
+                Span::styled("⌂", Style::default().fg(Color::Cyan)),
+                Span::raw(if tr.station.is_some() {
+                    " station"
+                } else {
+                    " station unknown (--station)"
+                }),
+            ]),
+        ];
+        f.render_widget(Paragraph::new(lines), rows[1]);
+    }
+}
@@ -0,0 +1,122 @@
+//! src/panels/terminal.rs
+//!
+//! Raw terminal: every received line with its receive time, colored by how
+//! the decoder classified it, with a search filter and scrollback (`t`).
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::telemetry::parse::LineClass;
+use crate::telemetry::rawlog::SharedRawLog;
+
+/// View of the raw line buffer.
+pub struct TerminalPanel {
+    pub rawlog: SharedRawLog,
+    /// Case-insensitive substring shown lines must contain.
+    pub filter: String,
+    /// Whether the filter is being typed.
+    pub editing: bool,
+    /// Bottom line shown while scrolled back; `None` follows the newest.
+    pub anchor: Option<u64>,
+}
+
+impl TerminalPanel {
+    pub fn new(rawlog: SharedRawLog) -> Self {
+        Self {
+            rawlog,
+            filter: String::new(),
+            editing: false,
+            anchor: None,
+        }
+    }
+
+    pub fn with_filter(mut self, filter: &str, editing: bool) -> Self {
+        self.filter = filter.to_string();
+        self.editing = editing;
+        self
+    }
+
+    pub fn with_anchor(mut self, anchor: Option<u64>) -> Self {
+        self.anchor = anchor;
+        self
+    }
+}
+
+fn class_color(class: LineClass) -> Color {
+    match class {
+        LineClass::Parsed => Color::White,
+        LineClass::Partial => Color::Yellow,
+        LineClass::Ignored => Color::DarkGray,
+    }
+}
+
+impl crate::ui::Panel for TerminalPanel {
+    fn draw(&self, f: &mut Frame<'_>, area: Rect) {
+        let raw = self.rawlog.read().unwrap();
+        let [parsed, partial, ignored] = raw.counts;
+        let mut title = vec![
+            Span::raw("Raw terminal "),
+            Span::styled(
+                format!("{} parsed ", parsed),
+                Style::default().fg(class_color(LineClass::Parsed)),
+            ),
+            Span::styled(
+                format!("{} partial ", partial),
+                Style::default().fg(class_color(LineClass::Partial)),
+            ),
+            Span::styled(
+                format!("{} ignored", ignored),
+                Style::default().fg(Color::Gray),
+            ),
+        ];
+        if self.editing || !self.filter.is_empty() {
+            title.push(Span::styled(
+                format!(" /{}", self.filter),
+                Style::default().fg(Color::Cyan),
+            ));
+            if self.editing {
+                title.push(Span::styled(
+                    "█",
+                    Style::default().add_modifier(Modifier::SLOW_BLINK),
+                ));
+            }
+        }
+        let matching: Vec<_> = raw.matching(&self.filter).collect();
+        let end = match self.anchor {
+            Some(a) => matching.partition_point(|l| l.seq <= a),
+            None => matching.len(),
+        };
+        let newer = matching.len() - end;
+        if newer > 0 {
+            title.push(Span::raw(" "));
+            title.push(Span::styled(
+                format!(" {} newer ", newer),
+                Style::default().fg(Color::Black).bg(Color::Yellow),
+            ));
+        }
+        let block = Block::default()
+            .title(Line::from(title))
+            .borders(Borders::ALL);
+
+        let rows = area.height.saturating_sub(2) as usize;
+        let start = end.saturating_sub(rows);
+        let lines: Vec<Line> = matching[start..end]
+            .iter()
+            .map(|l| {
+                Line::from(vec![
+                    Span::styled(
+                        l.time.format("%H:%M:%S%.3f ").to_string(),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::styled(l.text.clone(), Style::default().fg(class_color(l.class))),
+                ])
+            })
+            .collect();
+        f.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
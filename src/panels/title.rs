@@ -1,29 +1,203 @@
 //! src/panels/title.rs
 //!
-//! Simple title/header panel.
+//! Title/header panel with the telemetry link status and the time since the
+//! last packet (red once every channel has gone stale).
 
 use ratatui::{
     Frame,
     layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
 };
 
+use crate::graph::shared::SharedGraph;
+use crate::telemetry::link::{LinkState, SharedLink};
+use crate::telemetry::record::SharedRecorder;
+use crate::telemetry::replay::SharedReplay;
+
 pub struct TitlePanel {
     pub title: String,
+    pub links: Vec<SharedLink>,
+    pub replay: Option<SharedReplay>,
+    pub recorder: Option<SharedRecorder>,
+    pub graphs: Vec<SharedGraph>,
 }
 
 impl TitlePanel {
     pub fn new(title: &str) -> Self {
         Self {
             title: title.to_string(),
+            links: Vec::new(),
+            replay: None,
+            recorder: None,
+            graphs: Vec::new(),
         }
     }
+
+    /// Show the state of `link` next to the title (once per source).
+    pub fn with_link(mut self, link: SharedLink) -> Self {
+        self.links.push(link);
+        self
+    }
+
+    /// Show the transport state of `replay` next to the title.
+    pub fn with_replay(mut self, replay: SharedReplay) -> Self {
+        self.replay = Some(replay);
+        self
+    }
+
+    /// Show the time since any of `graphs` last received a sample.
+    pub fn with_graphs(mut self, graphs: Vec<SharedGraph>) -> Self {
+        self.graphs = graphs;
+        self
+    }
+
+    /// Show the session recording file and size on a second line.
+    pub fn with_recorder(mut self, recorder: SharedRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+}
+
+/// Format a byte count with a binary unit.
+fn human_bytes(n: u64) -> String {
+    let n = n as f64;
+    if n < 1024.0 {
+        format!("{} B", n)
+    } else if n < 1024.0 * 1024.0 {
+        format!("{:.1} KiB", n / 1024.0)
+    } else {
+        format!("{:.1} MiB", n / (1024.0 * 1024.0))
+    }
+}
+
+/// Format seconds as `mm:ss.s`.
+fn mmss(secs: f64) -> String {
+    let secs = secs.max(0.0);
+    format!("{:02}:{:04.1}", (secs / 60.0) as u64, secs % 60.0)
 }
 
 impl crate::ui::Panel for TitlePanel {
     fn draw(&self, f: &mut Frame<'_>, area: Rect) {
-        let p = Paragraph::new(self.title.clone())
-            .block(Block::default().title("Title").borders(Borders::ALL));
+        let mut spans = vec![Span::styled(
+            self.title.clone(),
+            Style::default().add_modifier(Modifier::BOLD),
+        )];
+
+        for link in &self.links {
+            let l = link.read().unwrap();
+            let color = match l.state {
+                LinkState::Connecting => Color::Yellow,
+                LinkState::Connected => Color::Green,
+                LinkState::Lost => Color::Red,
+                LinkState::Ended => Color::DarkGray,
+            };
+            let secs = l.since.elapsed().as_secs();
+            spans.push(Span::raw("  │  "));
+            spans.push(Span::styled(
+                format!("● {}", l.state.as_str()),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ));
+            spans.push(Span::raw(format!(
+                " {}  for {}m{:02}s  reconnects={}",
+                l.source,
+                secs / 60,
+                secs % 60,
+                l.reconnects
+            )));
+            if l.state != LinkState::Connected
+                && let Some(err) = &l.last_error
+            {
+                spans.push(Span::styled(
+                    format!("  last error: {}", err),
+                    Style::default().fg(Color::Red),
+                ));
+            }
+        }
+
+        if let Some(replay) = &self.replay {
+            let r = replay.read().unwrap();
+            let (icon, color) = if r.finished {
+                ("■ END", Color::DarkGray)
+            } else if r.paused {
+                ("⏸ PAUSED", Color::Yellow)
+            } else {
+                ("▶ REPLAY", Color::Green)
+            };
+            spans.push(Span::raw("  │  "));
+            spans.push(Span::styled(
+                icon,
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ));
+            spans.push(Span::raw(format!(
+                " {}  {}  {} / {}",
+                r.file,
+                r.speed.label(),
+                mmss(r.position),
+                mmss(r.duration)
+            )));
+        }
+
+        if !self.graphs.is_empty() {
+            let latest = self
+                .graphs
+                .iter()
+                .filter_map(|g| g.read().unwrap().silence())
+                .min();
+            let all_stale = self.graphs.iter().all(|g| {
+                let g = g.read().unwrap();
+                g.last_rx.is_none() || g.stale().is_some()
+            });
+            spans.push(Span::raw("  │  "));
+            spans.push(match latest {
+                Some(d) => {
+                    let secs = d.as_secs_f64();
+                    let age = if secs < 60.0 {
+                        format!("{:.1}s", secs)
+                    } else {
+                        mmss(secs)
+                    };
+                    let style = if all_stale {
+                        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(Color::Gray)
+                    };
+                    Span::styled(format!("last packet {} ago", age), style)
+                }
+                None => Span::styled("no packet yet", Style::default().fg(Color::DarkGray)),
+            });
+        }
+
+        let mut lines = vec![Line::from(spans)];
+
+        if let Some(rec) = &self.recorder {
+            let r = rec.read().unwrap();
+            let line = match &r.error {
+                Some(err) => Line::from(vec![
+                    Span::styled(
+                        "■ REC STOPPED ",
+                        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(err.clone(), Style::default().fg(Color::Red)),
+                ]),
+                None => Line::from(vec![
+                    Span::styled(
+                        "● REC ",
+                        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(format!(
+                        "{}  {}  {} lines",
+                        r.path.display(),
+                        human_bytes(r.bytes),
+                        r.rows
+                    )),
+                ]),
+            };
+            lines.push(line);
+        }
+
+        let p = Paragraph::new(lines).block(Block::default().title("Title").borders(Borders::ALL));
         f.render_widget(p, area);
     }
 }
@@ -0,0 +1,94 @@
+//! src/panels/uplink.rs
+//!
+//! Uplink console: the frames sent to the CanSat newest first with their
+//! acknowledgement state, and the command line while one is being typed (`u`).
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::telemetry::uplink::{SharedUplink, UplinkState};
+
+/// Read-only view of the uplink queue, plus the console input being edited.
+pub struct UplinkPanel {
+    pub uplink: SharedUplink,
+    pub input: Option<String>,
+}
+
+impl UplinkPanel {
+    pub fn new(uplink: SharedUplink) -> Self {
+        Self {
+            uplink,
+            input: None,
+        }
+    }
+
+    /// Show the console line with `input` typed so far.
+    pub fn with_input(mut self, input: Option<String>) -> Self {
+        self.input = input;
+        self
+    }
+}
+
+fn state_color(state: &UplinkState) -> Color {
+    match state {
+        UplinkState::Queued => Color::Gray,
+        UplinkState::Sent => Color::Yellow,
+        UplinkState::Carried => Color::LightCyan,
+        UplinkState::Confirmed(_) => Color::LightGreen,
+        UplinkState::NoAck | UplinkState::Failed(_) => Color::LightRed,
+    }
+}
+
+impl crate::ui::Panel for UplinkPanel {
+    fn draw(&self, f: &mut Frame<'_>, area: Rect) {
+        let up = self.uplink.read().unwrap();
+        let mut title = vec![Span::raw("Uplink")];
+        if !up.connected() {
+            title.push(Span::styled(
+                " offline ",
+                Style::default().fg(Color::White).bg(Color::DarkGray),
+            ));
+        }
+        let queued = up.queued();
+        if queued > 0 {
+            title.push(Span::raw(format!(" {} queued", queued)));
+        }
+        let block = Block::default()
+            .title(Line::from(title))
+            .borders(Borders::ALL);
+
+        let rows = area.height.saturating_sub(2) as usize;
+        let mut lines: Vec<Line> = Vec::new();
+        if let Some(input) = &self.input {
+            lines.push(Line::from(vec![
+                Span::styled("> ", Style::default().fg(Color::Yellow)),
+                Span::raw(input.clone()),
+                Span::styled("█", Style::default().add_modifier(Modifier::SLOW_BLINK)),
+            ]));
+        }
+        if up.commands.is_empty() && self.input.is_none() {
+            lines.push(Line::styled(
+                "u = type a command",
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        for c in up.commands.iter().rev() {
+            if lines.len() >= rows {
+                break;
+            }
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{:<14} ", c.state.label()),
+                    Style::default().fg(state_color(&c.state)),
+                ),
+                Span::raw(c.frame.clone()),
+            ]));
+        }
+        f.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
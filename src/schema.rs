@@ -0,0 +1,632 @@
+//! src/schema.rs
+//!
+//! Declarative channel schema loaded from TOML (see `channels.toml`).
+//!
+//! The schema drives graph construction, line decoding and the column layout,
+//! so adding a sensor only means adding a `[[channel]]` entry.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use ratatui::style::Color;
+use serde::{Deserialize, Deserializer};
+
+use crate::graph::shared::{GraphShared, SharedGraph};
+use crate::graph::{DataWindow, GraphConfig};
+use crate::telemetry::nmea::NmeaField;
+
+/// Schema compiled into the binary, used when `--channels` is not given.
+const DEFAULT_SCHEMA: &str = include_str!("../channels.toml");
+
+/// Layout options.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LayoutSpec {
+    /// Number of graph columns; channels are distributed in order.
+    #[serde(default = "default_columns")]
+    pub columns: usize,
+}
+
+impl Default for LayoutSpec {
+    fn default() -> Self {
+        Self {
+            columns: default_columns(),
+        }
+    }
+}
+
+/// Special meaning of a channel for the derived subsystems.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelRole {
+    /// Monotonic packet counter used for link-quality accounting.
+    Counter,
+    /// Signal strength (dBm) correlated with packet loss.
+    Rssi,
+    /// Altitude (metres) driving flight-phase detection.
+    Altitude,
+    /// Static pressure (hPa) the altitude can be recomputed from.
+    Pressure,
+    /// GPS latitude (decimal degrees) for the track map.
+    Latitude,
+    /// GPS longitude (decimal degrees) for the track map.
+    Longitude,
+}
+
+/// How a derived channel is computed from other channels (referenced by name,
+/// declared earlier in the schema).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum DeriveSpec {
+    /// Smoothed time derivative of `of` (per second), times `scale`.
+    Derivative {
+        of: String,
+        /// Span of the least-squares slope, seconds.
+        #[serde(default = "default_derive_span")]
+        span: f64,
+        #[serde(default = "default_derive_scale")]
+        scale: f64,
+    },
+    /// Barometric altitude (m) from `pressure` (hPa), standard atmosphere.
+    IsaAltitude {
+        pressure: String,
+        /// Sea-level pressure, hPa.
+        #[serde(default = "default_qnh")]
+        qnh: f64,
+    },
+    /// Dew point (°C) from `temperature` (°C) and relative `humidity` (%).
+    DewPoint {
+        temperature: String,
+        humidity: String,
+    },
+}
+
+impl DeriveSpec {
+    /// Names of the input channels.
+    pub fn inputs(&self) -> Vec<&str> {
+        match self {
+            DeriveSpec::Derivative { of, .. } => vec![of],
+            DeriveSpec::IsaAltitude { pressure, .. } => vec![pressure],
+            DeriveSpec::DewPoint {
+                temperature,
+                humidity,
+            } => vec![temperature, humidity],
+        }
+    }
+}
+
+/// Condition that raises an alarm on one channel.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AlarmCondition {
+    /// Value above `value`.
+    Above { value: f64 },
+    /// Value below `value`.
+    Below { value: f64 },
+    /// Value outside the safe band `[min, max]`.
+    Outside { min: f64, max: f64 },
+    /// Rate of change between consecutive samples above `limit` per second
+    /// (either direction).
+    Rate { limit: f64 },
+}
+
+impl AlarmCondition {
+    /// Short description, e.g. `below -110`.
+    pub fn describe(&self) -> String {
+        match self {
+            AlarmCondition::Above { value } => format!("above {}", value),
+            AlarmCondition::Below { value } => format!("below {}", value),
+            AlarmCondition::Outside { min, max } => format!("outside [{}, {}]", min, max),
+            AlarmCondition::Rate { limit } => format!("changing faster than {}/s", limit),
+        }
+    }
+}
+
+/// One alarm rule of a channel (`alarms = [{ kind = "...", ... }]`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct AlarmSpec {
+    #[serde(flatten)]
+    pub condition: AlarmCondition,
+    /// How far back past the threshold the value must return to clear.
+    #[serde(default)]
+    pub hysteresis: f64,
+    /// Seconds the condition must hold before the alarm is raised.
+    #[serde(default, rename = "for")]
+    pub min_duration: f64,
+}
+
+/// One telemetry channel as declared in the schema file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChannelSpec {
+    pub name: String,
+    #[serde(default)]
+    pub unit: String,
+    #[serde(default = "default_color")]
+    pub color: String,
+    /// First whitespace token of the lines carrying this channel.
+    #[serde(default)]
+    pub line: String,
+    /// Token index of the value within the line (the prefix is token 0).
+    #[serde(default)]
+    pub field: usize,
+    /// The field may be missing; shorter lines still decode the other channels.
+    #[serde(default)]
+    pub optional: bool,
+    /// Also (or only) fed from forwarded NMEA `GGA`/`RMC` sentences.
+    #[serde(default)]
+    pub nmea: Option<NmeaField>,
+    /// Computed from other channels instead of decoded.
+    #[serde(default)]
+    pub derive: Option<DeriveSpec>,
+    /// Give the channel a graph region (`false` keeps it for TCP/derived panels only).
+    #[serde(default = "default_graph")]
+    pub graph: bool,
+    /// Sample count (`50`) or duration (`"60s"`) of the live window.
+    #[serde(default = "default_window", deserialize_with = "deserialize_window")]
+    pub window: DataWindow,
+    #[serde(default = "default_history")]
+    pub history: usize,
+    #[serde(default = "default_y_range")]
+    pub y_range: (f64, f64),
+    #[serde(default = "default_autoscale")]
+    pub autoscale: bool,
+    #[serde(default = "default_smoothing")]
+    pub smoothing: f64,
+    /// Optional role for link-quality statistics or the track map.
+    #[serde(default)]
+    pub role: Option<ChannelRole>,
+    /// Seconds without a sample before the channel is marked stale.
+    #[serde(default = "default_stale_after")]
+    pub stale_after: f64,
+    /// Threshold alarms on this channel.
+    #[serde(default)]
+    pub alarms: Vec<AlarmSpec>,
+}
+
+impl ChannelSpec {
+    /// Parsed ratatui color (validated by `Schema::validate`).
+    pub fn color(&self) -> Color {
+        Color::from_str(&self.color).unwrap_or(Color::White)
+    }
+
+    /// Graph configuration derived from window/history/y_range.
+    pub fn graph_config(&self) -> GraphConfig {
+        GraphConfig::new(self.window, self.history, self.y_range)
+    }
+
+    /// Build the shared graph for this channel.
+    pub fn to_graph(&self) -> SharedGraph {
+        Arc::new(RwLock::new(
+            GraphShared::new(
+                self.graph_config(),
+                &self.name,
+                &self.unit,
+                self.color(),
+                self.autoscale,
+                self.smoothing,
+            )
+            .with_stale_after(Duration::from_secs_f64(self.stale_after)),
+        ))
+    }
+}
+
+/// The full schema: layout plus ordered channel list.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Schema {
+    #[serde(default)]
+    pub layout: LayoutSpec,
+    #[serde(rename = "channel", default)]
+    pub channels: Vec<ChannelSpec>,
+}
+
+impl Schema {
+    /// Load the schema from `path`, or the built-in default when `None`.
+    pub fn load(path: Option<&Path>) -> Result<Self, String> {
+        let (text, origin) = match path {
+            Some(p) => (
+                std::fs::read_to_string(p)
+                    .map_err(|e| format!("cannot read channel schema {}: {}", p.display(), e))?,
+                p.display().to_string(),
+            ),
+            None => (
+                DEFAULT_SCHEMA.to_string(),
+                "<built-in channels.toml>".into(),
+            ),
+        };
+        Self::parse(&text).map_err(|e| format!("invalid channel schema {}: {}", origin, e))
+    }
+
+    /// Parse and validate schema text.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let schema: Schema = toml::from_str(text).map_err(|e| e.to_string())?;
+        schema.validate()?;
+        Ok(schema)
+    }
+
+    /// Reject schemas that would produce an unusable UI or ambiguous decoding.
+    fn validate(&self) -> Result<(), String> {
+        if self.channels.is_empty() {
+            return Err("no [[channel]] entries".into());
+        }
+        if self.layout.columns == 0 {
+            return Err("layout.columns must be at least 1".into());
+        }
+        let mut seen = HashMap::new();
+        for (i, c) in self.channels.iter().enumerate() {
+            let ctx = format!("channel {} ({:?})", i, c.name);
+            if c.name.trim().is_empty() {
+                return Err(format!("channel {}: name must not be empty", i));
+            }
+            if let Some(prev) = seen.insert(c.name.as_str(), i) {
+                return Err(format!(
+                    "{}: duplicate name, already used by channel {}",
+                    ctx, prev
+                ));
+            }
+            if Color::from_str(&c.color).is_err() {
+                return Err(format!("{}: unknown color {:?}", ctx, c.color));
+            }
+            if let Some(d) = &c.derive {
+                if !c.line.is_empty() || c.nmea.is_some() {
+                    return Err(format!(
+                        "{}: a derived channel has no line or nmea source",
+                        ctx
+                    ));
+                }
+                for input in d.inputs() {
+                    if !self.channels[..i].iter().any(|o| o.name == input) {
+                        return Err(format!(
+                            "{}: derive input {:?} must name a channel declared before it",
+                            ctx, input
+                        ));
+                    }
+                }
+                match d {
+                    DeriveSpec::Derivative { span, .. } if !span.is_finite() || *span <= 0.0 => {
+                        return Err(format!("{}: derivative span must be > 0 s", ctx));
+                    }
+                    DeriveSpec::IsaAltitude { qnh, .. } if !qnh.is_finite() || *qnh <= 0.0 => {
+                        return Err(format!("{}: qnh must be > 0 hPa", ctx));
+                    }
+                    _ => {}
+                }
+            } else if c.line.is_empty() && c.nmea.is_none() {
+                return Err(format!(
+                    "{}: needs a line/field, an nmea source, or a derive rule",
+                    ctx
+                ));
+            }
+            if !c.line.is_empty() {
+                if c.line.trim().is_empty() || c.line.contains(char::is_whitespace) {
+                    return Err(format!("{}: line must be a single non-empty token", ctx));
+                }
+                if c.field == 0 {
+                    return Err(format!("{}: field 0 is the line prefix itself", ctx));
+                }
+            }
+            if let DataWindow::Samples(n) = c.window
+                && c.history < n
+            {
+                return Err(format!("{}: history must be >= window", ctx));
+            }
+            if c.y_range.0.partial_cmp(&c.y_range.1) != Some(std::cmp::Ordering::Less) {
+                return Err(format!(
+                    "{}: y_range must be [min, max] with min < max",
+                    ctx
+                ));
+            }
+            if !(0.0..=1.0).contains(&c.smoothing) {
+                return Err(format!("{}: smoothing must be within 0.0..=1.0", ctx));
+            }
+            if !(c.stale_after.is_finite() && c.stale_after > 0.0) {
+                return Err(format!("{}: stale_after must be > 0 s", ctx));
+            }
+        }
+        for c in &self.channels {
+            for a in &c.alarms {
+                let ctx = format!("channel {:?} alarm {}", c.name, a.condition.describe());
+                let values = match a.condition {
+                    AlarmCondition::Above { value } | AlarmCondition::Below { value } => {
+                        vec![value]
+                    }
+                    AlarmCondition::Outside { min, max } => {
+                        if min >= max {
+                            return Err(format!("{}: min must be below max", ctx));
+                        }
+                        vec![min, max]
+                    }
+                    AlarmCondition::Rate { limit } => {
+                        if limit <= 0.0 {
+                            return Err(format!("{}: limit must be > 0", ctx));
+                        }
+                        vec![limit]
+                    }
+                };
+                if values.iter().any(|v| !v.is_finite()) {
+                    return Err(format!("{}: thresholds must be finite", ctx));
+                }
+                if !(a.hysteresis.is_finite() && a.hysteresis >= 0.0) {
+                    return Err(format!("{}: hysteresis must be >= 0", ctx));
+                }
+                match a.condition {
+                    AlarmCondition::Rate { limit } if a.hysteresis >= limit => {
+                        return Err(format!("{}: hysteresis must be below the limit", ctx));
+                    }
+                    AlarmCondition::Outside { min, max } if 2.0 * a.hysteresis >= max - min => {
+                        return Err(format!(
+                            "{}: hysteresis must be under half of max - min",
+                            ctx
+                        ));
+                    }
+                    _ => {}
+                }
+                if !(a.min_duration.is_finite() && a.min_duration >= 0.0) {
+                    return Err(format!("{}: for must be >= 0 s", ctx));
+                }
+            }
+        }
+        if !self.channels.iter().any(|c| c.graph) {
+            return Err("at least one channel needs graph = true".into());
+        }
+        for (role, name) in [
+            (ChannelRole::Counter, "counter"),
+            (ChannelRole::Altitude, "altitude"),
+            (ChannelRole::Pressure, "pressure"),
+            (ChannelRole::Latitude, "latitude"),
+            (ChannelRole::Longitude, "longitude"),
+        ] {
+            if self.with_role(role).count() > 1 {
+                return Err(format!("at most one channel may have role = {:?}", name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Index of the channel called `name`.
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.channels.iter().position(|c| c.name == name)
+    }
+
+    /// Indices of the channels with `role`.
+    pub fn with_role(&self, role: ChannelRole) -> impl Iterator<Item = usize> + '_ {
+        self.channels
+            .iter()
+            .enumerate()
+            .filter(move |(_, c)| c.role == Some(role))
+            .map(|(i, _)| i)
+    }
+
+    /// Build one shared graph per channel, in schema order.
+    pub fn build_graphs(&self) -> Vec<SharedGraph> {
+        self.channels.iter().map(ChannelSpec::to_graph).collect()
+    }
+
+    /// Split the indices of channels with `graph = true` into
+    /// `layout.columns` contiguous columns.
+    ///
+    /// Earlier columns take the extra channel when the split is uneven
+    /// (7 channels over 2 columns gives 4 + 3).
+    pub fn columns(&self) -> Vec<Vec<usize>> {
+        let shown: Vec<usize> = (0..self.channels.len())
+            .filter(|&i| self.channels[i].graph)
+            .collect();
+        let n = shown.len();
+        let cols = self.layout.columns.min(n).max(1);
+        let per = n.div_ceil(cols).max(1);
+        shown.chunks(per).map(|c| c.to_vec()).collect()
+    }
+}
+
+fn default_columns() -> usize {
+    2
+}
+
+fn default_color() -> String {
+    "white".into()
+}
+
+fn default_derive_span() -> f64 {
+    2.0
+}
+
+fn default_derive_scale() -> f64 {
+    1.0
+}
+
+fn default_qnh() -> f64 {
+    1013.25
+}
+
+fn default_stale_after() -> f64 {
+    5.0
+}
+
+fn default_graph() -> bool {
+    true
+}
+
+fn default_window() -> DataWindow {
+    DataWindow::Samples(50)
+}
+
+/// `window` as written in TOML: an integer or a duration string.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawWindow {
+    Samples(usize),
+    Text(String),
+}
+
+fn deserialize_window<'de, D: Deserializer<'de>>(d: D) -> Result<DataWindow, D::Error> {
+    match RawWindow::deserialize(d)? {
+        RawWindow::Samples(n) => n.to_string().parse(),
+        RawWindow::Text(s) => s.parse(),
+    }
+    .map_err(serde::de::Error::custom)
+}
+
+fn default_history() -> usize {
+    1_000
+}
+
+fn default_y_range() -> (f64, f64) {
+    (0.0, 100.0)
+}
+
+fn default_autoscale() -> bool {
+    true
+}
+
+fn default_smoothing() -> f64 {
+    0.5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two `Received:` channels, with `extra` appended to the second one.
+    fn schema(extra: &str) -> Result<Schema, String> {
+        Schema::parse(&format!(
+            "[[channel]]\nname = \"TEMP\"\nline = \"Received:\"\nfield = 3\n\n\
+             [[channel]]\nname = \"PRESSURE\"\nline = \"Received:\"\nfield = 4\n{}",
+            extra
+        ))
+    }
+
+    fn rejected(extra: &str, reason: &str) {
+        match schema(extra) {
+            Ok(_) => panic!("{:?} should be rejected", extra),
+            Err(e) => assert!(e.contains(reason), "{:?}: unexpected error {:?}", extra, e),
+        }
+    }
+
+    #[test]
+    fn default_schema_loads() {
+        let s = Schema::load(None).unwrap();
+        assert!(!s.channels.is_empty());
+        assert_eq!(s.build_graphs().len(), s.channels.len());
+    }
+
+    #[test]
+    fn minimal_channel_gets_defaults() {
+        let s = schema("").unwrap();
+        let c = &s.channels[1];
+        assert_eq!((c.line.as_str(), c.field), ("Received:", 4));
+        assert_eq!(c.color(), Color::White);
+        assert_eq!(
+            schema("window = \"2m\"").unwrap().channels[1].window,
+            DataWindow::Seconds(120.0)
+        );
+        assert_eq!((c.window, c.history), (DataWindow::Samples(50), 1_000));
+        assert_eq!(s.layout.columns, 2);
+    }
+
+    #[test]
+    fn invalid_channels_are_rejected() {
+        rejected("name = \"x\"", "duplicate key");
+        rejected("color = \"mauve\"", "unknown color");
+        rejected("window = 0", "invalid window");
+        rejected("window = \"fortnight\"", "invalid window");
+        rejected("window = 10\nhistory = 5", "history must be >= window");
+        rejected("y_range = [5.0, 5.0]", "y_range");
+        rejected("smoothing = 1.5", "smoothing");
+        rejected("colour = \"red\"", "unknown field");
+    }
+
+    #[test]
+    fn alarm_rules_are_checked() {
+        let s = schema("alarms = [{ kind = \"rate\", limit = 5.0, hysteresis = 1.0, for = 2.0 }]")
+            .unwrap();
+        let a = &s.channels[1].alarms[0];
+        assert_eq!(a.condition, AlarmCondition::Rate { limit: 5.0 });
+        assert_eq!((a.hysteresis, a.min_duration), (1.0, 2.0));
+        schema("alarms = [{ kind = \"outside\", min = 0.0, max = 10.0, hysteresis = 4.9 }]")
+            .unwrap();
+
+        rejected(
+            "alarms = [{ kind = \"outside\", min = 5.0, max = 5.0 }]",
+            "min must be below max",
+        );
+        rejected(
+            "alarms = [{ kind = \"rate\", limit = 0.0 }]",
+            "limit must be > 0",
+        );
+        rejected("alarms = [{ kind = \"above\", value = inf }]", "finite");
+        rejected(
+            "alarms = [{ kind = \"below\", value = 1.0, hysteresis = -1.0 }]",
+            ">= 0",
+        );
+        rejected(
+            "alarms = [{ kind = \"above\", value = 1.0, for = -1.0 }]",
+            "for must be",
+        );
+        rejected(
+            "alarms = [{ kind = \"rate\", limit = 5.0, hysteresis = 5.0 }]",
+            "hysteresis must be below the limit",
+        );
+        rejected(
+            "alarms = [{ kind = \"outside\", min = 0.0, max = 10.0, hysteresis = 5.0 }]",
+            "hysteresis must be under half",
+        );
+    }
+
+    #[test]
+    fn duplicate_names_are_rejected() {
+        let e = Schema::parse(
+            "[[channel]]\nname = \"TEMP\"\nline = \"A:\"\nfield = 1\n\n\
+             [[channel]]\nname = \"TEMP\"\nline = \"B:\"\nfield = 1\n",
+        )
+        .unwrap_err();
+        assert!(e.contains("duplicate name"), "{}", e);
+    }
+
+    #[test]
+    fn prefix_field_and_line_are_checked() {
+        let one = |body: &str| Schema::parse(&format!("[[channel]]\nname = \"X\"\n{}", body));
+        assert!(
+            one("line = \"A:\"\nfield = 0")
+                .unwrap_err()
+                .contains("field 0")
+        );
+        assert!(
+            one("line = \"A: B\"\nfield = 1")
+                .unwrap_err()
+                .contains("single")
+        );
+        assert!(
+            one("line = \"\"\nfield = 1")
+                .unwrap_err()
+                .contains("needs a line/field")
+        );
+        assert!(one("nmea = \"latitude\"").is_ok());
+        assert!(one("nmea = \"bearing\"").is_err());
+        assert!(Schema::parse("").unwrap_err().contains("no [[channel]]"));
+        assert!(
+            Schema::parse(
+                "[layout]\ncolumns = 0\n\n[[channel]]\nname = \"X\"\nline = \"A:\"\nfield = 1"
+            )
+            .unwrap_err()
+            .contains("columns")
+        );
+    }
+
+    #[test]
+    fn columns_split_evenly_with_the_remainder_first() {
+        let mut text = String::from("[layout]\ncolumns = 2\n");
+        for i in 0..7 {
+            text.push_str(&format!(
+                "\n[[channel]]\nname = \"C{}\"\nline = \"A:\"\nfield = {}\n",
+                i,
+                i + 1
+            ));
+        }
+        let s = Schema::parse(&text).unwrap();
+        assert_eq!(s.columns(), vec![vec![0, 1, 2, 3], vec![4, 5, 6]]);
+    }
+}
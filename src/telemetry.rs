@@ -0,0 +1,28 @@
+//! src/telemetry.rs
+//!
+//! Telemetry ingestion: line decoding, the shared ingest pipeline, and the
+//! pluggable sources feeding it.
+
+pub mod alarm;
+pub mod altimeter;
+pub mod derive;
+pub mod diag;
+pub mod feed;
+pub mod flight;
+pub mod link;
+pub mod network;
+pub mod nmea;
+pub mod parse;
+pub mod pipeline;
+pub mod quality;
+pub mod rawlog;
+pub mod record;
+pub mod replay;
+pub mod serial;
+pub mod sim;
+pub mod source;
+pub mod stdin;
+pub mod track;
+pub mod uplink;
+
+pub use pipeline::Pipeline;
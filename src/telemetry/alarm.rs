@@ -0,0 +1,352 @@
+//! src/telemetry/alarm.rs
+//!
+//! Threshold alarms declared per channel in the schema (`alarms = [...]`).
+//!
+//! A rule is raised once its condition has held for `for` seconds of mission
+//! time, and cleared only when the value is back past the threshold by the
+//! rule's `hysteresis`, so a value hovering at the limit does not flap.
+//! Every raise is appended to the alarm log, where it stays (marked cleared)
+//! until the operator acknowledges it.
+
+use std::sync::{Arc, RwLock};
+
+use crate::schema::{AlarmCondition, AlarmSpec, Schema};
+
+/// Entries kept in the alarm log.
+const CAPACITY: usize = 200;
+
+/// One compiled rule with its evaluation state.
+#[derive(Debug, Clone)]
+struct Rule {
+    channel: usize,
+    spec: AlarmSpec,
+    /// Mission time since which the condition holds (not raised yet).
+    pending_since: Option<f64>,
+    /// Log id of the raise while active.
+    active: Option<u64>,
+    /// Previous sample, for rate rules.
+    last: Option<(f64, f64)>,
+}
+
+impl Rule {
+    /// `(triggered, cleared)` for a value (or rate, for rate rules).
+    fn check(&self, v: f64) -> (bool, bool) {
+        let h = self.spec.hysteresis;
+        match self.spec.condition {
+            AlarmCondition::Above { value } => (v > value, v <= value - h),
+            AlarmCondition::Below { value } => (v < value, v >= value + h),
+            AlarmCondition::Outside { min, max } => {
+                (v < min || v > max, v >= min + h && v <= max - h)
+            }
+            AlarmCondition::Rate { limit } => (v.abs() > limit, v.abs() <= limit - h),
+        }
+    }
+}
+
+/// One raised alarm in the log.
+#[derive(Clone, Debug)]
+pub struct AlarmEntry {
+    pub id: u64,
+    /// Mission time of the raise.
+    pub t: f64,
+    pub channel: usize,
+    /// Channel name and condition, e.g. `RSSI ACK below -110`.
+    pub text: String,
+    /// Value (or rate) that raised it.
+    pub value: f64,
+    /// Mission time the condition cleared.
+    pub cleared: Option<f64>,
+    pub acked: bool,
+}
+
+/// Alarm state, fed by the pipeline.
+#[derive(Debug)]
+pub struct Alarms {
+    rules: Vec<Rule>,
+    names: Vec<String>,
+    /// Raised alarms, oldest first (bounded).
+    pub log: Vec<AlarmEntry>,
+    /// Total raises so far (the UI rings the bell when it grows).
+    pub raised: u64,
+}
+
+impl Alarms {
+    /// Compile the alarm rules of every schema channel.
+    pub fn new(schema: &Schema) -> Self {
+        let rules = schema
+            .channels
+            .iter()
+            .enumerate()
+            .flat_map(|(i, c)| {
+                c.alarms.iter().map(move |spec| Rule {
+                    channel: i,
+                    spec: spec.clone(),
+                    pending_since: None,
+                    active: None,
+                    last: None,
+                })
+            })
+            .collect();
+        Self {
+            rules,
+            names: schema.channels.iter().map(|c| c.name.clone()).collect(),
+            log: Vec::new(),
+            raised: 0,
+        }
+    }
+
+    /// Whether the schema declares any alarm.
+    pub fn enabled(&self) -> bool {
+        !self.rules.is_empty()
+    }
+
+    /// Clear every rule and the log (used when a replay rewinds).
+    pub fn reset(&mut self) {
+        for r in &mut self.rules {
+            r.pending_since = None;
+            r.active = None;
+            r.last = None;
+        }
+        self.log.clear();
+    }
+
+    /// Feed the samples decoded from one line received at mission time `t`.
+    pub fn observe(&mut self, t: f64, samples: &[(usize, f64)]) {
+        for r in &mut self.rules {
+            let Some(&(_, y)) = samples.iter().find(|(idx, _)| *idx == r.channel) else {
+                continue;
+            };
+            if !y.is_finite() {
+                continue;
+            }
+            let v = match r.spec.condition {
+                AlarmCondition::Rate { .. } => {
+                    let prev = r.last.replace((t, y));
+                    match prev {
+                        Some((pt, py)) if t > pt => (y - py) / (t - pt),
+                        _ => continue,
+                    }
+                }
+                _ => y,
+            };
+            let (triggered, cleared) = r.check(v);
+            match r.active {
+                None if triggered => {
+                    let since = *r.pending_since.get_or_insert(t);
+                    if t - since >= r.spec.min_duration {
+                        r.pending_since = None;
+                        self.raised += 1;
+                        r.active = Some(self.raised);
+                        if self.log.len() == CAPACITY {
+                            self.log.remove(0);
+                        }
+                        self.log.push(AlarmEntry {
+                            id: self.raised,
+                            t: since,
+                            channel: r.channel,
+                            text: format!(
+                                "{} {}",
+                                self.names[r.channel],
+                                r.spec.condition.describe()
+                            ),
+                            value: v,
+                            cleared: None,
+                            acked: false,
+                        });
+                    }
+                }
+                None => r.pending_since = None,
+                Some(id) if cleared => {
+                    r.active = None;
+                    if let Some(e) = self.log.iter_mut().find(|e| e.id == id) {
+                        e.cleared = Some(t);
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    /// Whether `channel` has an alarm that is still active.
+    pub fn active_on(&self, channel: usize) -> bool {
+        self.rules
+            .iter()
+            .any(|r| r.channel == channel && r.active.is_some())
+    }
+
+    /// Whether `channel` has an alarm that was not acknowledged yet.
+    pub fn unacked_on(&self, channel: usize) -> bool {
+        self.log.iter().any(|e| e.channel == channel && !e.acked)
+    }
+
+    /// Number of log entries not acknowledged yet.
+    pub fn unacked(&self) -> usize {
+        self.log.iter().filter(|e| !e.acked).count()
+    }
+
+    /// Acknowledge every entry; returns how many were pending.
+    pub fn ack_all(&mut self) -> usize {
+        let n = self.unacked();
+        for e in &mut self.log {
+            e.acked = true;
+        }
+        n
+    }
+
+    /// `(threshold, active)` levels of `channel`'s rules to draw on its graph.
+    pub fn thresholds(&self, channel: usize) -> Vec<(f64, bool)> {
+        self.rules
+            .iter()
+            .filter(|r| r.channel == channel)
+            .flat_map(|r| {
+                let levels = match r.spec.condition {
+                    AlarmCondition::Above { value } | AlarmCondition::Below { value } => {
+                        vec![value]
+                    }
+                    AlarmCondition::Outside { min, max } => vec![min, max],
+                    AlarmCondition::Rate { .. } => vec![],
+                };
+                levels.into_iter().map(|y| (y, r.active.is_some()))
+            })
+            .collect()
+    }
+}
+
+/// Alias: Arc<RwLock<Alarms>>
+pub type SharedAlarms = Arc<RwLock<Alarms>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One channel `X` (index 0) with the given `alarms = [...]` rules.
+    fn alarms(rules: &str) -> Alarms {
+        let schema = Schema::parse(&format!(
+            "[[channel]]\nname = \"X\"\nline = \"Received:\"\nfield = 1\nalarms = [{}]\n",
+            rules
+        ))
+        .unwrap();
+        Alarms::new(&schema)
+    }
+
+    /// Feed `(t, value)` samples on channel 0.
+    fn feed(a: &mut Alarms, values: &[(f64, f64)]) {
+        for &(t, v) in values {
+            a.observe(t, &[(0, v)]);
+        }
+    }
+
+    #[test]
+    fn raises_once_the_condition_held_for_long_enough() {
+        let mut a = alarms("{ kind = \"above\", value = 10.0, for = 2.0 }");
+        assert!(a.enabled());
+        feed(&mut a, &[(0.0, 5.0), (1.0, 11.0), (2.0, 12.0)]);
+        assert!(!a.active_on(0));
+        assert!(a.log.is_empty());
+
+        feed(&mut a, &[(3.0, 11.0)]);
+        assert!(a.active_on(0) && a.unacked_on(0));
+        assert_eq!(a.raised, 1);
+        let e = &a.log[0];
+        assert_eq!((e.t, e.value, e.text.as_str()), (1.0, 11.0, "X above 10"));
+        assert_eq!(a.thresholds(0), [(10.0, true)]);
+
+        // still raised: no second entry
+        feed(&mut a, &[(4.0, 15.0)]);
+        assert_eq!((a.raised, a.log.len()), (1, 1));
+    }
+
+    #[test]
+    fn a_short_spike_never_raises() {
+        let mut a = alarms("{ kind = \"above\", value = 10.0, for = 2.0 }");
+        feed(
+            &mut a,
+            &[
+                (0.0, 50.0),
+                (1.0, 50.0),
+                (1.5, 5.0),
+                (2.5, 50.0),
+                (4.0, 50.0),
+            ],
+        );
+        assert!(!a.active_on(0));
+        assert_eq!(a.raised, 0);
+    }
+
+    #[test]
+    fn clears_only_past_the_hysteresis() {
+        let mut a = alarms("{ kind = \"below\", value = -110.0, hysteresis = 5.0 }");
+        feed(&mut a, &[(0.0, -115.0)]);
+        assert!(a.active_on(0));
+        feed(&mut a, &[(1.0, -108.0), (2.0, -106.0)]);
+        assert!(a.active_on(0));
+        assert_eq!(a.log[0].cleared, None);
+
+        feed(&mut a, &[(3.0, -105.0)]);
+        assert!(!a.active_on(0));
+        assert_eq!(a.log[0].cleared, Some(3.0));
+        assert!(a.unacked_on(0));
+        assert_eq!(a.ack_all(), 1);
+        assert!(!a.unacked_on(0));
+        assert_eq!(a.unacked(), 0);
+    }
+
+    #[test]
+    fn outside_band_uses_both_edges() {
+        let mut a = alarms("{ kind = \"outside\", min = 0.0, max = 10.0, hysteresis = 1.0 }");
+        assert_eq!(a.thresholds(0), [(0.0, false), (10.0, false)]);
+        feed(&mut a, &[(0.0, 5.0), (1.0, -0.5)]);
+        assert!(a.active_on(0));
+        feed(&mut a, &[(2.0, 0.5)]);
+        assert!(a.active_on(0));
+        feed(&mut a, &[(3.0, 1.0), (4.0, 10.5)]);
+        assert_eq!(a.raised, 2);
+        assert_eq!(a.log[0].cleared, Some(3.0));
+    }
+
+    #[test]
+    fn rate_alarms_use_the_slope_between_samples() {
+        let mut a = alarms("{ kind = \"rate\", limit = 10.0, hysteresis = 2.0 }");
+        assert!(a.thresholds(0).is_empty());
+        // first sample has no rate; 5 m/s is fine
+        feed(&mut a, &[(0.0, 100.0), (1.0, 105.0)]);
+        assert!(!a.active_on(0));
+        // falling at 20/s raises in either direction
+        feed(&mut a, &[(2.0, 85.0)]);
+        assert!(a.active_on(0));
+        assert_eq!(a.log[0].value, -20.0);
+        // 9/s is within the limit but not past the hysteresis
+        feed(&mut a, &[(3.0, 76.0)]);
+        assert!(a.active_on(0));
+        feed(&mut a, &[(4.0, 68.0)]);
+        assert!(!a.active_on(0));
+        // same timestamp yields no rate
+        feed(&mut a, &[(4.0, 0.0)]);
+        assert!(!a.active_on(0));
+    }
+
+    #[test]
+    fn non_finite_and_other_channels_are_ignored() {
+        let mut a = alarms("{ kind = \"above\", value = 10.0 }");
+        a.observe(0.0, &[(1, 50.0)]);
+        a.observe(1.0, &[(0, f64::INFINITY)]);
+        a.observe(2.0, &[(0, f64::NAN)]);
+        assert_eq!(a.raised, 0);
+    }
+
+    #[test]
+    fn reset_clears_rules_and_log() {
+        let mut a = alarms("{ kind = \"above\", value = 10.0, for = 1.0 }");
+        feed(&mut a, &[(0.0, 20.0), (1.0, 20.0), (10.0, 20.0)]);
+        assert!(a.active_on(0));
+        a.reset();
+        assert!(!a.active_on(0));
+        assert!(a.log.is_empty());
+        // pending time starts over after the reset
+        feed(&mut a, &[(11.0, 20.0)]);
+        assert!(!a.active_on(0));
+        feed(&mut a, &[(12.0, 20.0)]);
+        assert!(a.active_on(0));
+        assert_eq!(a.log[0].t, 11.0);
+    }
+}
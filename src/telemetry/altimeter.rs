@@ -0,0 +1,283 @@
+//! src/telemetry/altimeter.rs
+//!
+//! Altitude recalibration from the channels with `role = "pressure"` and
+//! `role = "altitude"`.
+//!
+//! The CanSat computes its altitude against a hard-coded sea-level pressure,
+//! which is wrong whenever the day's pressure differs. Once a baseline (QNH)
+//! is set here, by hand or by averaging the pressure on the pad ("zero on
+//! pad", which makes the pad read 0 m), every altitude sample is recomputed
+//! from the pressure received in the same line with the standard atmosphere.
+//! The pipeline rewrites the stored altitude history whenever the baseline
+//! changes, so graphs and flight phases stay consistent.
+
+use std::ops::RangeInclusive;
+use std::sync::{Arc, RwLock};
+
+use super::derive::isa_altitude;
+use crate::schema::{ChannelRole, Schema};
+
+/// Plausible sea-level pressures, hPa.
+pub const QNH_RANGE: RangeInclusive<f64> = 850.0..=1100.0;
+
+/// Default "zero on pad" averaging length, seconds.
+pub const ZERO_SECONDS: f64 = 10.0;
+
+/// Where the altitude baseline comes from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Baseline {
+    /// Altitude used as transmitted by the CanSat.
+    Transmitted,
+    /// QNH set by the operator, hPa.
+    Manual(f64),
+    /// QNH from the mean pad pressure, hPa.
+    Pad(f64),
+}
+
+impl Baseline {
+    /// Sea-level pressure in use, hPa (`None` while transmitted).
+    pub fn qnh(&self) -> Option<f64> {
+        match *self {
+            Baseline::Transmitted => None,
+            Baseline::Manual(q) | Baseline::Pad(q) => Some(q),
+        }
+    }
+
+    /// Short name of the source.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Baseline::Transmitted => "transmitted",
+            Baseline::Manual(_) => "manual",
+            Baseline::Pad(_) => "pad",
+        }
+    }
+}
+
+/// Pressure averaging in progress for "zero on pad".
+#[derive(Clone, Copy, Debug)]
+pub struct Zeroing {
+    /// Averaging length, seconds of mission time.
+    pub seconds: f64,
+    /// Mission time of the first averaged sample.
+    pub start: Option<f64>,
+    sum: f64,
+    n: u64,
+}
+
+impl Zeroing {
+    /// Seconds averaged so far at mission time `t`.
+    pub fn elapsed(&self, t: f64) -> f64 {
+        self.start.map_or(0.0, |s| (t - s).max(0.0))
+    }
+}
+
+/// Altimeter state, fed by the pipeline.
+#[derive(Debug)]
+pub struct Altimeter {
+    pressure_channel: Option<usize>,
+    altitude_channel: Option<usize>,
+    pub baseline: Baseline,
+    pub zeroing: Option<Zeroing>,
+    /// Latest `(t, hPa)` pressure reading.
+    pub last_pressure: Option<(f64, f64)>,
+}
+
+impl Altimeter {
+    /// Pick the pressure and altitude channels from the schema roles.
+    pub fn new(schema: &Schema, qnh: Option<f64>) -> Self {
+        Self {
+            pressure_channel: schema.with_role(ChannelRole::Pressure).next(),
+            altitude_channel: schema.with_role(ChannelRole::Altitude).next(),
+            baseline: qnh.map_or(Baseline::Transmitted, Baseline::Manual),
+            zeroing: None,
+            last_pressure: None,
+        }
+    }
+
+    /// `(pressure, altitude)` channel indices, when the schema declares both.
+    pub fn channels(&self) -> Option<(usize, usize)> {
+        Some((self.pressure_channel?, self.altitude_channel?))
+    }
+
+    /// Whether the altitude can be recomputed at all.
+    pub fn enabled(&self) -> bool {
+        self.channels().is_some()
+    }
+
+    /// Abort a pending zeroing (used when a replay rewinds); the baseline stays.
+    pub fn reset(&mut self) {
+        self.zeroing = None;
+        self.last_pressure = None;
+    }
+
+    /// Start averaging the pressure over the next `seconds` of mission time.
+    pub fn start_zeroing(&mut self, seconds: f64) {
+        self.zeroing = Some(Zeroing {
+            seconds,
+            start: None,
+            sum: 0.0,
+            n: 0,
+        });
+    }
+
+    /// Altitude for `pressure` with the current baseline.
+    pub fn altitude(&self, pressure: f64) -> Option<f64> {
+        isa_altitude(pressure, self.baseline.qnh()?)
+    }
+
+    /// Feed the samples of one line at mission time `t`: advance a pending
+    /// zeroing and replace (or add) the altitude sample. Returns the new
+    /// baseline when a zeroing completed with this line.
+    pub fn apply(&mut self, t: f64, samples: &mut Vec<(usize, f64)>) -> Option<Baseline> {
+        let (p_idx, a_idx) = self.channels()?;
+        let &(_, p) = samples.iter().find(|(idx, _)| *idx == p_idx)?;
+        if !(p.is_finite() && p > 0.0) {
+            return None;
+        }
+        self.last_pressure = Some((t, p));
+
+        let mut done = None;
+        if let Some(z) = &mut self.zeroing {
+            let start = *z.start.get_or_insert(t);
+            z.sum += p;
+            z.n += 1;
+            if t - start >= z.seconds {
+                self.baseline = Baseline::Pad(z.sum / z.n as f64);
+                self.zeroing = None;
+                done = Some(self.baseline);
+            }
+        }
+
+        if let Some(alt) = self.altitude(p) {
+            match samples.iter_mut().find(|(idx, _)| *idx == a_idx) {
+                Some(s) => s.1 = alt,
+                None => samples.push((a_idx, alt)),
+            }
+        }
+        done
+    }
+
+    /// One-line `key=value` summary (used by the TCP `baseline` command).
+    pub fn summary(&self) -> String {
+        let mut s = format!("baseline={}", self.baseline.as_str());
+        if let Some(q) = self.baseline.qnh() {
+            s.push_str(&format!(" qnh={:.2}", q));
+        }
+        if let Some((t, _)) = self.last_pressure
+            && let Some(z) = &self.zeroing
+        {
+            s.push_str(&format!(" zeroing={:.1}/{:.1}", z.elapsed(t), z.seconds));
+        }
+        s
+    }
+}
+
+/// Sea-level pressure (hPa) under which `pressure` (hPa) reads `altitude` (m);
+/// inverse of `isa_altitude`.
+pub fn implied_qnh(pressure: f64, altitude: f64) -> Option<f64> {
+    let q = pressure / (1.0 - altitude / 44_330.77).powf(1.0 / 0.190_263);
+    q.is_finite().then_some(q)
+}
+
+/// Alias: Arc<RwLock<Altimeter>>
+pub type SharedAltimeter = Arc<RwLock<Altimeter>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pressure on channel 0, altitude on channel 1.
+    fn altimeter(qnh: Option<f64>) -> Altimeter {
+        let schema = Schema::parse(
+            "[[channel]]\nname = \"P\"\nline = \"Received:\"\nfield = 1\nrole = \"pressure\"\n\n\
+             [[channel]]\nname = \"ALT\"\nline = \"Received:\"\nfield = 2\nrole = \"altitude\"\n",
+        )
+        .unwrap();
+        Altimeter::new(&schema, qnh)
+    }
+
+    #[test]
+    fn transmitted_altitude_is_left_alone() {
+        let mut a = altimeter(None);
+        assert_eq!(a.channels(), Some((0, 1)));
+        let mut samples = vec![(0, 1000.0), (1, 123.0)];
+        assert_eq!(a.apply(0.0, &mut samples), None);
+        assert_eq!(samples, [(0, 1000.0), (1, 123.0)]);
+        assert_eq!(a.last_pressure, Some((0.0, 1000.0)));
+    }
+
+    #[test]
+    fn manual_qnh_rewrites_or_adds_the_altitude() {
+        let mut a = altimeter(Some(1013.25));
+        let mut samples = vec![(0, 1013.25), (1, 123.0)];
+        a.apply(0.0, &mut samples);
+        assert!(samples[1].1.abs() < 1e-9);
+
+        let mut samples = vec![(0, 900.0)];
+        a.apply(1.0, &mut samples);
+        let expected = isa_altitude(900.0, 1013.25).unwrap();
+        assert_eq!(samples, [(0, 900.0), (1, expected)]);
+
+        let mut samples = vec![(0, f64::NAN), (1, 5.0)];
+        assert_eq!(a.apply(2.0, &mut samples), None);
+        assert_eq!(samples[1].1, 5.0);
+    }
+
+    #[test]
+    fn zero_on_pad_averages_the_pressure() {
+        let mut a = altimeter(None);
+        a.start_zeroing(2.0);
+        for (t, p) in [(10.0, 1000.0), (11.0, 1002.0)] {
+            assert_eq!(a.apply(t, &mut vec![(0, p)]), None);
+        }
+        assert_eq!(a.zeroing.unwrap().elapsed(11.0), 1.0);
+        assert!(a.summary().contains("zeroing=1.0/2.0"));
+
+        let mut samples = vec![(0, 1004.0), (1, 50.0)];
+        assert_eq!(a.apply(12.0, &mut samples), Some(Baseline::Pad(1002.0)));
+        assert!(a.zeroing.is_none());
+        assert_eq!(a.baseline.qnh(), Some(1002.0));
+        assert_eq!(samples[1].1, isa_altitude(1004.0, 1002.0).unwrap());
+        assert_eq!(a.summary(), "baseline=pad qnh=1002.00");
+    }
+
+    #[test]
+    fn reset_aborts_zeroing_but_keeps_the_baseline() {
+        let mut a = altimeter(Some(1020.0));
+        a.start_zeroing(5.0);
+        a.apply(0.0, &mut vec![(0, 1000.0)]);
+        a.reset();
+        assert!(a.zeroing.is_none() && a.last_pressure.is_none());
+        assert_eq!(a.baseline, Baseline::Manual(1020.0));
+    }
+
+    #[test]
+    fn implied_qnh_inverts_isa_altitude() {
+        for (p, alt) in [(1013.25, 0.0), (950.0, 420.0), (800.0, 2100.0)] {
+            let q = implied_qnh(p, alt).unwrap();
+            assert!(
+                (isa_altitude(p, q).unwrap() - alt).abs() < 1e-6,
+                "{} hPa at {} m",
+                p,
+                alt
+            );
+        }
+        for q in [980.0, 1013.25, 1040.0] {
+            let alt = isa_altitude(950.0, q).unwrap();
+            assert!((implied_qnh(950.0, alt).unwrap() - q).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn missing_roles_disable_recalibration() {
+        let schema = Schema::parse(
+            "[[channel]]\nname = \"P\"\nline = \"Received:\"\nfield = 1\nrole = \"pressure\"\n",
+        )
+        .unwrap();
+        let mut a = Altimeter::new(&schema, Some(1013.25));
+        assert!(!a.enabled());
+        let mut samples = vec![(0, 900.0)];
+        assert_eq!(a.apply(0.0, &mut samples), None);
+        assert_eq!(samples, [(0, 900.0)]);
+    }
+}
@@ -0,0 +1,196 @@
+//! src/telemetry/derive.rs
+//!
+//! Derived channels: values computed from other channels instead of decoded
+//! from the link (`derive = { kind = "..." }` in the schema).
+//!
+//! - `derivative`: least-squares slope of an input over the last `span`
+//!   seconds, times `scale` (e.g. vertical speed from altitude, `scale = -1`
+//!   for a positive descent rate),
+//! - `isa_altitude`: barometric altitude from pressure with the standard
+//!   atmosphere and a sea-level pressure `qnh`,
+//! - `dew_point`: Magnus formula from temperature and relative humidity.
+//!
+//! A derived value is produced whenever one of its inputs arrives, so it lands
+//! in the graphs, the recording and the trackers exactly like a decoded one.
+//! Inputs must be declared earlier in the schema, which lets derived channels
+//! build on each other.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+
+use crate::schema::{DeriveSpec, Schema};
+
+/// Magnus coefficients over water (Sonntag 1990), valid -45..60 °C.
+const MAGNUS_A: f64 = 17.62;
+const MAGNUS_B: f64 = 243.12;
+
+/// How one derived channel is computed, with input indices resolved.
+#[derive(Debug, Clone)]
+enum Rule {
+    Derivative {
+        input: usize,
+        span: f64,
+        scale: f64,
+        /// `(t, value)` samples within `span`.
+        recent: VecDeque<(f64, f64)>,
+    },
+    IsaAltitude {
+        pressure: usize,
+        qnh: f64,
+    },
+    DewPoint {
+        temperature: usize,
+        humidity: usize,
+    },
+}
+
+impl Rule {
+    fn inputs(&self) -> [Option<usize>; 2] {
+        match self {
+            Rule::Derivative { input, .. } => [Some(*input), None],
+            Rule::IsaAltitude { pressure, .. } => [Some(*pressure), None],
+            Rule::DewPoint {
+                temperature,
+                humidity,
+            } => [Some(*temperature), Some(*humidity)],
+        }
+    }
+}
+
+/// Derived-channel evaluator, compiled from the schema.
+#[derive(Debug, Clone)]
+pub struct Deriver {
+    /// `(output channel index, rule)` in schema order.
+    rules: Vec<(usize, Rule)>,
+    /// Latest value of every channel, for rules with several inputs.
+    latest: Vec<Option<f64>>,
+}
+
+impl Deriver {
+    /// Resolve the `derive` declarations of `schema` (already validated).
+    pub fn new(schema: &Schema) -> Self {
+        let idx = |name: &str| schema.index_of(name).expect("validated derive input");
+        let rules = schema
+            .channels
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| {
+                let rule = match c.derive.as_ref()? {
+                    DeriveSpec::Derivative { of, span, scale } => Rule::Derivative {
+                        input: idx(of),
+                        span: *span,
+                        scale: *scale,
+                        recent: VecDeque::new(),
+                    },
+                    DeriveSpec::IsaAltitude { pressure, qnh } => Rule::IsaAltitude {
+                        pressure: idx(pressure),
+                        qnh: *qnh,
+                    },
+                    DeriveSpec::DewPoint {
+                        temperature,
+                        humidity,
+                    } => Rule::DewPoint {
+                        temperature: idx(temperature),
+                        humidity: idx(humidity),
+                    },
+                };
+                Some((i, rule))
+            })
+            .collect();
+        Self {
+            rules,
+            latest: vec![None; schema.channels.len()],
+        }
+    }
+
+    /// Forget all inputs (used when a replay rewinds).
+    pub fn reset(&mut self) {
+        self.latest.fill(None);
+        for (_, rule) in &mut self.rules {
+            if let Rule::Derivative { recent, .. } = rule {
+                recent.clear();
+            }
+        }
+    }
+
+    /// Append the derived samples for one decoded line received at mission time `t`.
+    pub fn apply(&mut self, t: f64, samples: &mut Vec<(usize, f64)>) {
+        if self.rules.is_empty() {
+            return;
+        }
+        for &(idx, y) in samples.iter() {
+            if let Some(slot) = self.latest.get_mut(idx) {
+                *slot = Some(y);
+            }
+        }
+        for (out, rule) in &mut self.rules {
+            // only re-evaluate when an input arrived with this line
+            let fresh = rule
+                .inputs()
+                .into_iter()
+                .flatten()
+                .find_map(|i| samples.iter().find(|(idx, _)| *idx == i).map(|&(_, v)| v));
+            let Some(fresh) = fresh else {
+                continue;
+            };
+            let latest = &self.latest;
+            let value = match rule {
+                Rule::Derivative {
+                    span,
+                    scale,
+                    recent,
+                    ..
+                } => {
+                    recent.push_back((t, fresh));
+                    while recent.front().is_some_and(|&(ft, _)| t - ft > *span) {
+                        recent.pop_front();
+                    }
+                    slope(recent).map(|s| s * *scale)
+                }
+                Rule::IsaAltitude { qnh, .. } => isa_altitude(fresh, *qnh),
+                Rule::DewPoint {
+                    temperature,
+                    humidity,
+                } => match (latest[*temperature], latest[*humidity]) {
+                    (Some(tc), Some(rh)) => dew_point(tc, rh),
+                    _ => None,
+                },
+            };
+            if let Some(v) = value.filter(|v| v.is_finite()) {
+                samples.push((*out, v));
+                self.latest[*out] = Some(v);
+            }
+        }
+    }
+}
+
+/// Least-squares slope of `(t, v)` points; `None` below two distinct times.
+fn slope(points: &VecDeque<(f64, f64)>) -> Option<f64> {
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mt = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mv = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let (num, den) = points.iter().fold((0.0, 0.0), |(num, den), &(t, v)| {
+        (num + (t - mt) * (v - mv), den + (t - mt) * (t - mt))
+    });
+    (den > 0.0).then(|| num / den)
+}
+
+/// Standard-atmosphere altitude (m) for `pressure` (hPa) given `qnh` (hPa).
+pub fn isa_altitude(pressure: f64, qnh: f64) -> Option<f64> {
+    (pressure > 0.0).then(|| 44_330.77 * (1.0 - (pressure / qnh).powf(0.190_263)))
+}
+
+/// Dew point (°C) for temperature `tc` (°C) and relative humidity `rh` (%).
+pub fn dew_point(tc: f64, rh: f64) -> Option<f64> {
+    if rh <= 0.0 {
+        return None;
+    }
+    let g = (rh.min(100.0) / 100.0).ln() + MAGNUS_A * tc / (MAGNUS_B + tc);
+    Some(MAGNUS_B * g / (MAGNUS_A - g))
+}
+
+/// Alias: Arc<RwLock<Deriver>>
+pub type SharedDeriver = Arc<RwLock<Deriver>>;
@@ -0,0 +1,214 @@
+//! src/telemetry/diag.rs
+//!
+//! Parser diagnostics: how many telemetry lines were malformed, which fields
+//! failed to parse, and the last offending line.
+//!
+//! A line is malformed when its prefix is known but it has fewer tokens than
+//! the schema requires (nothing is decoded), or when some of its fields do not
+//! parse as numbers. By default the fields that did parse are still used; with
+//! `reject` set the whole packet is dropped instead, so the graphs never get
+//! a partial update out of step with each other.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Local};
+
+use super::parse::{Decoded, LineClass};
+use super::quality::WINDOW;
+use crate::schema::Schema;
+
+/// The most recent malformed line.
+#[derive(Clone, Debug)]
+pub struct BadLine {
+    pub time: DateTime<Local>,
+    pub text: String,
+    /// What was wrong, e.g. `short line` or `bad temperature, pressure`.
+    pub reason: String,
+}
+
+/// Parser counters, fed by the pipeline.
+#[derive(Debug)]
+pub struct ParseDiagnostics {
+    names: Vec<String>,
+    /// Drop every sample of a packet with a bad field.
+    pub reject: bool,
+    /// Telemetry lines seen (known prefix or NMEA sentence).
+    pub lines: u64,
+    /// Short lines or lines with at least one bad field.
+    pub malformed: u64,
+    /// Lines too short to decode.
+    pub short: u64,
+    /// Packets dropped because of `reject`.
+    pub rejected: u64,
+    /// Parse failures per channel index.
+    pub field_errors: Vec<u64>,
+    pub last_bad: Option<BadLine>,
+    /// `(mission time, malformed)` of the telemetry lines in the rolling window.
+    window: VecDeque<(f64, bool)>,
+}
+
+impl ParseDiagnostics {
+    pub fn new(schema: &Schema, reject: bool) -> Self {
+        Self {
+            names: schema.channels.iter().map(|c| c.name.clone()).collect(),
+            reject,
+            lines: 0,
+            malformed: 0,
+            short: 0,
+            rejected: 0,
+            field_errors: vec![0; schema.channels.len()],
+            last_bad: None,
+            window: VecDeque::new(),
+        }
+    }
+
+    /// Forget every counter (used when a replay rewinds).
+    pub fn reset(&mut self) {
+        self.lines = 0;
+        self.malformed = 0;
+        self.short = 0;
+        self.rejected = 0;
+        self.field_errors.fill(0);
+        self.last_bad = None;
+        self.window.clear();
+    }
+
+    /// Account for one decoded line received at mission time `t`. Returns
+    /// whether its samples must be dropped.
+    pub fn observe(&mut self, t: f64, text: &str, d: &Decoded) -> bool {
+        if d.class == LineClass::Ignored {
+            return false;
+        }
+        self.lines += 1;
+        let window = WINDOW.as_secs_f64();
+        while self.window.front().is_some_and(|(wt, _)| t - wt > window) {
+            self.window.pop_front();
+        }
+        self.window.push_back((t, d.malformed()));
+        if !d.malformed() {
+            return false;
+        }
+        self.malformed += 1;
+        let reason = if d.short {
+            self.short += 1;
+            "short line".to_string()
+        } else {
+            for name in &d.bad_fields {
+                if let Some(idx) = self.names.iter().position(|n| n == name) {
+                    self.field_errors[idx] += 1;
+                }
+            }
+            format!("bad {}", d.bad_fields.join(", "))
+        };
+        self.last_bad = Some(BadLine {
+            time: Local::now(),
+            text: text.to_string(),
+            reason,
+        });
+        let drop = self.reject && !d.samples.is_empty();
+        if drop {
+            self.rejected += 1;
+        }
+        drop
+    }
+
+    pub fn name(&self, idx: usize) -> &str {
+        self.names.get(idx).map_or("?", String::as_str)
+    }
+
+    /// Percentage of malformed telemetry lines over the rolling window.
+    pub fn bad_pct(&self) -> f64 {
+        if self.window.is_empty() {
+            return 0.0;
+        }
+        let bad = self.window.iter().filter(|(_, b)| *b).count();
+        100.0 * bad as f64 / self.window.len() as f64
+    }
+
+    /// Malformed lines per minute over the rolling window.
+    pub fn bad_per_min(&self) -> f64 {
+        let bad = self.window.iter().filter(|(_, b)| *b).count();
+        bad as f64 * 60.0 / WINDOW.as_secs_f64()
+    }
+
+    /// Counters as `key=value` pairs (remote `parse` command).
+    pub fn summary(&self) -> String {
+        format!(
+            "lines={} malformed={} short={} rejected={} bad_pct={:.2} bad_per_min={:.1} reject={}",
+            self.lines,
+            self.malformed,
+            self.short,
+            self.rejected,
+            self.bad_pct(),
+            self.bad_per_min(),
+            self.reject
+        )
+    }
+}
+
+/// Alias: Arc<RwLock<ParseDiagnostics>>
+pub type SharedDiagnostics = Arc<RwLock<ParseDiagnostics>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telemetry::parse::{Decoder, LineDecoder};
+
+    fn setup(reject: bool) -> (LineDecoder, ParseDiagnostics) {
+        let schema = Schema::parse(
+            "[[channel]]\nname = \"MSG\"\nline = \"Received:\"\nfield = 1\n\n\
+             [[channel]]\nname = \"TEMP\"\nline = \"Received:\"\nfield = 2\n",
+        )
+        .unwrap();
+        (
+            LineDecoder::new(&schema),
+            ParseDiagnostics::new(&schema, reject),
+        )
+    }
+
+    fn feed(p: &mut ParseDiagnostics, d: &LineDecoder, t: f64, l: &str) -> bool {
+        p.observe(t, l, &d.decode_line(l))
+    }
+
+    #[test]
+    fn counts_short_lines_and_bad_fields() {
+        let (d, mut p) = setup(false);
+        assert!(!feed(&mut p, &d, 0.0, "Received: 1 20.5"));
+        assert!(!feed(&mut p, &d, 1.0, "Received: 2"));
+        assert!(!feed(&mut p, &d, 2.0, "Received: 3 nan"));
+        assert!(!feed(&mut p, &d, 3.0, "ACK sent back automatically."));
+        assert_eq!((p.lines, p.malformed, p.short, p.rejected), (3, 2, 1, 0));
+        assert_eq!(p.field_errors, [0, 1]);
+        let last = p.last_bad.as_ref().unwrap();
+        assert_eq!(
+            (last.text.as_str(), last.reason.as_str()),
+            ("Received: 3 nan", "bad TEMP")
+        );
+    }
+
+    #[test]
+    fn reject_drops_partial_packets() {
+        let (d, mut p) = setup(true);
+        assert!(feed(&mut p, &d, 0.0, "Received: 1 err"));
+        // nothing decoded, nothing to drop
+        assert!(!feed(&mut p, &d, 1.0, "Received: err err"));
+        assert_eq!((p.malformed, p.rejected), (2, 1));
+    }
+
+    #[test]
+    fn window_follows_mission_time() {
+        let (d, mut p) = setup(false);
+        feed(&mut p, &d, 0.0, "Received: 1 err");
+        feed(&mut p, &d, 20.0, "Received: 2 20.5");
+        assert_eq!(p.bad_pct(), 50.0);
+        assert_eq!(p.bad_per_min(), 2.0);
+        feed(&mut p, &d, 40.0, "Received: 3 20.5");
+        assert_eq!(p.bad_pct(), 0.0);
+        assert_eq!(p.malformed, 1);
+
+        p.reset();
+        assert_eq!((p.lines, p.malformed, p.bad_pct()), (0, 0, 0.0));
+        assert!(p.last_bad.is_none());
+    }
+}
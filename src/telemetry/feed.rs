@@ -0,0 +1,161 @@
+//! src/telemetry/feed.rs
+//!
+//! Live sample feed for remote subscribers (`subscribe` over TCP).
+//!
+//! Every client registers a bounded queue; the pipeline publishes each pushed
+//! sample to the clients subscribed to its channel, already formatted as one
+//! text or JSON line. A client that does not keep up loses samples (counted in
+//! `dropped`) instead of stalling the ingest thread.
+
+use std::collections::BTreeSet;
+use std::sync::mpsc::{SyncSender, TrySendError};
+use std::sync::{Arc, RwLock};
+
+use crate::schema::Schema;
+
+/// Line format of pushed samples.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeedFormat {
+    /// `DATA <idx> <t> <value>`
+    Text,
+    /// `{"type":"sample","idx":..,"name":..,"t":..,"value":..}`
+    Json,
+}
+
+impl FeedFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeedFormat::Text => "text",
+            FeedFormat::Json => "json",
+        }
+    }
+}
+
+/// One connected client.
+#[derive(Debug)]
+struct Client {
+    id: u64,
+    /// Every channel, including ones subscribed later.
+    all: bool,
+    channels: BTreeSet<usize>,
+    format: FeedFormat,
+    tx: SyncSender<String>,
+    dropped: u64,
+}
+
+impl Client {
+    fn wants(&self, idx: usize) -> bool {
+        self.all || self.channels.contains(&idx)
+    }
+}
+
+/// Subscription registry, fed by the pipeline.
+#[derive(Debug)]
+pub struct Feed {
+    names: Vec<String>,
+    clients: Vec<Client>,
+    next_id: u64,
+}
+
+impl Feed {
+    pub fn new(schema: &Schema) -> Self {
+        Self {
+            names: schema.channels.iter().map(|c| c.name.clone()).collect(),
+            clients: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    /// Register a client queue; returns its id. Nothing is pushed until it
+    /// subscribes.
+    pub fn connect(&mut self, tx: SyncSender<String>) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.clients.push(Client {
+            id,
+            all: false,
+            channels: BTreeSet::new(),
+            format: FeedFormat::Text,
+            tx,
+            dropped: 0,
+        });
+        id
+    }
+
+    /// Forget a client (connection closed).
+    pub fn disconnect(&mut self, id: u64) {
+        self.clients.retain(|c| c.id != id);
+    }
+
+    /// Subscribe `id` to `channel` (`None` = every channel). The format
+    /// applies to everything the client receives.
+    pub fn subscribe(&mut self, id: u64, channel: Option<usize>, format: FeedFormat) {
+        if let Some(c) = self.clients.iter_mut().find(|c| c.id == id) {
+            match channel {
+                Some(idx) => {
+                    c.channels.insert(idx);
+                }
+                None => c.all = true,
+            }
+            c.format = format;
+        }
+    }
+
+    /// Stop pushing `channel` (`None` = everything) to `id`; returns the
+    /// number of samples dropped so far because the client was too slow.
+    pub fn unsubscribe(&mut self, id: u64, channel: Option<usize>) -> u64 {
+        let Some(c) = self.clients.iter_mut().find(|c| c.id == id) else {
+            return 0;
+        };
+        match channel {
+            Some(idx) => {
+                c.channels.remove(&idx);
+            }
+            None => {
+                c.all = false;
+                c.channels.clear();
+            }
+        }
+        c.dropped
+    }
+
+    /// Channels `id` is subscribed to (`None` = every channel).
+    pub fn subscriptions(&self, id: u64) -> Option<Vec<usize>> {
+        let c = self.clients.iter().find(|c| c.id == id)?;
+        (!c.all).then(|| c.channels.iter().copied().collect())
+    }
+
+    /// Push the samples of one line at mission time `t` to every subscriber.
+    pub fn publish(&mut self, t: f64, samples: &[(usize, f64)]) {
+        let names = &self.names;
+        self.clients.retain_mut(|c| {
+            for &(idx, y) in samples {
+                if !c.wants(idx) {
+                    continue;
+                }
+                let line = match c.format {
+                    FeedFormat::Text => format!("DATA {} {:.3} {}\n", idx, t, y),
+                    FeedFormat::Json => {
+                        let v = serde_json::json!({
+                            "type": "sample",
+                            "idx": idx,
+                            "name": names.get(idx),
+                            "t": t,
+                            "value": y,
+                        });
+                        format!("{}\n", v)
+                    }
+                };
+                match c.tx.try_send(line) {
+                    Ok(()) => {}
+                    Err(TrySendError::Full(_)) => c.dropped += 1,
+                    Err(TrySendError::Disconnected(_)) => return false,
+                }
+            }
+            true
+        });
+    }
+}
+
+/// Alias: Arc<RwLock<Feed>>
+pub type SharedFeed = Arc<RwLock<Feed>>;
@@ -0,0 +1,390 @@
+//! src/telemetry/flight.rs
+//!
+//! Flight-phase detection from the channel with `role = "altitude"`.
+//!
+//! The raw altitude is low-pass filtered (exponential, `FILTER_TAU`) and the
+//! vertical speed is taken over the last `SPEED_SPAN` of filtered samples.
+//! The state machine then walks through
+//!
+//! ```text
+//! PAD ──(climbing above ground + LAUNCH_HEIGHT)──▶ ASCENT
+//! ASCENT ──(falling below max - APOGEE_DROP)──▶ APOGEE ──(sinking)──▶ DESCENT
+//! DESCENT ──(still for LANDED_STILL)──▶ LANDED ──(climbing again)──▶ ASCENT
+//! ```
+//!
+//! Each transition is recorded as an event on the mission time axis. The apogee
+//! event is back-dated to the highest filtered sample and the landing event to
+//! the moment the altitude stopped changing.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+
+use crate::schema::{ChannelRole, Schema};
+
+/// Time constant of the altitude low-pass filter, seconds.
+const FILTER_TAU: f64 = 1.0;
+
+/// Time constant of the ground-level estimate while on the pad, seconds.
+const GROUND_TAU: f64 = 10.0;
+
+/// Span over which vertical speed is measured, seconds.
+const SPEED_SPAN: f64 = 2.0;
+
+/// Height above ground that counts as a launch, metres.
+const LAUNCH_HEIGHT: f64 = 15.0;
+
+/// Minimum climb rate confirming a launch, m/s.
+const LAUNCH_SPEED: f64 = 3.0;
+
+/// Drop below the maximum that confirms apogee, metres.
+const APOGEE_DROP: f64 = 5.0;
+
+/// Sink rate that confirms the descent after apogee, m/s.
+const DESCENT_SPEED: f64 = 1.5;
+
+/// Vertical speed below which the CanSat counts as still, m/s.
+const STILL_SPEED: f64 = 1.0;
+
+/// How long it must stay still to count as landed, seconds.
+const LANDED_STILL: f64 = 5.0;
+
+/// Flight phase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlightPhase {
+    Pad,
+    Ascent,
+    Apogee,
+    Descent,
+    Landed,
+}
+
+impl FlightPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlightPhase::Pad => "PAD",
+            FlightPhase::Ascent => "ASCENT",
+            FlightPhase::Apogee => "APOGEE",
+            FlightPhase::Descent => "DESCENT",
+            FlightPhase::Landed => "LANDED",
+        }
+    }
+}
+
+/// A phase transition on the mission time axis.
+#[derive(Clone, Copy, Debug)]
+pub struct FlightEvent {
+    /// Mission time, seconds.
+    pub t: f64,
+    /// Phase entered.
+    pub phase: FlightPhase,
+    /// Filtered altitude at `t`, metres.
+    pub altitude: f64,
+}
+
+/// Flight-state machine, fed by the pipeline.
+#[derive(Debug)]
+pub struct FlightTracker {
+    channel: Option<usize>,
+    pub phase: FlightPhase,
+    /// Every transition so far, oldest first.
+    pub events: Vec<FlightEvent>,
+    /// Filtered altitude, metres.
+    pub altitude: Option<f64>,
+    /// Vertical speed of the filtered altitude, m/s (up positive).
+    pub vertical_speed: Option<f64>,
+    /// Ground level estimated on the pad, metres.
+    pub ground: Option<f64>,
+    /// `(time, altitude)` of the highest filtered sample of the current flight.
+    pub max_altitude: Option<(f64, f64)>,
+    /// Mean sink rate during the descent, m/s (positive).
+    pub descent_rate: Option<f64>,
+    descent_sum: f64,
+    descent_n: u64,
+    /// Filtered `(t, altitude)` samples within `SPEED_SPAN`.
+    recent: VecDeque<(f64, f64)>,
+    /// Start of the current still period.
+    still_since: Option<(f64, f64)>,
+}
+
+impl FlightTracker {
+    /// Pick the altitude channel from the schema roles.
+    pub fn new(schema: &Schema) -> Self {
+        Self::with_channel(schema.with_role(ChannelRole::Altitude).next())
+    }
+
+    fn with_channel(channel: Option<usize>) -> Self {
+        Self {
+            channel,
+            phase: FlightPhase::Pad,
+            events: Vec::new(),
+            altitude: None,
+            vertical_speed: None,
+            ground: None,
+            max_altitude: None,
+            descent_rate: None,
+            descent_sum: 0.0,
+            descent_n: 0,
+            recent: VecDeque::new(),
+            still_since: None,
+        }
+    }
+
+    /// Whether the schema declares an altitude channel.
+    pub fn enabled(&self) -> bool {
+        self.channel.is_some()
+    }
+
+    /// Start over on the pad (used when a replay rewinds).
+    pub fn reset(&mut self) {
+        *self = Self::with_channel(self.channel);
+    }
+
+    /// Shift every stored altitude by `offset` metres (the altitude channel was
+    /// recalibrated), so the recalibration does not look like a climb or drop.
+    pub fn rebase(&mut self, offset: f64) {
+        let levels = self.altitude.iter_mut().chain(self.ground.iter_mut());
+        let points = (self.max_altitude.iter_mut())
+            .chain(self.still_since.iter_mut())
+            .chain(self.recent.iter_mut())
+            .map(|(_, a)| a);
+        let events = self.events.iter_mut().map(|e| &mut e.altitude);
+        for a in levels.chain(points).chain(events) {
+            *a += offset;
+        }
+    }
+
+    /// Feed the samples decoded from one line received at mission time `t`.
+    pub fn observe(&mut self, t: f64, samples: &[(usize, f64)]) {
+        let Some(&(_, raw)) = samples.iter().find(|(idx, _)| Some(*idx) == self.channel) else {
+            return;
+        };
+        if !raw.is_finite() {
+            return;
+        }
+        let dt = self.recent.back().map_or(0.0, |&(pt, _)| (t - pt).max(0.0));
+        let alt = self.filter(t, dt, raw);
+        self.step(t, dt, alt);
+    }
+
+    /// Low-pass filter and update the vertical speed; returns the filtered value.
+    fn filter(&mut self, t: f64, dt: f64, raw: f64) -> f64 {
+        let alt = match self.altitude {
+            Some(prev) => prev + (1.0 - (-dt / FILTER_TAU).exp()) * (raw - prev),
+            None => raw,
+        };
+        self.altitude = Some(alt);
+
+        self.recent.push_back((t, alt));
+        while self
+            .recent
+            .front()
+            .is_some_and(|&(ft, _)| t - ft > SPEED_SPAN)
+        {
+            self.recent.pop_front();
+        }
+        if let Some(&(ft, fa)) = self.recent.front()
+            && t - ft >= SPEED_SPAN / 2.0
+        {
+            self.vertical_speed = Some((alt - fa) / (t - ft));
+        }
+        alt
+    }
+
+    fn step(&mut self, t: f64, dt: f64, alt: f64) {
+        let vs = self.vertical_speed.unwrap_or(0.0);
+        match self.phase {
+            FlightPhase::Pad | FlightPhase::Landed => {
+                let ground = *self.ground.get_or_insert(alt);
+                if alt > ground + LAUNCH_HEIGHT && vs > LAUNCH_SPEED {
+                    self.max_altitude = Some((t, alt));
+                    self.descent_rate = None;
+                    self.descent_sum = 0.0;
+                    self.descent_n = 0;
+                    self.enter(t, FlightPhase::Ascent, alt);
+                } else if vs.abs() < STILL_SPEED {
+                    // follow slow drift (weather, sensor warm-up) on the ground
+                    let a = 1.0 - (-dt / GROUND_TAU).exp();
+                    self.ground = Some(ground + a * (alt - ground));
+                }
+            }
+            FlightPhase::Ascent => {
+                if self.max_altitude.is_none_or(|(_, m)| alt > m) {
+                    self.max_altitude = Some((t, alt));
+                }
+                if let Some((mt, m)) = self.max_altitude
+                    && alt < m - APOGEE_DROP
+                    && vs < 0.0
+                {
+                    self.enter(mt, FlightPhase::Apogee, m);
+                }
+            }
+            FlightPhase::Apogee => {
+                if vs < -DESCENT_SPEED {
+                    self.enter(t, FlightPhase::Descent, alt);
+                }
+            }
+            FlightPhase::Descent => {
+                if vs < 0.0 {
+                    self.descent_sum += -vs;
+                    self.descent_n += 1;
+                    self.descent_rate = Some(self.descent_sum / self.descent_n as f64);
+                }
+                if vs.abs() < STILL_SPEED {
+                    let (st, sa) = *self.still_since.get_or_insert((t, alt));
+                    if t - st >= LANDED_STILL {
+                        self.ground = Some(alt);
+                        self.enter(st, FlightPhase::Landed, sa);
+                    }
+                } else {
+                    self.still_since = None;
+                }
+            }
+        }
+    }
+
+    fn enter(&mut self, t: f64, phase: FlightPhase, altitude: f64) {
+        self.phase = phase;
+        self.still_since = None;
+        self.events.push(FlightEvent { t, phase, altitude });
+    }
+}
+
+/// Alias: Arc<RwLock<FlightTracker>>
+pub type SharedFlight = Arc<RwLock<FlightTracker>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Synthetic flight sampled at 10 Hz: 10 s on the pad at 100 m, climb at
+    /// 20 m/s to 500 m, 2 s at the top, sink at 8 m/s back to 100 m, then still.
+    fn profile(t: f64) -> f64 {
+        match t {
+            t if t < 10.0 => 100.0,
+            t if t < 30.0 => 100.0 + 20.0 * (t - 10.0),
+            t if t < 32.0 => 500.0,
+            t if t < 82.0 => 500.0 - 8.0 * (t - 32.0),
+            _ => 100.0,
+        }
+    }
+
+    /// Feed `profile` up to `end`; returns when each phase was first entered
+    /// (detection time, not the event time).
+    fn fly(f: &mut FlightTracker, end: f64) -> Vec<(FlightPhase, f64)> {
+        let mut seen = Vec::new();
+        for i in 0..(end * 10.0) as usize {
+            let t = i as f64 / 10.0;
+            f.observe(t, &[(0, profile(t))]);
+            if seen.last().is_none_or(|(p, _)| *p != f.phase) && f.phase != FlightPhase::Pad {
+                seen.push((f.phase, t));
+            }
+        }
+        seen
+    }
+
+    #[test]
+    fn full_flight_walks_through_every_phase() {
+        let mut f = FlightTracker::with_channel(Some(0));
+        let seen = fly(&mut f, 100.0);
+        let phases: Vec<FlightPhase> = f.events.iter().map(|e| e.phase).collect();
+        assert_eq!(
+            phases,
+            [
+                FlightPhase::Ascent,
+                FlightPhase::Apogee,
+                FlightPhase::Descent,
+                FlightPhase::Landed
+            ]
+        );
+        assert_eq!(f.phase, FlightPhase::Landed);
+        assert_eq!(seen.len(), 4);
+
+        let launch = f.events[0];
+        assert!(launch.t > 10.0 && launch.t < 13.0, "launch at {}", launch.t);
+    }
+
+    #[test]
+    fn apogee_is_dated_to_the_maximum() {
+        let mut f = FlightTracker::with_channel(Some(0));
+        let seen = fly(&mut f, 100.0);
+        let apogee = f.events[1];
+        let (mt, m) = f.max_altitude.unwrap();
+        assert_eq!((apogee.t, apogee.altitude), (mt, m));
+        assert!(apogee.t > 31.0 && apogee.t < 34.0, "apogee at {}", apogee.t);
+        assert!(m > 480.0 && m <= 500.0, "apogee altitude {}", m);
+        // detected only once the altitude had dropped APOGEE_DROP below it
+        assert!(seen[1].1 > apogee.t);
+    }
+
+    #[test]
+    fn landing_is_dated_to_the_start_of_the_still_period() {
+        let mut f = FlightTracker::with_channel(Some(0));
+        let seen = fly(&mut f, 100.0);
+        let landed = f.events[3];
+        assert!(landed.t > 82.0 && landed.t < 87.0, "landed at {}", landed.t);
+        assert!(seen[3].1 - landed.t >= LANDED_STILL);
+        assert!((landed.altitude - 100.0).abs() < 5.0);
+        assert!((f.ground.unwrap() - 100.0).abs() < 5.0);
+    }
+
+    #[test]
+    fn descent_rate_is_the_mean_sink_rate() {
+        let mut f = FlightTracker::with_channel(Some(0));
+        fly(&mut f, 100.0);
+        let rate = f.descent_rate.unwrap();
+        assert!(rate > 6.0 && rate < 8.5, "descent rate {}", rate);
+    }
+
+    #[test]
+    fn a_short_spike_on_the_pad_is_not_a_launch() {
+        let mut f = FlightTracker::with_channel(Some(0));
+        for i in 0..200 {
+            let t = i as f64 / 10.0;
+            let alt = if i == 100 { 130.0 } else { 100.0 };
+            f.observe(t, &[(0, alt)]);
+        }
+        assert_eq!(f.phase, FlightPhase::Pad);
+        assert!(f.events.is_empty());
+    }
+
+    #[test]
+    fn other_channels_and_non_finite_values_are_ignored() {
+        let mut f = FlightTracker::with_channel(Some(0));
+        f.observe(0.0, &[(1, 500.0)]);
+        f.observe(0.1, &[(0, f64::NAN)]);
+        assert_eq!(f.altitude, None);
+        assert!(!FlightTracker::with_channel(None).enabled());
+    }
+
+    #[test]
+    fn rebase_shifts_levels_without_a_transition() {
+        let mut f = FlightTracker::with_channel(Some(0));
+        fly(&mut f, 60.0);
+        assert_eq!(f.phase, FlightPhase::Descent);
+        let (phase, events, (mt, m)) = (f.phase, f.events.len(), f.max_altitude.unwrap());
+        let (alt, apogee) = (f.altitude.unwrap(), f.events[1].altitude);
+
+        f.rebase(-300.0);
+        assert_eq!((f.phase, f.events.len()), (phase, events));
+        assert_eq!(f.max_altitude, Some((mt, m - 300.0)));
+        assert_eq!(f.altitude, Some(alt - 300.0));
+        assert_eq!(f.events[1].altitude, apogee - 300.0);
+
+        // the next sample, on the new baseline, continues the descent
+        f.observe(60.0, &[(0, profile(60.0) - 300.0)]);
+        assert_eq!((f.phase, f.events.len()), (phase, events));
+        assert!(f.vertical_speed.unwrap() < -DESCENT_SPEED);
+    }
+
+    #[test]
+    fn reset_goes_back_to_the_pad() {
+        let mut f = FlightTracker::with_channel(Some(0));
+        fly(&mut f, 50.0);
+        assert_eq!(f.phase, FlightPhase::Descent);
+        f.reset();
+        assert_eq!(f.phase, FlightPhase::Pad);
+        assert!(f.events.is_empty());
+        assert_eq!((f.altitude, f.max_altitude), (None, None));
+        assert!(f.enabled());
+    }
+}
@@ -0,0 +1,68 @@
+//! src/telemetry/link.rs
+//!
+//! Connection state of a telemetry source, written by the reader thread and
+//! displayed by the title bar.
+
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+/// Phase of the link to the receiver.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkState {
+    /// Trying to open the device for the first time.
+    Connecting,
+    /// Device open and being read.
+    Connected,
+    /// Device failed after having been connected; retrying with backoff.
+    Lost,
+    /// Input exhausted (end of stdin); not retried.
+    Ended,
+}
+
+impl LinkState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkState::Connecting => "CONNECTING",
+            LinkState::Connected => "CONNECTED",
+            LinkState::Lost => "LOST",
+            LinkState::Ended => "ENDED",
+        }
+    }
+}
+
+/// Link status snapshot.
+#[derive(Debug)]
+pub struct LinkStatus {
+    pub state: LinkState,
+    /// Human-readable source description, e.g. `/dev/ttyACM0 @ 115200`.
+    pub source: String,
+    /// Text of the most recent open/read error.
+    pub last_error: Option<String>,
+    /// Number of successful re-opens after the first connection.
+    pub reconnects: u64,
+    /// When `state` last changed.
+    pub since: Instant,
+}
+
+impl LinkStatus {
+    pub fn new(source: &str) -> Self {
+        Self {
+            state: LinkState::Connecting,
+            source: source.to_string(),
+            last_error: None,
+            reconnects: 0,
+            since: Instant::now(),
+        }
+    }
+
+    /// Change state, resetting `since` only on an actual transition.
+    pub fn set_state(&mut self, state: LinkState) {
+        if self.state != state {
+            self.state = state;
+            self.since = Instant::now();
+        }
+    }
+}
+
+/// Alias: Arc<RwLock<LinkStatus>>
+pub type SharedLink = Arc<RwLock<LinkStatus>>;
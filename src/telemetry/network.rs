@@ -0,0 +1,198 @@
+//! src/telemetry/network.rs
+//!
+//! Network telemetry sources, for a receiver attached to a remote machine
+//! (e.g. a Raspberry Pi on the field) that forwards its lines:
+//!
+//! - `tcp:<host>:<port>`: connect to a line server (`ser2net`, `socat
+//!   TCP-LISTEN:5000,fork FILE:/dev/ttyACM0`, ...). The connection is
+//!   reopened with backoff when it drops, is refused, or stays silent for
+//!   `SILENCE_TIMEOUT` (a dead peer that never closed it). Uplink frames are
+//!   written back over the same connection.
+//! - `udp:<bind addr>`: listen for datagrams of one or more lines each, from
+//!   any sender. No uplink (there is no connection to write back to).
+//!
+//! Both accept exactly what the receiver prints on its serial port.
+
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::{TcpStream, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+use super::source::{Frame, TelemetrySource};
+
+/// How long connecting to a TCP server may take.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Read timeout; quiet periods shorter than this are not errors.
+const READ_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A TCP connection without any data for this long is reopened.
+const SILENCE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Largest datagram accepted, bytes.
+const MAX_DATAGRAM: usize = 65_507;
+
+/// Lines from a TCP server.
+pub struct TcpClientSource {
+    addr: String,
+    reader: Option<BufReader<TcpStream>>,
+    /// Partial line carried over a read timeout.
+    line: String,
+    last_data: Instant,
+}
+
+impl TcpClientSource {
+    /// `addr` is `host:port`, resolved again on every connection attempt.
+    pub fn new(addr: &str) -> Self {
+        Self {
+            addr: addr.to_string(),
+            reader: None,
+            line: String::new(),
+            last_data: Instant::now(),
+        }
+    }
+}
+
+impl TelemetrySource for TcpClientSource {
+    fn open(&mut self) -> Result<(), String> {
+        let fail = |e: std::io::Error| format!("cannot connect to {}: {}", self.addr, e);
+        let mut last_err = None;
+        for a in self.addr.to_socket_addrs().map_err(fail)? {
+            match TcpStream::connect_timeout(&a, CONNECT_TIMEOUT) {
+                Ok(stream) => {
+                    stream.set_read_timeout(Some(READ_TIMEOUT)).map_err(fail)?;
+                    self.line.clear();
+                    self.last_data = Instant::now();
+                    self.reader = Some(BufReader::new(stream));
+                    return Ok(());
+                }
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(fail(last_err.unwrap_or_else(|| {
+            std::io::Error::new(ErrorKind::NotFound, "no address")
+        })))
+    }
+
+    fn next_frame(&mut self) -> Result<Frame, String> {
+        let Some(reader) = &mut self.reader else {
+            return Err("not open".into());
+        };
+        match reader.read_line(&mut self.line) {
+            Ok(0) => Err("connection closed".to_string()),
+            Ok(_) => {
+                self.last_data = Instant::now();
+                let line = self.line.trim().to_string();
+                self.line.clear();
+                Ok(if line.is_empty() {
+                    Frame::Idle
+                } else {
+                    Frame::Line(line)
+                })
+            }
+            // partial data stays in `line` and is completed by the next read
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted
+                ) =>
+            {
+                if self.last_data.elapsed() > SILENCE_TIMEOUT {
+                    Err(format!("no data for {} s", SILENCE_TIMEOUT.as_secs()))
+                } else {
+                    Ok(Frame::Idle)
+                }
+            }
+            Err(e) => Err(format!("read error: {}", e)),
+        }
+    }
+
+    fn status(&self) -> String {
+        format!("tcp {}", self.addr)
+    }
+
+    fn close(&mut self) {
+        self.reader = None;
+    }
+
+    fn writer(&mut self) -> Result<Option<Box<dyn Write + Send>>, String> {
+        match &self.reader {
+            Some(r) => r
+                .get_ref()
+                .try_clone()
+                .map(|w| Some(Box::new(w) as Box<dyn Write + Send>))
+                .map_err(|e| e.to_string()),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Lines received as UDP datagrams.
+pub struct UdpSource {
+    bind: String,
+    socket: Option<UdpSocket>,
+    buf: Vec<u8>,
+    /// Lines of the last datagram not handed out yet.
+    pending: VecDeque<String>,
+}
+
+impl UdpSource {
+    /// Listen on `bind` (`host:port`, e.g. `0.0.0.0:5000`).
+    pub fn new(bind: &str) -> Self {
+        Self {
+            bind: bind.to_string(),
+            socket: None,
+            buf: vec![0; MAX_DATAGRAM],
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl TelemetrySource for UdpSource {
+    fn open(&mut self) -> Result<(), String> {
+        let fail = |e: std::io::Error| format!("cannot listen on udp {}: {}", self.bind, e);
+        let socket = UdpSocket::bind(&self.bind).map_err(fail)?;
+        socket.set_read_timeout(Some(READ_TIMEOUT)).map_err(fail)?;
+        self.pending.clear();
+        self.socket = Some(socket);
+        Ok(())
+    }
+
+    fn next_frame(&mut self) -> Result<Frame, String> {
+        if let Some(line) = self.pending.pop_front() {
+            return Ok(Frame::Line(line));
+        }
+        let Some(socket) = &self.socket else {
+            return Err("not open".into());
+        };
+        match socket.recv_from(&mut self.buf) {
+            Ok((n, _)) => {
+                self.pending.extend(
+                    String::from_utf8_lossy(&self.buf[..n])
+                        .lines()
+                        .map(str::trim)
+                        .filter(|l| !l.is_empty())
+                        .map(str::to_string),
+                );
+                Ok(self.pending.pop_front().map_or(Frame::Idle, Frame::Line))
+            }
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted
+                ) =>
+            {
+                Ok(Frame::Idle)
+            }
+            Err(e) => Err(format!("receive error: {}", e)),
+        }
+    }
+
+    fn status(&self) -> String {
+        format!("udp {}", self.bind)
+    }
+
+    fn close(&mut self) {
+        self.socket = None;
+    }
+}
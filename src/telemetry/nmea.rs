@@ -0,0 +1,232 @@
+//! src/telemetry/nmea.rs
+//!
+//! Minimal NMEA 0183 decoding for GPS sentences forwarded by the receiver.
+//!
+//! Only what the monitor needs is extracted, from `GGA` (fix data) and `RMC`
+//! (recommended minimum) sentences of any talker (`$GP`, `$GN`, `$GL`, ...):
+//!
+//! ```text
+//! $GPGGA,123519.00,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*69
+//! $GPRMC,123519.00,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*44
+//! ```
+//!
+//! A sentence with a `*hh` checksum is rejected when the checksum does not
+//! match. Position fields are only reported while the receiver has a fix.
+
+use serde::Deserialize;
+
+/// GPS quantity a schema channel can be fed from (`nmea = "..."`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NmeaField {
+    /// Decimal degrees, north positive.
+    Latitude,
+    /// Decimal degrees, east positive.
+    Longitude,
+    /// Altitude above mean sea level, metres (GGA).
+    Altitude,
+    /// Satellites in use (GGA).
+    Satellites,
+    /// Horizontal dilution of precision (GGA).
+    Hdop,
+    /// UTC time of the fix, seconds since midnight.
+    FixTime,
+}
+
+/// Values carried by one sentence; absent fields are `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GpsFix {
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub altitude: Option<f64>,
+    pub satellites: Option<f64>,
+    pub hdop: Option<f64>,
+    pub fix_time: Option<f64>,
+}
+
+impl GpsFix {
+    /// Value for one schema field.
+    pub fn get(&self, field: NmeaField) -> Option<f64> {
+        match field {
+            NmeaField::Latitude => self.latitude,
+            NmeaField::Longitude => self.longitude,
+            NmeaField::Altitude => self.altitude,
+            NmeaField::Satellites => self.satellites,
+            NmeaField::Hdop => self.hdop,
+            NmeaField::FixTime => self.fix_time,
+        }
+    }
+}
+
+/// Find and decode the NMEA sentence in `line` (text before `$` is ignored).
+///
+/// Returns `None` for anything that is not a valid GGA or RMC sentence.
+pub fn parse_sentence(line: &str) -> Option<GpsFix> {
+    let start = line.find('$')?;
+    let body = line[start + 1..].trim_end();
+    let body = match body.split_once('*') {
+        Some((data, sum)) => {
+            let expected = u8::from_str_radix(sum.get(..2)?, 16).ok()?;
+            if checksum(data) != expected {
+                return None;
+            }
+            data
+        }
+        None => body,
+    };
+
+    let f: Vec<&str> = body.split(',').collect();
+    let kind = f.first()?.get(2..)?;
+    let num = |i: usize| f.get(i).and_then(|v| v.parse::<f64>().ok());
+
+    let mut fix = GpsFix::default();
+    match kind {
+        "GGA" => {
+            fix.fix_time = parse_time(f.get(1)?);
+            fix.satellites = num(7);
+            fix.hdop = num(8);
+            // quality 0 = no fix
+            if num(6).is_some_and(|q| q > 0.0) {
+                fix.latitude = parse_coord(f.get(2)?, f.get(3)?, 2);
+                fix.longitude = parse_coord(f.get(4)?, f.get(5)?, 3);
+                fix.altitude = num(9);
+            }
+        }
+        "RMC" => {
+            fix.fix_time = parse_time(f.get(1)?);
+            // status A = valid, V = receiver warning
+            if f.get(2) == Some(&"A") {
+                fix.latitude = parse_coord(f.get(3)?, f.get(4)?, 2);
+                fix.longitude = parse_coord(f.get(5)?, f.get(6)?, 3);
+            }
+        }
+        _ => return None,
+    }
+    Some(fix)
+}
+
+/// XOR of all bytes between `$` and `*`.
+pub fn checksum(body: &str) -> u8 {
+    body.bytes().fold(0, |acc, b| acc ^ b)
+}
+
+/// `hhmmss.ss` into seconds since midnight.
+fn parse_time(s: &str) -> Option<f64> {
+    if s.len() < 6 {
+        return None;
+    }
+    // `get` rather than indexing: the field is untrusted and may not be ASCII
+    let h: f64 = s.get(0..2)?.parse().ok()?;
+    let m: f64 = s.get(2..4)?.parse().ok()?;
+    let sec: f64 = s.get(4..)?.parse().ok()?;
+    Some(h * 3600.0 + m * 60.0 + sec)
+}
+
+/// `(d)ddmm.mmmm` plus hemisphere into signed decimal degrees.
+fn parse_coord(value: &str, hemisphere: &str, degree_digits: usize) -> Option<f64> {
+    if value.len() <= degree_digits {
+        return None;
+    }
+    let deg: f64 = value.get(..degree_digits)?.parse().ok()?;
+    let min: f64 = value.get(degree_digits..)?.parse().ok()?;
+    let v = deg + min / 60.0;
+    match hemisphere {
+        "N" | "E" => Some(v),
+        "S" | "W" => Some(-v),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GGA: &str = "$GPGGA,123519.00,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*69";
+    const RMC: &str = "$GPRMC,123519.00,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*44";
+
+    fn close(a: Option<f64>, b: f64) -> bool {
+        a.is_some_and(|a| (a - b).abs() < 1e-9)
+    }
+
+    #[test]
+    fn gga_fix() {
+        let fix = parse_sentence(GGA).unwrap();
+        assert!(close(fix.latitude, 48.0 + 7.038 / 60.0));
+        assert!(close(fix.longitude, 11.0 + 31.0 / 60.0));
+        assert_eq!(fix.altitude, Some(545.4));
+        assert_eq!(fix.satellites, Some(8.0));
+        assert_eq!(fix.hdop, Some(0.9));
+        assert_eq!(fix.fix_time, Some(12.0 * 3600.0 + 35.0 * 60.0 + 19.0));
+    }
+
+    #[test]
+    fn rmc_fix() {
+        let fix = parse_sentence(RMC).unwrap();
+        assert!(close(fix.latitude, 48.0 + 7.038 / 60.0));
+        assert!(close(fix.longitude, 11.0 + 31.0 / 60.0));
+        assert_eq!(fix.altitude, None);
+        assert_eq!(fix.fix_time, Some(45319.0));
+    }
+
+    #[test]
+    fn text_before_dollar_is_ignored() {
+        let fix = parse_sentence(&format!("GPS: {}", GGA)).unwrap();
+        assert_eq!(fix.altitude, Some(545.4));
+    }
+
+    #[test]
+    fn southern_and_western_hemispheres_are_negative() {
+        let body = "GNGGA,000000,3351.000,S,15112.000,W,1,05,1.2,20.0,M,,M,,";
+        let fix = parse_sentence(&format!("${}*{:02X}", body, checksum(body))).unwrap();
+        assert!(close(fix.latitude, -(33.0 + 51.0 / 60.0)));
+        assert!(close(fix.longitude, -(151.0 + 12.0 / 60.0)));
+    }
+
+    #[test]
+    fn no_fix_keeps_only_time_and_quality_fields() {
+        let body = "GPGGA,123519.00,4807.038,N,01131.000,E,0,00,99.9,,M,,M,,";
+        let fix = parse_sentence(&format!("${}*{:02X}", body, checksum(body))).unwrap();
+        assert_eq!(fix.latitude, None);
+        assert_eq!(fix.longitude, None);
+        assert_eq!(fix.satellites, Some(0.0));
+        assert_eq!(fix.fix_time, Some(45319.0));
+    }
+
+    #[test]
+    fn bad_checksum_is_rejected() {
+        assert_eq!(parse_sentence(&GGA.replace("*69", "*68")), None);
+        assert_eq!(parse_sentence(&GGA.replace("545.4", "545.5")), None);
+        assert_eq!(parse_sentence(&GGA.replace("*69", "*6")), None);
+    }
+
+    #[test]
+    fn sentence_without_checksum_is_accepted() {
+        let fix = parse_sentence(GGA.trim_end_matches("*69")).unwrap();
+        assert_eq!(fix.satellites, Some(8.0));
+    }
+
+    #[test]
+    fn other_sentences_and_text_are_ignored() {
+        assert_eq!(parse_sentence("$GPGSV,3,1,11,03,03,111,00*74"), None);
+        assert_eq!(parse_sentence("Received: 136 -91.0"), None);
+        assert_eq!(parse_sentence("$"), None);
+    }
+
+    #[test]
+    fn non_ascii_fields_do_not_panic() {
+        let fix =
+            parse_sentence("$GPGGA,aé1234,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*45")
+                .unwrap();
+        assert_eq!(fix.fix_time, None);
+        assert_eq!(fix.altitude, Some(545.4));
+
+        let fix =
+            parse_sentence("$GPGGA,123519.00,48é7.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*33")
+                .unwrap();
+        assert_eq!(fix.latitude, None);
+        assert!(close(fix.longitude, 11.0 + 31.0 / 60.0));
+
+        assert_eq!(parse_sentence("$Gé"), None);
+        assert_eq!(parse_sentence("$GPGGA,1é,*"), None);
+    }
+}
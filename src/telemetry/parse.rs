@@ -0,0 +1,268 @@
+//! src/telemetry/parse.rs
+//!
+//! Schema-driven decoding of receiver lines into `(channel name, value)` samples.
+//!
+//! Example accepted input (default schema):
+//! ----------------------------------------
+//! Received:  136  -91.0  18.45  995.85  58.93  300.045200
+//! RSSI_PACKET: -89.5 dBm
+//! ACK sent back automatically.
+//! $GPGGA,123519.00,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*69
+//! ----------------------------------------
+//!
+//! Channels declared `optional` do not count towards a prefix's minimum token
+//! count, so GPS fields appended to `Received:` are picked up when present
+//! without dropping packets that lack them. Channels with an `nmea` source
+//! are fed from NMEA sentences found anywhere in a line.
+
+use std::collections::HashMap;
+
+use super::nmea::{NmeaField, parse_sentence};
+use crate::schema::Schema;
+
+/// How much of a line the decoder understood (raw terminal coloring).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineClass {
+    /// Every declared field decoded.
+    Parsed,
+    /// Known prefix or NMEA sentence, but too short or with fields that
+    /// failed to parse.
+    Partial,
+    /// Not telemetry (unknown prefix, banners, tracebacks, ...).
+    Ignored,
+}
+
+/// Result of decoding one line, borrowing the channel names from the decoder.
+#[derive(Clone, Debug)]
+pub struct Decoded<'a> {
+    /// `(channel name, value)` for every field that decoded.
+    pub samples: Vec<(&'a str, f64)>,
+    pub class: LineClass,
+    /// Channels whose field was present but did not parse as a number.
+    pub bad_fields: Vec<&'a str>,
+    /// Known prefix with fewer tokens than the schema requires.
+    pub short: bool,
+}
+
+impl Decoded<'_> {
+    fn new(class: LineClass) -> Self {
+        Self {
+            samples: Vec::new(),
+            class,
+            bad_fields: Vec::new(),
+            short: false,
+        }
+    }
+
+    /// Whether the line is malformed telemetry (short or with bad fields).
+    pub fn malformed(&self) -> bool {
+        self.short || !self.bad_fields.is_empty()
+    }
+}
+
+/// Turns received lines into named samples.
+///
+/// Sources only cut their input into lines; the pipeline decodes them with a
+/// `Decoder`, normally the schema-driven `LineDecoder`, and maps the names onto
+/// the schema's channels (names it does not know are ignored).
+pub trait Decoder: Send + Sync {
+    /// Decode one line (without line ending).
+    fn decode_line<'a>(&'a self, line: &str) -> Decoded<'a>;
+}
+
+/// All channels fed by one line prefix.
+#[derive(Debug, Clone)]
+struct PrefixRule {
+    /// Minimum token count (prefix included) for the line to be decoded.
+    min_tokens: usize,
+    /// `(channel index, token index)` pairs.
+    fields: Vec<(usize, usize)>,
+}
+
+/// Decoder compiled from the schema's `line`/`field` declarations.
+#[derive(Debug, Clone)]
+pub struct LineDecoder {
+    /// Channel names by schema index.
+    names: Vec<String>,
+    rules: HashMap<String, PrefixRule>,
+    /// `(channel index, GPS quantity)` pairs fed from NMEA sentences.
+    nmea: Vec<(usize, NmeaField)>,
+}
+
+impl LineDecoder {
+    /// Group the schema channels by line prefix.
+    pub fn new(schema: &Schema) -> Self {
+        let mut rules: HashMap<String, PrefixRule> = HashMap::new();
+        let mut nmea = Vec::new();
+        for (idx, c) in schema.channels.iter().enumerate() {
+            if let Some(field) = c.nmea {
+                nmea.push((idx, field));
+            }
+            if c.line.is_empty() {
+                continue;
+            }
+            let rule = rules.entry(c.line.clone()).or_insert(PrefixRule {
+                min_tokens: 0,
+                fields: Vec::new(),
+            });
+            if !c.optional {
+                rule.min_tokens = rule.min_tokens.max(c.field + 1);
+            }
+            rule.fields.push((idx, c.field));
+        }
+        Self {
+            names: schema.channels.iter().map(|c| c.name.clone()).collect(),
+            rules,
+            nmea,
+        }
+    }
+}
+
+impl Decoder for LineDecoder {
+    /// Decode a single line: the samples found, how well the line matched
+    /// the schema, and what was wrong with it.
+    ///
+    /// Lines with an unknown prefix or fewer tokens than the schema requires
+    /// yield no samples; individual fields that are missing (optional) or fail
+    /// to parse as `f64` are skipped (see `Decoded::bad_fields`).
+    fn decode_line<'a>(&'a self, l: &str) -> Decoded<'a> {
+        if !self.nmea.is_empty()
+            && let Some(fix) = parse_sentence(l)
+        {
+            let mut out = Decoded::new(LineClass::Parsed);
+            for &(idx, field) in &self.nmea {
+                if let Some(val) = fix.get(field) {
+                    out.samples.push((&self.names[idx], val));
+                }
+            }
+            if out.samples.is_empty() {
+                out.class = LineClass::Partial;
+            }
+            return out;
+        }
+        let parts: Vec<&str> = l.split_whitespace().collect();
+        let Some(rule) = parts.first().and_then(|p| self.rules.get(*p)) else {
+            return Decoded::new(LineClass::Ignored);
+        };
+        let mut out = Decoded::new(LineClass::Parsed);
+        if parts.len() < rule.min_tokens {
+            out.class = LineClass::Partial;
+            out.short = true;
+            return out;
+        }
+        for &(idx, field) in &rule.fields {
+            match parts.get(field).map(|v| v.parse::<f64>()) {
+                Some(Ok(val)) if val.is_finite() => out.samples.push((&self.names[idx], val)),
+                // a missing optional field is fine, a garbled or non-finite one
+                // ("nan", "inf") is not
+                Some(_) => out.bad_fields.push(&self.names[idx]),
+                None => {}
+            }
+        }
+        if !out.bad_fields.is_empty() {
+            out.class = LineClass::Partial;
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decoder() -> LineDecoder {
+        LineDecoder::new(
+            &Schema::parse(
+                "[[channel]]\nname = \"MSG\"\nline = \"Received:\"\nfield = 1\n\n\
+                 [[channel]]\nname = \"TEMP\"\nline = \"Received:\"\nfield = 3\n\n\
+                 [[channel]]\nname = \"RSSI\"\nline = \"RSSI_PACKET:\"\nfield = 1\n",
+            )
+            .unwrap(),
+        )
+    }
+
+    /// `(samples, class)` of one line.
+    fn decode<'a>(d: &'a LineDecoder, l: &str) -> (Vec<(&'a str, f64)>, LineClass) {
+        let out = d.decode_line(l);
+        (out.samples, out.class)
+    }
+
+    #[test]
+    fn decodes_every_field_of_a_known_prefix() {
+        let d = decoder();
+        assert_eq!(
+            decode(&d, "Received:  136  -91.0  18.45  995.85"),
+            (vec![("MSG", 136.0), ("TEMP", 18.45)], LineClass::Parsed)
+        );
+        assert_eq!(
+            decode(&d, "RSSI_PACKET: -89.5 dBm"),
+            (vec![("RSSI", -89.5)], LineClass::Parsed)
+        );
+        assert!(!d.decode_line("RSSI_PACKET: -89.5 dBm").malformed());
+    }
+
+    #[test]
+    fn short_lines_are_flagged_and_unknown_prefixes_ignored() {
+        let d = decoder();
+        // field 3 needs four tokens
+        let short = d.decode_line("Received: 136 -91.0");
+        assert_eq!(
+            (short.samples.len(), short.class, short.short),
+            (0, LineClass::Partial, true)
+        );
+        assert!(short.malformed());
+        assert_eq!(
+            decode(&d, "ACK sent back automatically."),
+            (vec![], LineClass::Ignored)
+        );
+        assert_eq!(decode(&d, ""), (vec![], LineClass::Ignored));
+    }
+
+    #[test]
+    fn optional_fields_do_not_raise_the_minimum() {
+        let d = LineDecoder::new(
+            &Schema::parse(
+                "[[channel]]\nname = \"MSG\"\nline = \"Received:\"\nfield = 1\n\n\
+                 [[channel]]\nname = \"LAT\"\nline = \"Received:\"\nfield = 3\n\
+                 optional = true\nnmea = \"latitude\"\n",
+            )
+            .unwrap(),
+        );
+        assert_eq!(
+            decode(&d, "Received: 136"),
+            (vec![("MSG", 136.0)], LineClass::Parsed)
+        );
+        assert_eq!(
+            decode(&d, "Received: 136 -91 48.5"),
+            (vec![("MSG", 136.0), ("LAT", 48.5)], LineClass::Parsed)
+        );
+        let (lat, class) = decode(
+            &d,
+            "$GPGGA,123519.00,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*69",
+        );
+        assert_eq!((lat.len(), class), (1, LineClass::Parsed));
+        assert!((lat[0].1 - (48.0 + 7.038 / 60.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn unparsable_fields_are_bad() {
+        let d = decoder();
+        let out = d.decode_line("Received: 136 -91.0 err");
+        assert_eq!(out.samples, [("MSG", 136.0)]);
+        assert_eq!(
+            (out.class, out.bad_fields),
+            (LineClass::Partial, vec!["TEMP"])
+        );
+    }
+
+    #[test]
+    fn non_finite_values_are_bad() {
+        let d = decoder();
+        for bad in ["nan", "NaN", "inf", "-inf", "infinity"] {
+            let out = d.decode_line(&format!("Received: 136 -91.0 {}", bad));
+            assert_eq!(out.samples, [("MSG", 136.0)], "{}", bad);
+            assert_eq!(out.bad_fields, ["TEMP"], "{}", bad);
+            assert!(out.malformed());
+        }
+    }
+}
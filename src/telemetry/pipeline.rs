@@ -0,0 +1,408 @@
+//! src/telemetry/pipeline.rs
+//!
+//! The ingest pipeline shared by every telemetry source: decode a line with the
+//! schema decoder (keeping the raw line for the terminal panel, counting parse
+//! errors and dropping malformed packets when asked to), recompute the
+//! altitude from the operator's baseline, compute the derived channels, record
+//! it, check the alarm rules, update link-quality statistics, the GPS track,
+//! the flight phase and the uplink command acknowledgements, push the resulting
+//! samples into the shared graphs, and publish them to remote subscribers.
+//!
+//! Samples are stamped with seconds since the pipeline was created (receive
+//! time) unless the source supplies its own timestamp via `ingest_at`.
+//!
+//! Every source gets its own clone of the pipeline; `with_channels` restricts
+//! a clone to the channels its source was mapped to, so two inputs carrying
+//! the same prefix (e.g. two receivers) do not interleave their values.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+use super::alarm::SharedAlarms;
+use super::altimeter::{Baseline, QNH_RANGE, SharedAltimeter, implied_qnh};
+use super::derive::{Deriver, SharedDeriver, isa_altitude};
+use super::diag::SharedDiagnostics;
+use super::feed::SharedFeed;
+use super::flight::{FlightPhase, SharedFlight};
+use super::parse::{Decoder, LineDecoder};
+use super::quality::SharedQuality;
+use super::rawlog::SharedRawLog;
+use super::record::SharedRecorder;
+use super::track::SharedTrack;
+use super::uplink::SharedUplink;
+use crate::graph::shared::SharedGraph;
+use crate::schema::Schema;
+
+/// Cheap-to-clone handle that sources use to feed received lines.
+#[derive(Clone)]
+pub struct Pipeline {
+    pub graphs: Vec<SharedGraph>,
+    decoder: Arc<dyn Decoder>,
+    /// Schema index of every channel name.
+    index: Arc<HashMap<String, usize>>,
+    /// Decoded channels this handle may feed, by index (`None`: all).
+    only: Option<Arc<[bool]>>,
+    deriver: SharedDeriver,
+    recorder: Option<SharedRecorder>,
+    quality: Option<SharedQuality>,
+    track: Option<SharedTrack>,
+    flight: Option<SharedFlight>,
+    altimeter: Option<SharedAltimeter>,
+    alarms: Option<SharedAlarms>,
+    feed: Option<SharedFeed>,
+    uplink: Option<SharedUplink>,
+    rawlog: Option<SharedRawLog>,
+    diagnostics: Option<SharedDiagnostics>,
+    /// Mission time origin for receive timestamps.
+    epoch: Instant,
+}
+
+impl Pipeline {
+    /// Create a pipeline for `graphs`, which must be built from the same `schema`.
+    pub fn new(schema: &Schema, graphs: Vec<SharedGraph>) -> Self {
+        Self {
+            graphs,
+            decoder: Arc::new(LineDecoder::new(schema)),
+            index: Arc::new(
+                schema
+                    .channels
+                    .iter()
+                    .enumerate()
+                    .map(|(i, c)| (c.name.clone(), i))
+                    .collect(),
+            ),
+            only: None,
+            deriver: Arc::new(RwLock::new(Deriver::new(schema))),
+            recorder: None,
+            quality: None,
+            track: None,
+            flight: None,
+            altimeter: None,
+            alarms: None,
+            feed: None,
+            uplink: None,
+            rawlog: None,
+            diagnostics: None,
+            epoch: Instant::now(),
+        }
+    }
+
+    /// Append every ingested line to `recorder`.
+    pub fn with_recorder(mut self, recorder: SharedRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Feed every decoded sample to the link-quality tracker.
+    pub fn with_quality(mut self, quality: SharedQuality) -> Self {
+        self.quality = Some(quality);
+        self
+    }
+
+    /// Collect GPS positions for the track map.
+    pub fn with_track(mut self, track: SharedTrack) -> Self {
+        self.track = Some(track);
+        self
+    }
+
+    /// Run flight-phase detection on the altitude channel.
+    pub fn with_flight(mut self, flight: SharedFlight) -> Self {
+        self.flight = Some(flight);
+        self
+    }
+
+    /// Recompute the altitude from pressure with the operator's baseline.
+    pub fn with_altimeter(mut self, altimeter: SharedAltimeter) -> Self {
+        self.altimeter = Some(altimeter);
+        self
+    }
+
+    /// Check every sample against the schema's alarm rules.
+    pub fn with_alarms(mut self, alarms: SharedAlarms) -> Self {
+        self.alarms = Some(alarms);
+        self
+    }
+
+    /// Publish every pushed sample to remote subscribers.
+    pub fn with_feed(mut self, feed: SharedFeed) -> Self {
+        self.feed = Some(feed);
+        self
+    }
+
+    /// Keep every received line, classified, for the raw terminal.
+    pub fn with_rawlog(mut self, rawlog: SharedRawLog) -> Self {
+        self.rawlog = Some(rawlog);
+        self
+    }
+
+    /// Count parse errors, and drop malformed packets if it says so.
+    pub fn with_diagnostics(mut self, diagnostics: SharedDiagnostics) -> Self {
+        self.diagnostics = Some(diagnostics);
+        self
+    }
+
+    /// Correlate uplink commands with the receiver's ACKs and packet counter.
+    pub fn with_uplink(mut self, uplink: SharedUplink) -> Self {
+        self.uplink = Some(uplink);
+        self
+    }
+
+    /// Feed only the decoded channels called `names` (derived channels still
+    /// follow their inputs). Fails on a name the schema does not declare.
+    pub fn with_channels(mut self, names: &[String]) -> Result<Self, String> {
+        let mut only = vec![false; self.index.len()];
+        for name in names {
+            let idx = self
+                .index
+                .get(name)
+                .ok_or_else(|| format!("unknown channel {:?}", name))?;
+            only[*idx] = true;
+        }
+        self.only = Some(only.into());
+        Ok(self)
+    }
+
+    /// Set the sea-level pressure (hPa) and recompute the altitude history.
+    pub fn set_qnh(&self, qnh: f64) -> Result<(), String> {
+        if !QNH_RANGE.contains(&qnh) {
+            return Err(format!(
+                "QNH {} hPa is outside {}..={} hPa",
+                qnh,
+                QNH_RANGE.start(),
+                QNH_RANGE.end()
+            ));
+        }
+        let alt = self.enabled_altimeter()?;
+        {
+            let mut a = alt.write().unwrap();
+            a.baseline = Baseline::Manual(qnh);
+            a.zeroing = None;
+        }
+        self.recalibrate();
+        Ok(())
+    }
+
+    /// Move the baseline by `delta` hPa. Starting from the transmitted
+    /// altitude, the baseline that reproduces the latest altitude is nudged.
+    pub fn nudge_qnh(&self, delta: f64) -> Result<f64, String> {
+        let alt = self.enabled_altimeter()?;
+        let (channels, baseline, pressure) = {
+            let a = alt.read().unwrap();
+            (a.channels(), a.baseline, a.last_pressure)
+        };
+        let current = match (baseline.qnh(), channels, pressure) {
+            (Some(q), _, _) => q,
+            (None, Some((_, a_idx)), Some((_, p))) => self.graphs[a_idx]
+                .read()
+                .ok()
+                .and_then(|g| g.data.history.back().copied())
+                .and_then(|(_, h)| implied_qnh(p, h))
+                .ok_or("no altitude received yet")?,
+            _ => return Err("no pressure received yet".into()),
+        };
+        let qnh = ((current + delta) * 100.0).round() / 100.0;
+        self.set_qnh(qnh)?;
+        Ok(qnh)
+    }
+
+    /// Start "zero on pad": average the pressure over the next `seconds` and
+    /// use it as the baseline, so the pad reads 0 m.
+    pub fn zero_on_pad(&self, seconds: f64) -> Result<(), String> {
+        if !(seconds > 0.0 && seconds <= 600.0) {
+            return Err("averaging time must be within (0, 600] s".into());
+        }
+        let alt = self.enabled_altimeter()?;
+        if let Some(fl) = &self.flight
+            && let Ok(fl) = fl.read()
+            && !matches!(fl.phase, FlightPhase::Pad | FlightPhase::Landed)
+        {
+            return Err(format!("cannot zero during {}", fl.phase.as_str()));
+        }
+        alt.write().unwrap().start_zeroing(seconds);
+        Ok(())
+    }
+
+    fn enabled_altimeter(&self) -> Result<&SharedAltimeter, String> {
+        self.altimeter
+            .as_ref()
+            .filter(|a| a.read().unwrap().enabled())
+            .ok_or_else(|| "no channels with role = \"pressure\" / \"altitude\"".to_string())
+    }
+
+    /// Rewrite the stored altitude history from the stored pressure history
+    /// with the current baseline, and shift the flight state to match.
+    fn recalibrate(&self) {
+        let Some(alt) = &self.altimeter else {
+            return;
+        };
+        let (channels, baseline) = {
+            let a = alt.read().unwrap();
+            (a.channels(), a.baseline)
+        };
+        let (Some((p_idx, a_idx)), Some(qnh)) = (channels, baseline.qnh()) else {
+            return;
+        };
+        let pressure: Vec<(f64, f64)> = match self.graphs[p_idx].read() {
+            Ok(g) => g.data.history.iter().copied().collect(),
+            Err(_) => return,
+        };
+        let mut offset = None;
+        if let Ok(mut g) = self.graphs[a_idx].write() {
+            let before = g.data.history.back().map(|p| p.1);
+            g.data.recompute(|t, _| {
+                let i = pressure.partition_point(|p| p.0 < t);
+                let &(pt, p) = pressure.get(i)?;
+                if pt == t { isa_altitude(p, qnh) } else { None }
+            });
+            offset = before.zip(g.data.history.back()).map(|(b, a)| a.1 - b);
+        }
+        if let Some(offset) = offset
+            && let Some(fl) = &self.flight
+            && let Ok(mut fl) = fl.write()
+        {
+            fl.rebase(offset);
+        }
+        // derivatives must not see the step
+        if let Ok(mut d) = self.deriver.write() {
+            d.reset();
+        }
+    }
+
+    /// Clear graphs and derived statistics (a replay rewinding).
+    pub fn reset(&self) {
+        for g in &self.graphs {
+            if let Ok(mut g) = g.write() {
+                g.reset();
+            }
+        }
+        if let Ok(mut d) = self.deriver.write() {
+            d.reset();
+        }
+        if let Some(a) = &self.altimeter
+            && let Ok(mut a) = a.write()
+        {
+            a.reset();
+        }
+        if let Some(d) = &self.diagnostics
+            && let Ok(mut d) = d.write()
+        {
+            d.reset();
+        }
+        if let Some(al) = &self.alarms
+            && let Ok(mut al) = al.write()
+        {
+            al.reset();
+        }
+        if let Some(q) = &self.quality
+            && let Ok(mut q) = q.write()
+        {
+            q.reset();
+        }
+        if let Some(tr) = &self.track
+            && let Ok(mut tr) = tr.write()
+        {
+            tr.reset();
+        }
+        if let Some(fl) = &self.flight
+            && let Ok(mut fl) = fl.write()
+        {
+            fl.reset();
+        }
+    }
+
+    /// Seconds since the mission time origin.
+    pub fn now(&self) -> f64 {
+        self.epoch.elapsed().as_secs_f64()
+    }
+
+    /// Decode one line received just now, record it, and push its samples.
+    pub fn ingest(&self, line: &str) {
+        self.ingest_at(line, self.now());
+    }
+
+    /// Like `ingest`, with a source-provided timestamp `t` (mission seconds).
+    pub fn ingest_at(&self, line: &str, t: f64) {
+        let mut samples = Vec::new();
+        for l in line.lines() {
+            let decoded = self.decoder.decode_line(l);
+            if let Some(raw) = &self.rawlog
+                && let Ok(mut raw) = raw.write()
+            {
+                raw.push(l, decoded.class);
+            }
+            let rejected = match &self.diagnostics {
+                Some(d) => d.write().is_ok_and(|mut d| d.observe(t, l, &decoded)),
+                None => false,
+            };
+            if !rejected {
+                samples.extend(decoded.samples.iter().filter_map(|&(name, y)| {
+                    let idx = *self.index.get(name)?;
+                    self.only
+                        .as_ref()
+                        .is_none_or(|only| only[idx])
+                        .then_some((idx, y))
+                }));
+            }
+        }
+        let zeroed = match &self.altimeter {
+            Some(a) => a.write().ok().and_then(|mut a| a.apply(t, &mut samples)),
+            None => None,
+        };
+        if zeroed.is_some() {
+            self.recalibrate();
+        }
+        if let Ok(mut d) = self.deriver.write() {
+            d.apply(t, &mut samples);
+        }
+        if let Some(rec) = &self.recorder
+            && let Ok(mut rec) = rec.write()
+        {
+            rec.record(line, &samples);
+        }
+        if let Some(al) = &self.alarms
+            && let Ok(mut al) = al.write()
+        {
+            al.observe(t, &samples);
+        }
+        if let Some(q) = &self.quality
+            && let Ok(mut q) = q.write()
+        {
+            q.observe(t, &samples);
+        }
+        if let Some(tr) = &self.track
+            && let Ok(mut tr) = tr.write()
+        {
+            tr.observe(t, &samples);
+        }
+        if let Some(fl) = &self.flight
+            && let Ok(mut fl) = fl.write()
+        {
+            fl.observe(t, &samples);
+            // a launch ends any zeroing: the pad pressure is gone
+            if !matches!(fl.phase, FlightPhase::Pad | FlightPhase::Landed)
+                && let Some(a) = &self.altimeter
+                && let Ok(mut a) = a.write()
+            {
+                a.zeroing = None;
+            }
+        }
+        if let Some(up) = &self.uplink
+            && let Ok(mut up) = up.write()
+        {
+            up.observe(line, &samples);
+        }
+        for &(idx, y) in &samples {
+            if let Some(graph) = self.graphs.get(idx)
+                && let Ok(mut g) = graph.write()
+            {
+                g.push(t, y);
+            }
+        }
+        if let Some(feed) = &self.feed
+            && let Ok(mut feed) = feed.write()
+        {
+            feed.publish(t, &samples);
+        }
+    }
+}
@@ -0,0 +1,433 @@
+//! src/telemetry/quality.rs
+//!
+//! Link-quality accounting derived from the CanSat message counter.
+//!
+//! The channel with `role = "counter"` drives the statistics:
+//! - a jump forward by more than one counts the skipped numbers as missing,
+//! - the same number twice in a row is a duplicate,
+//! - a number below the last one is a late (out-of-order) packet if it was
+//!   counted missing within the last `RECENT` numbers, a duplicate if it was
+//!   already seen, and otherwise a counter reset (the CanSat rebooted).
+//!
+//! Rolling loss and packet rate are computed over the last `WINDOW` of the
+//! pipeline's time (the `t` given to `observe`), not the wall clock, so they
+//! follow the timestamps of the data. Channels with `role = "rssi"` are
+//! correlated with loss by attributing every gap to the signal strength of the
+//! first packet heard after it, in 10 dB buckets.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::schema::{ChannelRole, Schema};
+
+/// Span of the rolling statistics.
+pub const WINDOW: Duration = Duration::from_secs(30);
+
+/// How many counter values back received and missing numbers are remembered
+/// for duplicate/late detection.
+const RECENT: usize = 256;
+
+/// Width of an RSSI bucket, dB.
+const BUCKET_DB: f64 = 10.0;
+
+/// One received packet in the rolling window.
+#[derive(Clone, Copy, Debug)]
+struct Arrival {
+    /// Mission seconds.
+    at: f64,
+    /// Packets counted missing right before this one.
+    gap: u64,
+}
+
+/// Received/missing counts for one RSSI bucket.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BucketStats {
+    pub received: u64,
+    pub missing: u64,
+}
+
+impl BucketStats {
+    /// Loss percentage within this bucket.
+    pub fn loss_pct(&self) -> f64 {
+        let total = self.received + self.missing;
+        if total == 0 {
+            0.0
+        } else {
+            100.0 * self.missing as f64 / total as f64
+        }
+    }
+}
+
+/// Per-RSSI-channel correlation data.
+#[derive(Clone, Debug)]
+pub struct RssiStats {
+    pub channel: usize,
+    pub name: String,
+    /// `(mission seconds, dBm)` readings inside the rolling window.
+    window: VecDeque<(f64, f64)>,
+    /// Keyed by bucket lower bound in dBm (e.g. -100 for [-100, -90)).
+    pub buckets: BTreeMap<i64, BucketStats>,
+}
+
+impl RssiStats {
+    /// Mean dBm over the rolling window.
+    pub fn mean(&self) -> Option<f64> {
+        if self.window.is_empty() {
+            None
+        } else {
+            Some(self.window.iter().map(|(_, v)| v).sum::<f64>() / self.window.len() as f64)
+        }
+    }
+}
+
+/// Link-quality state, fed by the pipeline.
+#[derive(Debug)]
+pub struct LinkQuality {
+    counter_channel: Option<usize>,
+    pub received: u64,
+    pub missing: u64,
+    pub duplicates: u64,
+    pub out_of_order: u64,
+    pub resets: u64,
+    pub last_counter: Option<u64>,
+    recent: VecDeque<u64>,
+    /// Numbers counted missing within the last `RECENT` below `last_counter`.
+    gaps: BTreeSet<u64>,
+    arrivals: VecDeque<Arrival>,
+    pub rssi: Vec<RssiStats>,
+    /// Latest reading per RSSI channel for the packet being assembled.
+    pending_rssi: Vec<Option<f64>>,
+    /// Gap preceding the packet being assembled.
+    pending_gap: Option<u64>,
+}
+
+impl LinkQuality {
+    /// Pick the counter and RSSI channels from the schema roles.
+    pub fn new(schema: &Schema) -> Self {
+        let rssi = schema
+            .with_role(ChannelRole::Rssi)
+            .map(|i| (i, schema.channels[i].name.clone()))
+            .collect();
+        Self::with_channels(schema.with_role(ChannelRole::Counter).next(), rssi)
+    }
+
+    fn with_channels(counter_channel: Option<usize>, rssi: Vec<(usize, String)>) -> Self {
+        Self {
+            counter_channel,
+            received: 0,
+            missing: 0,
+            duplicates: 0,
+            out_of_order: 0,
+            resets: 0,
+            last_counter: None,
+            recent: VecDeque::with_capacity(RECENT),
+            gaps: BTreeSet::new(),
+            arrivals: VecDeque::new(),
+            pending_rssi: vec![None; rssi.len()],
+            pending_gap: None,
+            rssi: rssi
+                .into_iter()
+                .map(|(channel, name)| RssiStats {
+                    channel,
+                    name,
+                    window: VecDeque::new(),
+                    buckets: BTreeMap::new(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Whether the schema declares a counter channel at all.
+    pub fn enabled(&self) -> bool {
+        self.counter_channel.is_some()
+    }
+
+    /// Packets the counter says were sent (received + missing).
+    pub fn expected(&self) -> u64 {
+        self.received + self.missing
+    }
+
+    /// Drop all statistics (used when a replay rewinds).
+    pub fn reset(&mut self) {
+        let rssi = self
+            .rssi
+            .iter()
+            .map(|r| (r.channel, r.name.clone()))
+            .collect();
+        *self = Self::with_channels(self.counter_channel, rssi);
+    }
+
+    /// Feed the samples decoded from one line.
+    pub fn observe(&mut self, t: f64, samples: &[(usize, f64)]) {
+        for &(idx, y) in samples {
+            if Some(idx) == self.counter_channel {
+                if y >= 0.0 && y.fract() == 0.0 {
+                    self.on_counter(y as u64, t);
+                }
+            } else if let Some(k) = self.rssi.iter().position(|r| r.channel == idx) {
+                // 0 dBm is what the CanSat sends before its first ACK
+                if y < 0.0 {
+                    self.rssi[k].window.push_back((t, y));
+                    self.pending_rssi[k] = Some(y);
+                }
+            }
+        }
+        self.expire(t);
+    }
+
+    fn on_counter(&mut self, n: u64, t: f64) {
+        self.finish_packet();
+
+        let gap = match self.last_counter {
+            None => Some(0),
+            Some(last) if n == last => {
+                self.duplicates += 1;
+                None
+            }
+            Some(last) if n > last => {
+                let gap = n - last - 1;
+                self.missing += gap;
+                self.last_counter = Some(n);
+                let floor = n.saturating_sub(RECENT as u64);
+                self.gaps.extend((last + 1).max(floor)..n);
+                self.gaps = self.gaps.split_off(&floor);
+                Some(gap)
+            }
+            Some(_) => {
+                if self.gaps.remove(&n) {
+                    // a packet we had given up on arrived late
+                    self.missing -= 1;
+                    self.out_of_order += 1;
+                    Some(0)
+                } else if self.recent.contains(&n) {
+                    self.duplicates += 1;
+                    None
+                } else {
+                    self.resets += 1;
+                    self.recent.clear();
+                    self.gaps.clear();
+                    self.last_counter = Some(n);
+                    Some(0)
+                }
+            }
+        };
+        if self.last_counter.is_none() {
+            self.last_counter = Some(n);
+        }
+
+        if let Some(gap) = gap {
+            self.received += 1;
+            if self.recent.len() == RECENT {
+                self.recent.pop_front();
+            }
+            self.recent.push_back(n);
+            self.arrivals.push_back(Arrival { at: t, gap });
+            self.pending_gap = Some(gap);
+        }
+    }
+
+    /// Attribute the previous packet (and the gap before it) to RSSI buckets.
+    fn finish_packet(&mut self) {
+        let Some(gap) = self.pending_gap.take() else {
+            return;
+        };
+        for (k, r) in self.rssi.iter_mut().enumerate() {
+            if let Some(v) = self.pending_rssi[k].take() {
+                let key = ((v / BUCKET_DB).floor() * BUCKET_DB) as i64;
+                let b = r.buckets.entry(key).or_default();
+                b.received += 1;
+                b.missing += gap;
+            }
+        }
+    }
+
+    fn expire(&mut self, now: f64) {
+        let window = WINDOW.as_secs_f64();
+        while self.arrivals.front().is_some_and(|a| now - a.at > window) {
+            self.arrivals.pop_front();
+        }
+        for r in &mut self.rssi {
+            while r.window.front().is_some_and(|(t, _)| now - *t > window) {
+                r.window.pop_front();
+            }
+        }
+    }
+
+    /// Loss percentage over the rolling window.
+    pub fn rolling_loss_pct(&self) -> f64 {
+        let received = self.arrivals.len() as u64;
+        let missing: u64 = self.arrivals.iter().map(|a| a.gap).sum();
+        if received + missing == 0 {
+            0.0
+        } else {
+            100.0 * missing as f64 / (received + missing) as f64
+        }
+    }
+
+    /// Received packets per second over the rolling window.
+    pub fn rate_hz(&self) -> f64 {
+        let (Some(first), Some(last)) = (self.arrivals.front(), self.arrivals.back()) else {
+            return 0.0;
+        };
+        let span = last.at - first.at;
+        if span <= 0.0 {
+            0.0
+        } else {
+            (self.arrivals.len() - 1) as f64 / span
+        }
+    }
+
+    /// Overall loss percentage since start.
+    pub fn total_loss_pct(&self) -> f64 {
+        let expected = self.expected();
+        if expected == 0 {
+            0.0
+        } else {
+            100.0 * self.missing as f64 / expected as f64
+        }
+    }
+
+    /// One-line `key=value` summary (used by the TCP `stats` command).
+    pub fn summary(&self) -> String {
+        let mut s = format!(
+            "received={} expected={} missing={} duplicates={} out_of_order={} resets={} loss_pct={:.2} rolling_loss_pct={:.2} rate_hz={:.2}",
+            self.received,
+            self.expected(),
+            self.missing,
+            self.duplicates,
+            self.out_of_order,
+            self.resets,
+            self.total_loss_pct(),
+            self.rolling_loss_pct(),
+            self.rate_hz()
+        );
+        for r in &self.rssi {
+            if let Some(m) = r.mean() {
+                s.push_str(&format!(" rssi_mean[{}]={:.1}", r.channel, m));
+            }
+        }
+        s
+    }
+}
+
+/// Alias: Arc<RwLock<LinkQuality>>
+pub type SharedQuality = Arc<RwLock<LinkQuality>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Counter on channel 0, RSSI on channel 1.
+    fn quality() -> LinkQuality {
+        LinkQuality::with_channels(Some(0), vec![(1, "RSSI".to_string())])
+    }
+
+    /// Feed one counter value per second.
+    fn feed(q: &mut LinkQuality, counters: &[u64]) {
+        for &n in counters {
+            let t = q.arrivals.back().map_or(0.0, |a| a.at + 1.0);
+            q.observe(t, &[(0, n as f64)]);
+        }
+    }
+
+    #[test]
+    fn consecutive_numbers_lose_nothing() {
+        let mut q = quality();
+        feed(&mut q, &[7, 8, 9, 10]);
+        assert_eq!((q.received, q.missing, q.expected()), (4, 0, 4));
+        assert_eq!(q.total_loss_pct(), 0.0);
+    }
+
+    #[test]
+    fn skipped_numbers_are_missing() {
+        let mut q = quality();
+        feed(&mut q, &[1, 2, 5, 6]);
+        assert_eq!((q.received, q.missing), (4, 2));
+        assert_eq!(q.total_loss_pct(), 100.0 * 2.0 / 6.0);
+        assert_eq!(q.last_counter, Some(6));
+    }
+
+    #[test]
+    fn repeated_numbers_are_duplicates() {
+        let mut q = quality();
+        feed(&mut q, &[1, 2, 2, 3, 1]);
+        assert_eq!(
+            (q.received, q.duplicates, q.out_of_order, q.resets),
+            (3, 2, 0, 0)
+        );
+    }
+
+    #[test]
+    fn missing_number_arriving_late_is_out_of_order() {
+        let mut q = quality();
+        feed(&mut q, &[1, 2, 5, 3]);
+        assert_eq!((q.received, q.missing, q.out_of_order), (4, 1, 1));
+        // the same late packet again is a duplicate, not a second recovery
+        feed(&mut q, &[3, 4]);
+        assert_eq!((q.missing, q.out_of_order, q.duplicates), (0, 2, 1));
+    }
+
+    #[test]
+    fn lower_number_outside_a_gap_is_a_reset() {
+        let mut q = quality();
+        feed(&mut q, &[10, 11, 14]);
+        assert_eq!(q.missing, 2);
+        // 0 was never missing, even though 12 and 13 are
+        feed(&mut q, &[0, 1]);
+        assert_eq!((q.resets, q.out_of_order, q.missing), (1, 0, 2));
+        assert_eq!(q.last_counter, Some(1));
+        // the gap before the reset is forgotten
+        feed(&mut q, &[12]);
+        assert_eq!((q.out_of_order, q.missing), (0, 12));
+    }
+
+    #[test]
+    fn only_recent_gaps_can_be_recovered() {
+        let mut q = quality();
+        feed(&mut q, &[1, 3, 3 + RECENT as u64 + 1]);
+        assert_eq!(q.missing, 1 + RECENT as u64);
+        feed(&mut q, &[2]);
+        assert_eq!((q.out_of_order, q.resets), (0, 1));
+    }
+
+    #[test]
+    fn rolling_window_uses_mission_time() {
+        let mut q = quality();
+        // 4 Hz with every other packet lost, whatever the replay speed
+        for (i, n) in [1u64, 3, 5, 7, 9].into_iter().enumerate() {
+            q.observe(i as f64 * 0.5, &[(0, n as f64)]);
+        }
+        assert_eq!(q.rate_hz(), 2.0);
+        assert_eq!(q.rolling_loss_pct(), 100.0 * 4.0 / 9.0);
+        // everything before t = 100 - WINDOW has expired
+        q.observe(100.0, &[(0, 10.0)]);
+        assert_eq!(q.arrivals.len(), 1);
+        assert_eq!(q.rate_hz(), 0.0);
+        assert_eq!(q.rolling_loss_pct(), 0.0);
+    }
+
+    #[test]
+    fn gaps_are_attributed_to_the_rssi_after_them() {
+        let mut q = quality();
+        q.observe(0.0, &[(0, 1.0), (1, -85.0)]);
+        q.observe(1.0, &[(0, 4.0), (1, -101.0)]);
+        // buckets are settled when the next packet starts
+        q.observe(2.0, &[(0, 5.0)]);
+        let r = &q.rssi[0];
+        assert_eq!(r.buckets[&-90].received, 1);
+        assert_eq!(r.buckets[&-90].missing, 0);
+        assert_eq!(r.buckets[&-110].received, 1);
+        assert_eq!(r.buckets[&-110].missing, 2);
+        assert_eq!(r.mean(), Some(-93.0));
+    }
+
+    #[test]
+    fn non_integer_counters_and_zero_rssi_are_ignored() {
+        let mut q = quality();
+        q.observe(0.0, &[(0, 1.5), (1, 0.0)]);
+        q.observe(0.0, &[(0, -1.0)]);
+        assert_eq!(q.received, 0);
+        assert_eq!(q.rssi[0].mean(), None);
+    }
+}
@@ -0,0 +1,79 @@
+//! src/telemetry/rawlog.rs
+//!
+//! Every line received from the source, as received, with its receive time
+//! and how the decoder classified it (shown by the raw terminal panel).
+
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Local};
+
+use super::parse::LineClass;
+
+/// Lines kept for scrolling back.
+const CAPACITY: usize = 5_000;
+
+/// One received line.
+#[derive(Clone, Debug)]
+pub struct RawLine {
+    /// Position in the stream (0 = first line received).
+    pub seq: u64,
+    pub time: DateTime<Local>,
+    pub text: String,
+    pub class: LineClass,
+}
+
+/// Bounded buffer of received lines, fed by the pipeline.
+#[derive(Debug, Default)]
+pub struct RawLog {
+    /// Oldest first.
+    pub lines: VecDeque<RawLine>,
+    /// Lines received per class, since the start: parsed, partial, ignored.
+    pub counts: [u64; 3],
+}
+
+impl RawLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, text: &str, class: LineClass) {
+        if self.lines.len() == CAPACITY {
+            self.lines.pop_front();
+        }
+        let seq = self.counts.iter().sum();
+        self.counts[class as usize] += 1;
+        self.lines.push_back(RawLine {
+            seq,
+            time: Local::now(),
+            text: text.to_string(),
+            class,
+        });
+    }
+
+    /// Lines containing `filter` (case-insensitive; all lines when empty),
+    /// oldest first.
+    pub fn matching<'a>(&'a self, filter: &str) -> impl Iterator<Item = &'a RawLine> {
+        let needle = filter.to_lowercase();
+        self.lines
+            .iter()
+            .filter(move |l| needle.is_empty() || l.text.to_lowercase().contains(&needle))
+    }
+
+    /// Move a scrollback anchor (the `seq` of the bottom line shown; `None`
+    /// follows the newest line) by `by` matching lines, negative = older.
+    /// Returns `None` once the newest line is reached again.
+    pub fn scroll(&self, filter: &str, anchor: Option<u64>, by: isize) -> Option<u64> {
+        let seqs: Vec<u64> = self.matching(filter).map(|l| l.seq).collect();
+        let last = seqs.len().checked_sub(1)?;
+        let pos = match anchor {
+            Some(a) => seqs.partition_point(|&s| s <= a).saturating_sub(1),
+            None => last,
+        };
+        let pos = pos.saturating_add_signed(by).min(last);
+        (pos < last).then(|| seqs[pos])
+    }
+}
+
+/// Alias: Arc<RwLock<RawLog>>
+pub type SharedRawLog = Arc<RwLock<RawLog>>;
@@ -0,0 +1,134 @@
+//! src/telemetry/record.rs
+//!
+//! Session recorder: appends every received line to a per-session CSV file.
+//!
+//! One row per raw line:
+//!
+//! ```text
+//! received_at,raw,Msg #,RSSI ACK (dBm),...
+//! 2026-05-02T14:03:11.482+02:00,"Received:  136  -91.0  18.45  995.85  58.93  300.045200",136,-91,...
+//! 2026-05-02T14:03:11.483+02:00,"ACK sent back automatically.",,,,,,,
+//! ```
+//!
+//! `received_at` is the wall-clock receive time; channel columns are empty when
+//! the line did not carry that channel. Every row is flushed and `fsync`ed at
+//! once (a few rows per second at most), so a crash or power loss, even right
+//! before the link goes quiet, costs at most the row being written.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use chrono::{Local, SecondsFormat};
+
+use crate::schema::Schema;
+
+/// Open session file plus counters shown in the UI.
+pub struct Recorder {
+    pub path: PathBuf,
+    /// Bytes written so far (header included).
+    pub bytes: u64,
+    /// Rows written so far.
+    pub rows: u64,
+    /// Set when a write failed; recording stops after that.
+    pub error: Option<String>,
+    channels: usize,
+    out: Option<BufWriter<File>>,
+}
+
+impl Recorder {
+    /// Create `dir` if needed and start a new session file in it.
+    pub fn create(dir: &Path, schema: &Schema) -> Result<Self, String> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("cannot create record dir {}: {}", dir.display(), e))?;
+        let name = format!("session-{}.csv", Local::now().format("%Y%m%d-%H%M%S"));
+        let path = dir.join(name);
+        let file = OpenOptions::new()
+            .create_new(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("cannot create {}: {}", path.display(), e))?;
+
+        let mut rec = Self {
+            path,
+            bytes: 0,
+            rows: 0,
+            error: None,
+            channels: schema.channels.len(),
+            out: Some(BufWriter::new(file)),
+        };
+        let mut header = String::from("received_at,raw");
+        for c in &schema.channels {
+            header.push(',');
+            let label = if c.unit.is_empty() {
+                c.name.clone()
+            } else {
+                format!("{} ({})", c.name, c.unit)
+            };
+            header.push_str(&csv_field(&label));
+        }
+        header.push('\n');
+        rec.write(&header)?;
+        Ok(rec)
+    }
+
+    /// Append one row for `raw` with the decoded `samples`.
+    pub fn record(&mut self, raw: &str, samples: &[(usize, f64)]) {
+        if self.out.is_none() {
+            return;
+        }
+        let mut cells = vec![String::new(); self.channels];
+        for &(idx, y) in samples {
+            if let Some(c) = cells.get_mut(idx) {
+                *c = y.to_string();
+            }
+        }
+        let row = format!(
+            "{},{},{}\n",
+            Local::now().to_rfc3339_opts(SecondsFormat::Millis, false),
+            csv_field(raw),
+            cells.join(",")
+        );
+        if let Err(e) = self.write(&row) {
+            self.error = Some(e);
+            self.out = None;
+        } else {
+            self.rows += 1;
+        }
+    }
+
+    /// Write, flush and fsync.
+    fn write(&mut self, text: &str) -> Result<(), String> {
+        let Some(out) = self.out.as_mut() else {
+            return Ok(());
+        };
+        let err = |e: std::io::Error| format!("write to {} failed: {}", self.path.display(), e);
+        out.write_all(text.as_bytes()).map_err(err)?;
+        out.flush().map_err(err)?;
+        out.get_ref().sync_data().map_err(err)?;
+        self.bytes += text.len() as u64;
+        Ok(())
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        if let Some(out) = self.out.as_mut() {
+            let _ = out.flush();
+            let _ = out.get_ref().sync_data();
+        }
+    }
+}
+
+/// Quote a CSV field when it contains a separator, quote or newline.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) || s.starts_with(' ') || s.ends_with(' ') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Alias: Arc<RwLock<Recorder>>
+pub type SharedRecorder = Arc<RwLock<Recorder>>;
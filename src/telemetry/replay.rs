@@ -0,0 +1,451 @@
+//! src/telemetry/replay.rs
+//!
+//! Replay source for files written by the onboard `Logger` (`test_tobias.py`):
+//!
+//! ```text
+//! === LOGS STARTED (v0.1) ===
+//! [00:01:52.932]  [INFO]     1  0.0  19.60  992.01  61.62  99.517
+//! [00:00:17.750] [ERROR]    System Crashed
+//! ```
+//!
+//! `[INFO]` lines carrying the six numeric telemetry fields are rewritten as a
+//! receiver `Received:` line and fed through the normal pipeline (the replay
+//! is a `TelemetrySource`), so replayed data is decoded by the same schema as
+//! live data. Any other entry goes to the event log. Records are paced by
+//! their original timestamps, scaled by the replay speed; the UI can pause,
+//! change speed and seek. Graph samples carry the original timestamp
+//! (relative to the first record), not the replay time.
+
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::source::{Frame, TelemetrySource};
+use crate::events::Level;
+
+/// Replay tick; bounds how late pause/seek/speed changes take effect.
+const TICK: Duration = Duration::from_millis(10);
+
+/// Records ingested per tick at `Max` speed, so the UI keeps up.
+const MAX_SPEED_BATCH: usize = 200;
+
+/// Available replay speeds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplaySpeed {
+    Half,
+    Normal,
+    Fast,
+    Max,
+}
+
+impl ReplaySpeed {
+    const ALL: [ReplaySpeed; 4] = [
+        ReplaySpeed::Half,
+        ReplaySpeed::Normal,
+        ReplaySpeed::Fast,
+        ReplaySpeed::Max,
+    ];
+
+    /// Time multiplier; `None` means "as fast as possible".
+    pub fn factor(&self) -> Option<f64> {
+        match self {
+            ReplaySpeed::Half => Some(0.5),
+            ReplaySpeed::Normal => Some(1.0),
+            ReplaySpeed::Fast => Some(4.0),
+            ReplaySpeed::Max => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReplaySpeed::Half => "0.5x",
+            ReplaySpeed::Normal => "1x",
+            ReplaySpeed::Fast => "4x",
+            ReplaySpeed::Max => "max",
+        }
+    }
+
+    /// Next faster speed (saturating).
+    pub fn faster(&self) -> Self {
+        let i = Self::ALL.iter().position(|s| s == self).unwrap_or(0);
+        Self::ALL[(i + 1).min(Self::ALL.len() - 1)]
+    }
+
+    /// Next slower speed (saturating).
+    pub fn slower(&self) -> Self {
+        let i = Self::ALL.iter().position(|s| s == self).unwrap_or(0);
+        Self::ALL[i.saturating_sub(1)]
+    }
+}
+
+impl FromStr for ReplaySpeed {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.strip_suffix('x').filter(|_| s != "max").unwrap_or(s) {
+            "0.5" => Ok(ReplaySpeed::Half),
+            "1" => Ok(ReplaySpeed::Normal),
+            "4" => Ok(ReplaySpeed::Fast),
+            "max" => Ok(ReplaySpeed::Max),
+            _ => Err(format!("unknown speed {:?} (expected 0.5, 1, 4 or max)", s)),
+        }
+    }
+}
+
+/// One entry of the log file.
+#[derive(Clone, Debug)]
+enum Payload {
+    /// Telemetry rewritten as a receiver line.
+    Telemetry(String),
+    /// Any other logger entry.
+    Message(Level, String),
+}
+
+#[derive(Clone, Debug)]
+struct Record {
+    /// Seconds since the logger's time origin (made monotonic).
+    t: f64,
+    payload: Payload,
+}
+
+/// Parse `[HH:MM:SS.mmm]` into seconds.
+fn parse_timestamp(s: &str) -> Option<f64> {
+    let inner = s.strip_prefix('[')?.strip_suffix(']')?;
+    let mut it = inner.split(':');
+    let h: f64 = it.next()?.parse().ok()?;
+    let m: f64 = it.next()?.parse().ok()?;
+    let sec: f64 = it.next()?.parse().ok()?;
+    if it.next().is_some() {
+        return None;
+    }
+    Some(h * 3600.0 + m * 60.0 + sec)
+}
+
+/// Parse one log line into a record; headers and garbage return `None`.
+fn parse_record(line: &str) -> Option<Record> {
+    let mut parts = line.split_whitespace();
+    let t = parse_timestamp(parts.next()?)?;
+    let level_tag = parts.next()?;
+    let rest: Vec<&str> = parts.collect();
+
+    // unknown tags (`[DEBUG]`, `[TRACE]`, ...) are informational
+    let level = match level_tag {
+        "[ERROR]" | "[CRITICAL]" => Level::Error,
+        "[WARNING]" | "[WARN]" => Level::Warn,
+        _ => Level::Info,
+    };
+    let is_telemetry =
+        level_tag == "[INFO]" && rest.len() == 6 && rest.iter().all(|v| v.parse::<f64>().is_ok());
+    let payload = if is_telemetry {
+        Payload::Telemetry(format!("Received: {}", rest.join(" ")))
+    } else {
+        Payload::Message(level, format!("replay: {}", rest.join(" ")))
+    };
+    Some(Record { t, payload })
+}
+
+/// Parse a whole log file. Timestamps going backwards are clamped so the
+/// replay clock only ever moves forward.
+fn parse_log(text: &str) -> Vec<Record> {
+    let mut out: Vec<Record> = Vec::new();
+    for line in text.lines() {
+        if let Some(mut r) = parse_record(line) {
+            if let Some(prev) = out.last() {
+                r.t = r.t.max(prev.t);
+            }
+            out.push(r);
+        }
+    }
+    out
+}
+
+/// Replay transport state, shared between the UI (controls) and the replay
+/// thread (position).
+#[derive(Debug)]
+pub struct ReplayControl {
+    pub file: String,
+    pub paused: bool,
+    pub speed: ReplaySpeed,
+    /// Current position, seconds from the first record.
+    pub position: f64,
+    /// Time span of the file, seconds.
+    pub duration: f64,
+    /// All records have been played.
+    pub finished: bool,
+    /// Pending absolute seek target (seconds from the first record).
+    pub seek_to: Option<f64>,
+}
+
+impl ReplayControl {
+    /// Request a relative seek from the current position.
+    pub fn seek_by(&mut self, delta: f64) {
+        let base = self.seek_to.unwrap_or(self.position);
+        self.seek_to = Some((base + delta).clamp(0.0, self.duration));
+    }
+}
+
+/// Alias: Arc<RwLock<ReplayControl>>
+pub type SharedReplay = Arc<RwLock<ReplayControl>>;
+
+/// A log file replayed as a telemetry source, paced by its timestamps and
+/// driven by a `ReplayControl`.
+pub struct ReplaySource {
+    records: Vec<Record>,
+    /// Timestamp of the first record.
+    origin: f64,
+    duration: f64,
+    control: SharedReplay,
+    /// Frames of the current tick not handed out yet.
+    pending: VecDeque<Frame>,
+    next: usize,
+    position: f64,
+    last_tick: Instant,
+    announced_end: bool,
+}
+
+impl ReplaySource {
+    /// Load `path`. Fails if the file cannot be read or contains no
+    /// timestamped records.
+    pub fn load(path: &str, speed: ReplaySpeed) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read replay file {}: {}", path, e))?;
+        let records = parse_log(&text);
+        let (Some(first), Some(last)) = (records.first(), records.last()) else {
+            return Err(format!("replay file {} has no timestamped entries", path));
+        };
+        let origin = first.t;
+        let duration = last.t - origin;
+        let control: SharedReplay = Arc::new(RwLock::new(ReplayControl {
+            file: path.to_string(),
+            paused: false,
+            speed,
+            position: 0.0,
+            duration,
+            finished: false,
+            seek_to: None,
+        }));
+        Ok(Self {
+            records,
+            origin,
+            duration,
+            control,
+            pending: VecDeque::new(),
+            next: 0,
+            position: 0.0,
+            last_tick: Instant::now(),
+            announced_end: false,
+        })
+    }
+
+    /// Transport state for the UI.
+    pub fn control(&self) -> SharedReplay {
+        self.control.clone()
+    }
+
+    /// Queue the frame of record `i`.
+    fn play(&mut self, i: usize) {
+        let r = &self.records[i];
+        self.pending.push_back(match &r.payload {
+            Payload::Telemetry(line) => Frame::Timed(r.t - self.origin, line.clone()),
+            Payload::Message(level, text) => Frame::Message(*level, text.clone()),
+        });
+    }
+
+    /// Advance the replay clock by one tick, queueing what became due.
+    fn tick(&mut self) {
+        thread::sleep(TICK);
+        let now = Instant::now();
+        let dt = now.duration_since(self.last_tick).as_secs_f64();
+        self.last_tick = now;
+
+        let (paused, speed, seek) = {
+            let mut c = self.control.write().unwrap();
+            (c.paused, c.speed, c.seek_to.take())
+        };
+
+        if let Some(target) = seek {
+            if target < self.position {
+                // rewind: start over from empty graphs
+                self.pending.push_back(Frame::Reset);
+                self.next = 0;
+            }
+            // fast-forward to the target without pacing
+            while self.next < self.records.len()
+                && self.records[self.next].t - self.origin <= target
+            {
+                self.play(self.next);
+                self.next += 1;
+            }
+            self.position = target;
+        } else if !paused {
+            match speed.factor() {
+                Some(f) => {
+                    self.position += dt * f;
+                    while self.next < self.records.len()
+                        && self.records[self.next].t - self.origin <= self.position
+                    {
+                        self.play(self.next);
+                        self.next += 1;
+                    }
+                }
+                None => {
+                    let end = (self.next + MAX_SPEED_BATCH).min(self.records.len());
+                    for i in self.next..end {
+                        self.play(i);
+                    }
+                    self.next = end;
+                    if let Some(r) = self.records.get(self.next.saturating_sub(1)) {
+                        self.position = self.position.max(r.t - self.origin);
+                    }
+                }
+            }
+        }
+
+        let finished = self.next >= self.records.len();
+        if finished && !self.announced_end {
+            self.pending
+                .push_back(Frame::Message(Level::Info, "replay finished".into()));
+        }
+        self.announced_end = finished;
+
+        let mut c = self.control.write().unwrap();
+        c.position = self.position.min(self.duration);
+        c.finished = finished;
+    }
+}
+
+impl TelemetrySource for ReplaySource {
+    fn open(&mut self) -> Result<(), String> {
+        self.last_tick = Instant::now();
+        self.pending.push_back(Frame::Message(
+            Level::Info,
+            format!(
+                "replaying {} ({} records, {:.1} s)",
+                self.control.read().unwrap().file,
+                self.records.len(),
+                self.duration
+            ),
+        ));
+        Ok(())
+    }
+
+    /// A finished replay stays open (idle) so it can still be rewound.
+    fn next_frame(&mut self) -> Result<Frame, String> {
+        if self.pending.is_empty() {
+            self.tick();
+        }
+        Ok(self.pending.pop_front().unwrap_or(Frame::Idle))
+    }
+
+    fn status(&self) -> String {
+        self.control.read().unwrap().file.clone()
+    }
+
+    fn close(&mut self) {
+        self.pending.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn telemetry(r: &Record) -> Option<&str> {
+        match &r.payload {
+            Payload::Telemetry(line) => Some(line),
+            Payload::Message(..) => None,
+        }
+    }
+
+    #[test]
+    fn speed_from_str() {
+        assert_eq!("0.5".parse(), Ok(ReplaySpeed::Half));
+        assert_eq!("1".parse(), Ok(ReplaySpeed::Normal));
+        assert_eq!("4x".parse(), Ok(ReplaySpeed::Fast));
+        assert_eq!(" 0.5x ".parse(), Ok(ReplaySpeed::Half));
+        assert_eq!("max".parse(), Ok(ReplaySpeed::Max));
+        assert!("2".parse::<ReplaySpeed>().is_err());
+        assert!("x".parse::<ReplaySpeed>().is_err());
+        assert!("".parse::<ReplaySpeed>().is_err());
+    }
+
+    #[test]
+    fn speed_steps_saturate() {
+        assert_eq!(ReplaySpeed::Half.slower(), ReplaySpeed::Half);
+        assert_eq!(ReplaySpeed::Half.faster(), ReplaySpeed::Normal);
+        assert_eq!(ReplaySpeed::Max.faster(), ReplaySpeed::Max);
+        assert_eq!(ReplaySpeed::Max.factor(), None);
+    }
+
+    #[test]
+    fn timestamp() {
+        assert_eq!(parse_timestamp("[00:01:52.932]"), Some(112.932));
+        assert_eq!(parse_timestamp("[01:00:00]"), Some(3600.0));
+        assert_eq!(parse_timestamp("00:01:52.932"), None);
+        assert_eq!(parse_timestamp("[01:52.932]"), None);
+        assert_eq!(parse_timestamp("[00:00:01:52]"), None);
+    }
+
+    #[test]
+    fn telemetry_record_becomes_a_receiver_line() {
+        let r = parse_record("[00:01:52.932]  [INFO]     1  0.0  19.60  992.01  61.62  99.517")
+            .unwrap();
+        assert_eq!(r.t, 112.932);
+        assert_eq!(
+            telemetry(&r),
+            Some("Received: 1 0.0 19.60 992.01 61.62 99.517")
+        );
+    }
+
+    #[test]
+    fn other_records_are_messages() {
+        let r = parse_record("[00:00:17.750] [ERROR]    System Crashed").unwrap();
+        assert!(matches!(
+            &r.payload,
+            Payload::Message(Level::Error, text) if text == "replay: System Crashed"
+        ));
+        // five fields, or one that is not a number, is not telemetry
+        let r = parse_record("[00:00:18.000] [INFO] 1 2 3 4 5").unwrap();
+        assert!(matches!(&r.payload, Payload::Message(Level::Info, _)));
+        let r = parse_record("[00:00:18.000] [WARNING] 1 2 3 4 5 x").unwrap();
+        assert!(matches!(&r.payload, Payload::Message(Level::Warn, _)));
+        let r = parse_record("[00:00:18.000] [CRITICAL] battery low").unwrap();
+        assert!(matches!(&r.payload, Payload::Message(Level::Error, _)));
+    }
+
+    #[test]
+    fn unknown_tags_are_informational() {
+        let r = parse_record("[00:00:18.000] [DEBUG] loop took 12 ms").unwrap();
+        assert!(matches!(&r.payload, Payload::Message(Level::Info, _)));
+        // only `[INFO]` entries carry telemetry
+        let r = parse_record("[00:00:18.000] [TRACE] 1 0.0 19.60 992.01 61.62 99.517").unwrap();
+        assert!(matches!(&r.payload, Payload::Message(Level::Info, _)));
+    }
+
+    #[test]
+    fn headers_and_garbage_are_skipped() {
+        assert!(parse_record("=== LOGS STARTED (v0.1) ===").is_none());
+        assert!(parse_record("").is_none());
+        assert!(parse_record("[00:00:18.000]").is_none());
+    }
+
+    #[test]
+    fn log_timestamps_never_go_backwards() {
+        let log = "=== LOGS STARTED (v0.1) ===\n\
+                   [00:01:52.932]  [INFO]     1  0.0  19.60  992.01  61.62  99.517\n\
+                   [00:00:17.750] [ERROR]    System Crashed\n\
+                   not a record\n\
+                   [00:01:53.384]  [INFO]     2  0.0  19.58  992.07  61.59  99.019\n";
+        let records = parse_log(log);
+        let t: Vec<f64> = records.iter().map(|r| r.t).collect();
+        assert_eq!(t, [112.932, 112.932, 113.384]);
+        assert_eq!(
+            records.iter().filter_map(telemetry).count(),
+            2,
+            "the crash message sits between two telemetry records"
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! src/telemetry/serial.rs
+//!
+//! Serial-port telemetry source: reads newline-terminated text from the LoRa
+//! receiver. While connected, the port also carries the uplink commands
+//! written back to the receiver.
+//!
+//! The reader thread (`source::start_source`) never gives up: when the device
+//! disappears or a read fails it marks the link `Lost`, waits with exponential
+//! backoff and reopens it. If the configured path is gone but a USB device
+//! with the same VID/PID/serial number shows up under another name
+//! (re-enumeration, `/dev/ttyACM0` → `/dev/ttyACM1`) that port is used
+//! instead.
+
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::time::Duration;
+
+use serialport::{SerialPort, SerialPortType, UsbPortInfo};
+
+use super::source::{Frame, TelemetrySource};
+
+/// Read timeout; quiet periods shorter than this are not errors.
+const READ_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Open the serial port, mapping failures to a message that names the device
+/// and baud rate.
+pub fn open_serial(port_name: &str, baud_rate: u32) -> Result<Box<dyn SerialPort>, String> {
+    serialport::new(port_name, baud_rate)
+        .timeout(READ_TIMEOUT)
+        .open()
+        .map_err(|e| {
+            format!(
+                "failed to open serial port {} @ {} baud: {}",
+                port_name, baud_rate, e
+            )
+        })
+}
+
+/// USB identity of `port_name`, if it is a USB port currently present.
+fn usb_identity(port_name: &str) -> Option<UsbPortInfo> {
+    serialport::available_ports()
+        .ok()?
+        .into_iter()
+        .find(|p| p.port_name == port_name)
+        .and_then(|p| match p.port_type {
+            SerialPortType::UsbPort(info) => Some(info),
+            _ => None,
+        })
+}
+
+/// Pick the device to open: the configured path when present, otherwise a
+/// port whose USB identity matches the one seen on the last connection.
+fn resolve_port(configured: &str, identity: Option<&UsbPortInfo>) -> String {
+    let Some(id) = identity else {
+        return configured.to_string();
+    };
+    let Ok(ports) = serialport::available_ports() else {
+        return configured.to_string();
+    };
+    if ports.iter().any(|p| p.port_name == configured) {
+        return configured.to_string();
+    }
+    ports
+        .into_iter()
+        .find(|p| match &p.port_type {
+            SerialPortType::UsbPort(info) => {
+                info.vid == id.vid && info.pid == id.pid && info.serial_number == id.serial_number
+            }
+            _ => false,
+        })
+        .map(|p| p.port_name)
+        .unwrap_or_else(|| configured.to_string())
+}
+
+/// The LoRa receiver on a serial port.
+pub struct SerialSource {
+    port_name: String,
+    baud_rate: u32,
+    /// Port opened by the startup check, used by the first `open`.
+    pending: Option<Box<dyn SerialPort>>,
+    /// USB identity seen on the last connection, to follow re-enumeration.
+    identity: Option<UsbPortInfo>,
+    /// Device actually opened (may differ from `port_name`).
+    target: String,
+    reader: Option<BufReader<Box<dyn SerialPort>>>,
+    /// Partial line carried over a read timeout.
+    line: String,
+}
+
+impl SerialSource {
+    pub fn new(port_name: &str, baud_rate: u32) -> Self {
+        Self {
+            port_name: port_name.to_string(),
+            baud_rate,
+            pending: None,
+            identity: usb_identity(port_name),
+            target: port_name.to_string(),
+            reader: None,
+            line: String::new(),
+        }
+    }
+
+    /// Start with a port that is already open (from the startup check).
+    pub fn with_port(mut self, port: Option<Box<dyn SerialPort>>) -> Self {
+        self.pending = port;
+        self
+    }
+}
+
+impl TelemetrySource for SerialSource {
+    fn open(&mut self) -> Result<(), String> {
+        self.target = resolve_port(&self.port_name, self.identity.as_ref());
+        let port = match self.pending.take() {
+            Some(p) => p,
+            None => open_serial(&self.target, self.baud_rate)?,
+        };
+        if let Some(id) = usb_identity(&self.target) {
+            self.identity = Some(id);
+        }
+        self.line.clear();
+        self.reader = Some(BufReader::new(port));
+        Ok(())
+    }
+
+    fn next_frame(&mut self) -> Result<Frame, String> {
+        let Some(reader) = &mut self.reader else {
+            return Err("not open".into());
+        };
+        match reader.read_line(&mut self.line) {
+            Ok(0) => Err("device closed".to_string()),
+            Ok(_) => {
+                let line = self.line.trim().to_string();
+                self.line.clear();
+                Ok(if line.is_empty() {
+                    Frame::Idle
+                } else {
+                    Frame::Line(line)
+                })
+            }
+            // partial data stays in `line` and is completed by the next read
+            Err(e) if e.kind() == ErrorKind::TimedOut || e.kind() == ErrorKind::Interrupted => {
+                Ok(Frame::Idle)
+            }
+            Err(e) => Err(format!("read error: {}", e)),
+        }
+    }
+
+    fn status(&self) -> String {
+        format!("{} @ {}", self.target, self.baud_rate)
+    }
+
+    fn close(&mut self) {
+        self.reader = None;
+    }
+
+    fn writer(&mut self) -> Result<Option<Box<dyn Write + Send>>, String> {
+        match &self.reader {
+            Some(r) => r
+                .get_ref()
+                .try_clone()
+                .map(|w| Some(Box::new(w) as Box<dyn Write + Send>))
+                .map_err(|e| e.to_string()),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,307 @@
+//! src/telemetry/sim.rs
+//!
+//! Simulated CanSat flight for demos and development (`--simulate`).
+//!
+//! The simulator produces the exact text the ground receiver prints
+//! (`Received: ...`, `RSSI_PACKET: ... dBm`, `ACK sent back automatically.`)
+//! as a `TelemetrySource`, so decoding and the UI are exercised exactly as
+//! with real hardware.
+//!
+//! One flight cycle: pad wait → boosted ascent → apogee → parachute descent with
+//! wind drift → landed, then the cycle starts over. Sensor values follow the
+//! international standard atmosphere; the transmitted altitude uses the same
+//! `(baseline - pressure) * 8.3` approximation as `main.py`. RSSI follows a
+//! free-space path-loss model with noise, and packets are dropped with the
+//! configured base probability plus extra loss near receiver sensitivity.
+//! Once per second a forwarded GPS fix (`$GPGGA` + `$GPRMC`) is emitted for the
+//! drifting position.
+
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+use std::io::Write;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{Timelike, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::nmea::checksum;
+use super::source::{Frame, TelemetrySource};
+
+/// Ground elevation of the launch site, metres.
+const SITE_ELEVATION: f64 = 100.0;
+
+/// Apogee above the launch site, metres.
+const APOGEE_AGL: f64 = 1_000.0;
+
+/// Sea-level pressure of the simulated day, hPa.
+const QNH: f64 = 1013.25;
+
+/// Sea-level temperature of the simulated day, °C.
+const SEA_LEVEL_TEMP: f64 = 20.0;
+
+/// `baseline` hard-coded in `main.py`, hPa.
+const ONBOARD_BASELINE: f64 = 1014.0;
+
+/// Parachute descent rate, m/s.
+const DESCENT_RATE: f64 = 8.0;
+
+/// Horizontal wind drift during the flight, m/s.
+const WIND: f64 = 4.0;
+
+/// Launch pad position, decimal degrees.
+const SITE_LAT: f64 = 50.8503;
+const SITE_LON: f64 = 4.3517;
+
+/// Ground-station distance from the launch pad, metres (upwind, i.e. west).
+const STATION_OFFSET: f64 = 50.0;
+
+/// Phase durations, seconds.
+const PAD_TIME: f64 = 10.0;
+const ASCENT_TIME: f64 = 12.0;
+const LANDED_TIME: f64 = 20.0;
+
+/// RSSI at which the receiver stops hearing the CanSat, dBm.
+const SENSITIVITY: f64 = -118.0;
+
+/// Simulation parameters from the command line.
+#[derive(Clone, Copy, Debug)]
+pub struct SimConfig {
+    /// Packets per second.
+    pub rate_hz: f64,
+    /// Base probability (0..=1) that a packet is lost.
+    pub loss: f64,
+}
+
+/// Physical state of the simulated CanSat at one instant.
+struct FlightState {
+    /// Altitude above mean sea level, metres.
+    altitude: f64,
+    /// Horizontal distance travelled from the pad, metres.
+    drift: f64,
+}
+
+/// Flight profile for time `t` (seconds) within one cycle.
+fn flight_state(t: f64) -> FlightState {
+    let descent_time = APOGEE_AGL / DESCENT_RATE;
+    let t_apogee = PAD_TIME + ASCENT_TIME;
+    let t_landed = t_apogee + descent_time;
+
+    let agl = if t < PAD_TIME {
+        0.0
+    } else if t < t_apogee {
+        // decelerating climb: full speed at burnout, zero at apogee
+        let u = (t - PAD_TIME) / ASCENT_TIME;
+        APOGEE_AGL * (1.0 - (1.0 - u).powi(2))
+    } else if t < t_landed {
+        APOGEE_AGL - DESCENT_RATE * (t - t_apogee)
+    } else {
+        0.0
+    };
+    let airborne = (t.min(t_landed) - PAD_TIME).max(0.0);
+    FlightState {
+        altitude: SITE_ELEVATION + agl.max(0.0),
+        drift: WIND * airborne,
+    }
+}
+
+/// Length of one pad-to-landed-and-wait cycle, seconds.
+fn cycle_length() -> f64 {
+    PAD_TIME + ASCENT_TIME + APOGEE_AGL / DESCENT_RATE + LANDED_TIME
+}
+
+/// Simulated ground-station position (`STATION_OFFSET` west of the pad).
+pub fn station_position() -> (f64, f64) {
+    let lon = SITE_LON - STATION_OFFSET / (111_320.0 * SITE_LAT.to_radians().cos());
+    (SITE_LAT, lon)
+}
+
+/// Standard-atmosphere temperature (°C) at `h` metres.
+fn isa_temperature(h: f64) -> f64 {
+    SEA_LEVEL_TEMP - 0.0065 * h
+}
+
+/// Standard-atmosphere pressure (hPa) at `h` metres.
+fn isa_pressure(h: f64) -> f64 {
+    let t0 = SEA_LEVEL_TEMP + 273.15;
+    QNH * (1.0 - 0.0065 * h / t0).powf(5.255_88)
+}
+
+/// Relative humidity (%) drying out with altitude.
+fn humidity(h: f64) -> f64 {
+    (62.0 * (-(h - SITE_ELEVATION) / 3_000.0).exp()).clamp(0.0, 100.0)
+}
+
+/// Received signal strength (dBm) over `distance` metres at 433 MHz.
+fn path_loss_rssi(distance: f64) -> f64 {
+    // free-space path loss at 433 MHz, +20 dBm TX, ~40 dB of antenna/body losses
+    let fspl = 20.0 * distance.max(1.0).log10() + 20.0 * 433.0f64.log10() - 27.55;
+    20.0 - 40.0 - fspl
+}
+
+/// `$GPGGA` and `$GPRMC` sentences for a fix at `drift` metres east of the pad.
+fn gps_sentences(altitude: f64, drift: f64, sats: u32, hdop: f64) -> [String; 2] {
+    let lat = SITE_LAT;
+    let lon = SITE_LON + drift / (111_320.0 * SITE_LAT.to_radians().cos());
+    let now = Utc::now();
+    let time = format!(
+        "{:02}{:02}{:02}.{:02}",
+        now.hour(),
+        now.minute(),
+        now.second(),
+        now.timestamp_subsec_millis() / 10
+    );
+    let coord = |v: f64, width: usize, pos: char, neg: char| {
+        let a = v.abs();
+        let deg = a.trunc();
+        format!(
+            "{:0width$}{:07.4},{}",
+            deg as u32,
+            (a - deg) * 60.0,
+            if v < 0.0 { neg } else { pos }
+        )
+    };
+    let (lat, lon) = (coord(lat, 2, 'N', 'S'), coord(lon, 3, 'E', 'W'));
+    let gga = format!(
+        "GPGGA,{},{},{},1,{:02},{:.1},{:.1},M,46.9,M,,",
+        time, lat, lon, sats, hdop, altitude
+    );
+    let rmc = format!(
+        "GPRMC,{},A,{},{},{:.1},090.0,{},,",
+        time,
+        lat,
+        lon,
+        WIND * 1.943_84,
+        now.format("%d%m%y")
+    );
+    [gga, rmc].map(|body| format!("${}*{:02X}", body, checksum(&body)))
+}
+
+/// Standard normal sample (Box–Muller).
+fn gaussian(rng: &mut impl Rng) -> f64 {
+    let u1: f64 = rng.random::<f64>().max(f64::MIN_POSITIVE);
+    let u2: f64 = rng.random();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+}
+
+/// The simulated flight as a telemetry source.
+pub struct SimSource {
+    cfg: SimConfig,
+    rng: StdRng,
+    period: Duration,
+    /// When the next packet is due.
+    next_tick: Instant,
+    /// Lines of the current packet not handed out yet.
+    pending: VecDeque<String>,
+    counter: u64,
+    last_ack_rssi: Option<f64>,
+    /// Position in the flight cycle, seconds.
+    t: f64,
+    next_gps: f64,
+}
+
+impl SimSource {
+    pub fn new(cfg: SimConfig) -> Self {
+        Self {
+            cfg,
+            rng: StdRng::from_os_rng(),
+            period: Duration::from_secs_f64(1.0 / cfg.rate_hz),
+            next_tick: Instant::now(),
+            pending: VecDeque::new(),
+            counter: 1,
+            last_ack_rssi: None,
+            t: 0.0,
+            next_gps: 0.0,
+        }
+    }
+
+    /// Produce the lines of one packet period (none when the packet is lost).
+    fn step(&mut self) {
+        let rng = &mut self.rng;
+        let s = flight_state(self.t);
+        let height = s.altitude - SITE_ELEVATION;
+        let distance = (s.drift + STATION_OFFSET).hypot(height);
+
+        let temp = isa_temperature(s.altitude) + 0.05 * gaussian(rng);
+        let pres = isa_pressure(s.altitude) + 0.03 * gaussian(rng);
+        let hum = humidity(s.altitude) + 0.3 * gaussian(rng);
+        let alt = (ONBOARD_BASELINE - pres) * 8.3;
+        let rssi_packet = path_loss_rssi(distance) + 2.0 * gaussian(rng);
+
+        // more loss as the signal approaches the receiver's sensitivity
+        let margin = rssi_packet - SENSITIVITY;
+        let weak_loss = (1.0 - margin / 10.0).clamp(0.0, 1.0);
+        let lost = rng.random::<f64>() < self.cfg.loss.max(weak_loss);
+
+        if !lost {
+            self.pending.push_back(format!(
+                "Received:  {}  {:.1}  {:.2}  {:.2}  {:.2}  {:.2}",
+                self.counter,
+                self.last_ack_rssi.unwrap_or(0.0),
+                temp,
+                pres,
+                hum.clamp(0.0, 100.0),
+                alt
+            ));
+            self.pending
+                .push_back(format!("RSSI_PACKET: {:.1} dBm", rssi_packet));
+            self.pending
+                .push_back("ACK sent back automatically.".to_string());
+
+            if self.t >= self.next_gps || self.t < self.next_gps - 1.0 {
+                let sats = 8 + (rng.random::<f64>() * 3.0) as u32;
+                let hdop = 0.8 + 0.3 * rng.random::<f64>();
+                let gps_alt = s.altitude + 3.0 * gaussian(rng);
+                self.pending
+                    .extend(gps_sentences(gps_alt, s.drift, sats, hdop));
+                self.next_gps = self.t + 1.0;
+            }
+
+            // the ACK travels back over the same path; it can be lost too
+            if rng.random::<f64>() >= self.cfg.loss {
+                self.last_ack_rssi = Some(path_loss_rssi(distance) + 2.0 * gaussian(rng));
+            }
+        }
+
+        self.counter += 1;
+        self.t = (self.t + self.period.as_secs_f64()) % cycle_length();
+    }
+}
+
+impl TelemetrySource for SimSource {
+    fn open(&mut self) -> Result<(), String> {
+        self.next_tick = Instant::now();
+        Ok(())
+    }
+
+    fn next_frame(&mut self) -> Result<Frame, String> {
+        if let Some(line) = self.pending.pop_front() {
+            return Ok(Frame::Line(line));
+        }
+        let now = Instant::now();
+        if now < self.next_tick {
+            thread::sleep(self.next_tick - now);
+        }
+        self.next_tick += self.period;
+        self.step();
+        Ok(self.pending.pop_front().map_or(Frame::Idle, Frame::Line))
+    }
+
+    fn status(&self) -> String {
+        format!(
+            "simulation @ {} Hz, {:.1}% loss",
+            self.cfg.rate_hz,
+            self.cfg.loss * 100.0
+        )
+    }
+
+    fn close(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Commands are acknowledged like on hardware, but have no effect.
+    fn writer(&mut self) -> Result<Option<Box<dyn Write + Send>>, String> {
+        Ok(Some(Box::new(std::io::sink())))
+    }
+}
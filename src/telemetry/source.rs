@@ -0,0 +1,394 @@
+//! src/telemetry/source.rs
+//!
+//! Pluggable telemetry inputs.
+//!
+//! A `TelemetrySource` only knows how to reach its input and cut it into
+//! frames (serial port, network, replayed log file, stdin, simulator);
+//! decoding the lines is the pipeline's `Decoder`. Each source runs in its own
+//! reader thread (`start_source`) that owns the connection lifecycle,
+//! identical for every kind of input:
+//!
+//! ```text
+//! CONNECTING ──open()──▶ CONNECTED ──next_frame() error──▶ LOST ──open()──▶ ...
+//!      ▲ open() error: retry with exponential backoff          │
+//!      └───────────────────────────────────────────────────────┘
+//! CONNECTED ──Frame::End──▶ ENDED (never reopened)
+//! ```
+//!
+//! Several sources can run at once, each with its own link status, all
+//! feeding the same pipeline: every source updates the channels its lines
+//! carry (e.g. a second receiver sending only `GPS:` lines), or only the
+//! channels it is mapped to (`<spec>=<channel>,...`, see `SourceArg`).
+
+use std::io::Write;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+use super::link::{LinkState, SharedLink};
+use super::pipeline::Pipeline;
+use super::uplink::SharedUplink;
+use crate::events::{Level, SharedLog, log};
+
+/// First reopen delay; doubled after every failed attempt.
+const BACKOFF_MIN: Duration = Duration::from_millis(250);
+
+/// Upper bound for the reopen delay.
+const BACKOFF_MAX: Duration = Duration::from_secs(5);
+
+/// One unit of input.
+#[derive(Clone, Debug)]
+pub enum Frame {
+    /// A received line (without line ending), stamped with the receive time.
+    Line(String),
+    /// A line with the source's own timestamp, mission seconds (replays).
+    Timed(f64, String),
+    /// A non-telemetry message for the event log (replayed logger entries).
+    Message(Level, String),
+    /// The source rewound: start over from empty graphs.
+    Reset,
+    /// Nothing arrived within the source's poll interval.
+    Idle,
+    /// The input is exhausted; the source is closed and not reopened.
+    End,
+}
+
+/// An input of telemetry lines.
+pub trait TelemetrySource: Send {
+    /// Connect to the input. Called again after `next_frame` fails.
+    fn open(&mut self) -> Result<(), String>;
+
+    /// Wait for the next frame, returning `Frame::Idle` after a short poll
+    /// interval so the reader thread stays responsive. An error means the
+    /// connection is lost.
+    fn next_frame(&mut self) -> Result<Frame, String>;
+
+    /// What is being read right now, e.g. `/dev/ttyACM1 @ 115200` (shown in
+    /// the title bar; may change on reopen).
+    fn status(&self) -> String;
+
+    /// Release the input after an error or the end.
+    fn close(&mut self);
+
+    /// Writer for uplink frames while open, for inputs with a return path.
+    fn writer(&mut self) -> Result<Option<Box<dyn Write + Send>>, String> {
+        Ok(None)
+    }
+}
+
+/// Source selected with `--source`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SourceSpec {
+    /// `serial:<device>[@<baud>]` (`--baud` when the rate is omitted).
+    Serial { port: String, baud: Option<u32> },
+    /// `file:<path>`: replay an onboard log file.
+    File(String),
+    /// `tcp:<host>:<port>`: lines from a TCP server (reconnects).
+    Tcp(String),
+    /// `udp:[<host>:]<port>`: lines received as UDP datagrams (all
+    /// interfaces when only the port is given).
+    Udp(String),
+    /// `stdin` (or `-`): lines piped into the program.
+    Stdin,
+    /// `sim`: the simulated flight (`--sim-rate`, `--sim-loss`).
+    Sim,
+}
+
+impl FromStr for SourceSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (kind, arg) = s.split_once(':').unwrap_or((s, ""));
+        match (kind.to_ascii_lowercase().as_str(), arg) {
+            ("stdin" | "-", "") => Ok(SourceSpec::Stdin),
+            ("sim", "") => Ok(SourceSpec::Sim),
+            ("file", path) if !path.is_empty() => Ok(SourceSpec::File(path.to_string())),
+            ("tcp", addr) if is_host_port(addr) => Ok(SourceSpec::Tcp(addr.to_string())),
+            ("tcp", addr) => Err(format!(
+                "bad TCP address {:?} (expected <host>:<port>, IPv6 hosts in brackets)",
+                addr
+            )),
+            ("udp", addr) if is_host_port(addr) => Ok(SourceSpec::Udp(addr.to_string())),
+            ("udp", port) if port.parse::<u16>().is_ok() => {
+                Ok(SourceSpec::Udp(format!("0.0.0.0:{}", port)))
+            }
+            ("udp", addr) => Err(format!(
+                "bad UDP address {:?} (expected [<host>:]<port>, IPv6 hosts in brackets)",
+                addr
+            )),
+            ("serial", dev) if !dev.is_empty() => {
+                let (port, baud) = match dev.rsplit_once('@') {
+                    Some((port, baud)) => {
+                        let baud = baud
+                            .parse::<u32>()
+                            .ok()
+                            .filter(|b| *b > 0)
+                            .ok_or_else(|| format!("bad baud rate {:?}", baud))?;
+                        (port, Some(baud))
+                    }
+                    None => (dev, None),
+                };
+                Ok(SourceSpec::Serial {
+                    port: port.to_string(),
+                    baud,
+                })
+            }
+            _ => Err(format!(
+                "unknown source {:?} (expected serial:<device>[@<baud>], tcp:<host>:<port>, udp:[<host>:]<port>, file:<path>, stdin or sim)",
+                s
+            )),
+        }
+    }
+}
+
+/// Whether `addr` is `<host>:<port>`, an IPv6 host written in brackets
+/// (`[::1]:5000`) as `ToSocketAddrs` expects.
+fn is_host_port(addr: &str) -> bool {
+    addr.rsplit_once(':').is_some_and(|(host, port)| {
+        !host.is_empty()
+            && port.parse::<u16>().is_ok()
+            && (!host.contains(':') || (host.starts_with('[') && host.ends_with(']')))
+    })
+}
+
+/// One `--source` argument: an input, and optionally the only channels it
+/// feeds (`<spec>=<channel>,<channel>...`, by schema name).
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceArg {
+    pub spec: SourceSpec,
+    /// Empty: every channel its lines carry.
+    pub channels: Vec<String>,
+}
+
+impl From<SourceSpec> for SourceArg {
+    fn from(spec: SourceSpec) -> Self {
+        Self {
+            spec,
+            channels: Vec::new(),
+        }
+    }
+}
+
+impl FromStr for SourceArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((spec, channels)) = s.rsplit_once('=') else {
+            return s.parse::<SourceSpec>().map(Self::from);
+        };
+        let channels: Vec<String> = channels.split(',').map(|c| c.trim().to_string()).collect();
+        if channels.iter().any(String::is_empty) {
+            return Err(format!(
+                "bad channel list in {:?} (expected <spec>=<channel>,<channel>...)",
+                s
+            ));
+        }
+        Ok(Self {
+            spec: spec.parse()?,
+            channels,
+        })
+    }
+}
+
+/// Spawn the reader thread of `source`: open it, feed its frames to
+/// `pipeline`, and reopen it with backoff when it fails.
+///
+/// While open, a source with a return path carries the uplink commands unless
+/// another source already does.
+pub fn start_source(
+    mut source: Box<dyn TelemetrySource>,
+    pipeline: Pipeline,
+    link: SharedLink,
+    uplink: SharedUplink,
+    events: SharedLog,
+) {
+    thread::spawn(move || {
+        let mut backoff = BACKOFF_MIN;
+        let mut connected_once = false;
+        let mut last_reported: Option<String> = None;
+
+        loop {
+            if let Err(err) = source.open() {
+                // only log when the failure changes, the UI shows the rest
+                if last_reported.as_deref() != Some(err.as_str()) {
+                    log(&events, Level::Warn, err.clone());
+                    last_reported = Some(err.clone());
+                }
+                if let Ok(mut l) = link.write() {
+                    l.last_error = Some(err);
+                    if connected_once {
+                        l.set_state(LinkState::Lost);
+                    }
+                }
+                thread::sleep(backoff);
+                backoff = (backoff * 2).min(BACKOFF_MAX);
+                continue;
+            }
+
+            let name = source.status();
+            if let Ok(mut l) = link.write() {
+                if connected_once {
+                    l.reconnects += 1;
+                }
+                l.source = name.clone();
+                l.set_state(LinkState::Connected);
+            }
+            log(&events, Level::Info, format!("connected: {}", name));
+            connected_once = true;
+            backoff = BACKOFF_MIN;
+            last_reported = None;
+
+            // token of the uplink writer this source attached, if any
+            let uplink_token = match source.writer() {
+                Ok(Some(w)) => {
+                    let mut up = uplink.write().unwrap();
+                    (!up.connected()).then(|| up.attach(w))
+                }
+                Ok(None) => None,
+                Err(e) => {
+                    log(
+                        &events,
+                        Level::Warn,
+                        format!("uplink unavailable on {}: {}", name, e),
+                    );
+                    None
+                }
+            };
+
+            let err = loop {
+                match source.next_frame() {
+                    Ok(Frame::Line(line)) => pipeline.ingest(&line),
+                    Ok(Frame::Timed(t, line)) => pipeline.ingest_at(&line, t),
+                    Ok(Frame::Message(level, text)) => log(&events, level, text),
+                    Ok(Frame::Reset) => pipeline.reset(),
+                    Ok(Frame::Idle) => {}
+                    Ok(Frame::End) => break None,
+                    Err(e) => break Some(e),
+                }
+            };
+            if let Some(token) = uplink_token {
+                uplink.write().unwrap().detach(token);
+            }
+            source.close();
+
+            let Some(err) = err else {
+                log(&events, Level::Info, format!("input ended: {}", name));
+                if let Ok(mut l) = link.write() {
+                    l.set_state(LinkState::Ended);
+                }
+                return;
+            };
+            log(&events, Level::Error, format!("lost {}: {}", name, err));
+            if let Ok(mut l) = link.write() {
+                l.last_error = Some(err);
+                l.set_state(LinkState::Lost);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(s: &str) -> Result<SourceSpec, String> {
+        s.parse()
+    }
+
+    #[test]
+    fn simple_sources() {
+        assert_eq!(spec("stdin"), Ok(SourceSpec::Stdin));
+        assert_eq!(spec("-"), Ok(SourceSpec::Stdin));
+        assert_eq!(spec(" SIM "), Ok(SourceSpec::Sim));
+        assert_eq!(
+            spec("file:logs/flight 2.txt"),
+            Ok(SourceSpec::File("logs/flight 2.txt".into()))
+        );
+        assert!(spec("file:").is_err());
+        assert!(spec("sim:fast").is_err());
+        assert!(spec("carrier-pigeon").is_err());
+    }
+
+    #[test]
+    fn serial_with_and_without_baud() {
+        assert_eq!(
+            spec("serial:/dev/ttyACM0"),
+            Ok(SourceSpec::Serial {
+                port: "/dev/ttyACM0".into(),
+                baud: None
+            })
+        );
+        assert_eq!(
+            spec("serial:COM3@9600"),
+            Ok(SourceSpec::Serial {
+                port: "COM3".into(),
+                baud: Some(9600)
+            })
+        );
+        assert!(spec("serial:/dev/ttyUSB0@0").is_err());
+        assert!(spec("serial:/dev/ttyUSB0@fast").is_err());
+        assert!(spec("serial:").is_err());
+    }
+
+    #[test]
+    fn tcp_addresses() {
+        assert_eq!(
+            spec("tcp:pi.local:5000"),
+            Ok(SourceSpec::Tcp("pi.local:5000".into()))
+        );
+        assert_eq!(
+            spec("tcp:192.168.1.20:5000"),
+            Ok(SourceSpec::Tcp("192.168.1.20:5000".into()))
+        );
+        assert_eq!(
+            spec("tcp:[::1]:5000"),
+            Ok(SourceSpec::Tcp("[::1]:5000".into()))
+        );
+        assert!(spec("tcp:::1:5000").is_err());
+        assert!(spec("tcp:pi.local").is_err());
+        assert!(spec("tcp::5000").is_err());
+        assert!(spec("tcp:pi.local:70000").is_err());
+    }
+
+    #[test]
+    fn udp_addresses() {
+        assert_eq!(spec("udp:5000"), Ok(SourceSpec::Udp("0.0.0.0:5000".into())));
+        assert_eq!(
+            spec("udp:127.0.0.1:5000"),
+            Ok(SourceSpec::Udp("127.0.0.1:5000".into()))
+        );
+        assert_eq!(
+            spec("udp:[::]:5000"),
+            Ok(SourceSpec::Udp("[::]:5000".into()))
+        );
+        assert!(spec("udp:fe80::1:5000").is_err());
+        assert!(spec("udp:").is_err());
+        assert!(spec("udp:port").is_err());
+    }
+
+    #[test]
+    fn channel_mapping() {
+        assert_eq!(
+            "serial:/dev/ttyUSB0=GPS LAT, GPS LON".parse(),
+            Ok(SourceArg {
+                spec: SourceSpec::Serial {
+                    port: "/dev/ttyUSB0".into(),
+                    baud: None
+                },
+                channels: vec!["GPS LAT".into(), "GPS LON".into()],
+            })
+        );
+        assert_eq!("sim".parse(), Ok(SourceArg::from(SourceSpec::Sim)));
+        assert!("sim=".parse::<SourceArg>().is_err());
+        assert!("sim=TEMP,,HUMIDITY".parse::<SourceArg>().is_err());
+        assert!("serial:=TEMP".parse::<SourceArg>().is_err());
+        assert!("tcp:pi=TEMP".parse::<SourceArg>().is_err());
+        assert_eq!(
+            "tcp:[::1]:5000=GPS LAT"
+                .parse::<SourceArg>()
+                .map(|a| a.spec),
+            Ok(SourceSpec::Tcp("[::1]:5000".into()))
+        );
+    }
+}
@@ -0,0 +1,57 @@
+//! src/telemetry/stdin.rs
+//!
+//! Lines piped into the program (`--source stdin`), e.g. from `nc` or a
+//! capture file: `cat capture.txt | test_tui_more_competent --source stdin`.
+//! The end of the input ends the source. The UI reads the keyboard from the
+//! controlling terminal, not from stdin.
+
+use std::io::Stdin;
+
+use super::source::{Frame, TelemetrySource};
+
+/// Standard input as a telemetry source.
+#[derive(Default)]
+pub struct StdinSource {
+    stdin: Option<Stdin>,
+    line: String,
+}
+
+impl StdinSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl TelemetrySource for StdinSource {
+    fn open(&mut self) -> Result<(), String> {
+        self.stdin = Some(std::io::stdin());
+        Ok(())
+    }
+
+    fn next_frame(&mut self) -> Result<Frame, String> {
+        let Some(stdin) = &self.stdin else {
+            return Err("not open".into());
+        };
+        self.line.clear();
+        match stdin.read_line(&mut self.line) {
+            Ok(0) => Ok(Frame::End),
+            Ok(_) => {
+                let line = self.line.trim();
+                Ok(if line.is_empty() {
+                    Frame::Idle
+                } else {
+                    Frame::Line(line.to_string())
+                })
+            }
+            Err(e) => Err(format!("read error: {}", e)),
+        }
+    }
+
+    fn status(&self) -> String {
+        "stdin".to_string()
+    }
+
+    fn close(&mut self) {
+        self.stdin = None;
+    }
+}
@@ -0,0 +1,136 @@
+//! src/telemetry/track.rs
+//!
+//! Ground track of the CanSat, assembled from the channels with
+//! `role = "latitude"` and `role = "longitude"`.
+//!
+//! A position is recorded whenever one decoded line carries both coordinates
+//! (an NMEA fix, or a `Received:` line with GPS fields). The first position of
+//! the session is taken as the launch point. Distances and bearings use a
+//! spherical Earth, which is far more precise than a consumer GPS fix at the
+//! ranges a CanSat flies.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+use crate::schema::{ChannelRole, Schema};
+
+/// Mean Earth radius, metres.
+const EARTH_RADIUS: f64 = 6_371_000.0;
+
+/// Positions kept for the trail.
+const CAPACITY: usize = 5_000;
+
+/// One fix: mission time (seconds) and decimal-degree coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrackPoint {
+    pub t: f64,
+    pub lat: f64,
+    pub lon: f64,
+}
+
+/// Track state, fed by the pipeline.
+#[derive(Debug)]
+pub struct Track {
+    lat_channel: Option<usize>,
+    lon_channel: Option<usize>,
+    /// Fixes in arrival order (bounded).
+    pub points: VecDeque<TrackPoint>,
+    /// First fix of the session.
+    pub launch: Option<(f64, f64)>,
+    /// Ground-station position (`--station`), if known.
+    pub station: Option<(f64, f64)>,
+    /// Wall-clock arrival of the latest fix.
+    pub last_fix: Option<Instant>,
+}
+
+impl Track {
+    /// Pick the coordinate channels from the schema roles.
+    pub fn new(schema: &Schema, station: Option<(f64, f64)>) -> Self {
+        Self {
+            lat_channel: schema.with_role(ChannelRole::Latitude).next(),
+            lon_channel: schema.with_role(ChannelRole::Longitude).next(),
+            points: VecDeque::new(),
+            launch: None,
+            station,
+            last_fix: None,
+        }
+    }
+
+    /// Whether the schema declares both coordinate channels.
+    pub fn enabled(&self) -> bool {
+        self.lat_channel.is_some() && self.lon_channel.is_some()
+    }
+
+    /// Drop the trail and launch point (used when a replay rewinds).
+    pub fn reset(&mut self) {
+        self.points.clear();
+        self.launch = None;
+        self.last_fix = None;
+    }
+
+    /// Feed the samples decoded from one line received at mission time `t`.
+    pub fn observe(&mut self, t: f64, samples: &[(usize, f64)]) {
+        let find = |ch: Option<usize>| {
+            samples
+                .iter()
+                .find(|(idx, _)| Some(*idx) == ch)
+                .map(|&(_, v)| v)
+        };
+        let (Some(lat), Some(lon)) = (find(self.lat_channel), find(self.lon_channel)) else {
+            return;
+        };
+        // a receiver without fix may report zeros
+        if !(-90.0..=90.0).contains(&lat)
+            || !(-180.0..=180.0).contains(&lon)
+            || lat == 0.0 && lon == 0.0
+        {
+            return;
+        }
+        if self.points.len() == CAPACITY {
+            self.points.pop_front();
+        }
+        self.points.push_back(TrackPoint { t, lat, lon });
+        self.launch.get_or_insert((lat, lon));
+        self.last_fix = Some(Instant::now());
+    }
+
+    /// Most recent fix.
+    pub fn current(&self) -> Option<TrackPoint> {
+        self.points.back().copied()
+    }
+}
+
+/// Great-circle distance in metres between two `(lat, lon)` positions.
+pub fn distance_m(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (lat1, lat2) = (a.0.to_radians(), b.0.to_radians());
+    let dlat = lat2 - lat1;
+    let dlon = (b.1 - a.1).to_radians();
+    let h = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS * h.sqrt().asin()
+}
+
+/// Initial bearing in degrees (0 = north, clockwise) from `a` to `b`.
+pub fn bearing_deg(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (lat1, lat2) = (a.0.to_radians(), b.0.to_radians());
+    let dlon = (b.1 - a.1).to_radians();
+    let y = dlon.sin() * lat2.cos();
+    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos();
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+/// Eight-point compass name for a bearing.
+pub fn compass(bearing: f64) -> &'static str {
+    const NAMES: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+    NAMES[((bearing.rem_euclid(360.0) + 22.5) / 45.0) as usize % 8]
+}
+
+/// Local east/north offset in metres of `p` from `origin` (equirectangular).
+pub fn local_xy(origin: (f64, f64), p: (f64, f64)) -> (f64, f64) {
+    let x = (p.1 - origin.1).to_radians() * origin.0.to_radians().cos() * EARTH_RADIUS;
+    let y = (p.0 - origin.0).to_radians() * EARTH_RADIUS;
+    (x, y)
+}
+
+/// Alias: Arc<RwLock<Track>>
+pub type SharedTrack = Arc<RwLock<Track>>;
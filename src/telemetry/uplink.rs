@@ -0,0 +1,446 @@
+//! src/telemetry/uplink.rs
+//!
+//! Uplink commands from the ground station to the CanSat.
+//!
+//! Commands are written to the receiver as NMEA-style frames,
+//! `$CMD,<id>,<text>*<checksum>\r\n` (XOR checksum as in NMEA), and the
+//! receiver relays the pending frame with its next automatic ACK. Only one
+//! command is in flight at a time; the rest wait in the queue. A command is
+//!
+//! ```text
+//! QUEUED ──(written)──▶ SENT ──(receiver's next "ACK ..." line)──▶ CARRIED
+//! CARRIED ──(next packet counter)──▶ CONFIRMED #<packet>
+//! SENT ──(no ACK within SEND_TIMEOUT)──▶ NO ACK
+//! ```
+//!
+//! so the operator sees which packet was the first one sent after the
+//! CanSat got the command.
+
+use std::collections::VecDeque;
+use std::io::Write;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use super::nmea::checksum;
+use crate::schema::{ChannelRole, Schema};
+
+/// Longest accepted command text, bytes.
+pub const MAX_COMMAND: usize = 64;
+
+/// How long a sent command may wait for the receiver's ACK.
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Prefix of the line the receiver prints after sending an ACK.
+const ACK_PREFIX: &str = "ACK";
+
+/// Commands kept (queued and past).
+const CAPACITY: usize = 100;
+
+/// Progress of one command.
+#[derive(Clone, Debug, PartialEq)]
+pub enum UplinkState {
+    Queued,
+    Sent,
+    /// Relayed to the CanSat with an ACK.
+    Carried,
+    /// First packet received after the ACK that carried it.
+    Confirmed(u64),
+    /// No ACK within `SEND_TIMEOUT`.
+    NoAck,
+    /// Writing to the receiver failed.
+    Failed(String),
+}
+
+impl UplinkState {
+    /// Machine-readable name (remote `uplink` command).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UplinkState::Queued => "queued",
+            UplinkState::Sent => "sent",
+            UplinkState::Carried => "carried",
+            UplinkState::Confirmed(_) => "confirmed",
+            UplinkState::NoAck => "no_ack",
+            UplinkState::Failed(_) => "failed",
+        }
+    }
+
+    /// Display text, with the confirming packet or the error.
+    pub fn label(&self) -> String {
+        match self {
+            UplinkState::Queued => "QUEUED".into(),
+            UplinkState::Sent => "SENT".into(),
+            UplinkState::Carried => "CARRIED".into(),
+            UplinkState::Confirmed(n) => format!("CONFIRMED #{}", n),
+            UplinkState::NoAck => "NO ACK".into(),
+            UplinkState::Failed(e) => format!("FAILED {}", e),
+        }
+    }
+
+    /// Whether the command is still waiting for something.
+    pub fn pending(&self) -> bool {
+        matches!(
+            self,
+            UplinkState::Queued | UplinkState::Sent | UplinkState::Carried
+        )
+    }
+}
+
+/// One command and what happened to it.
+#[derive(Clone, Debug)]
+pub struct UplinkCommand {
+    pub id: u64,
+    pub text: String,
+    /// Exact frame written (without the line ending).
+    pub frame: String,
+    pub state: UplinkState,
+    /// When it was written to the receiver.
+    pub sent_at: Option<Instant>,
+}
+
+/// Uplink state, written by the UI / remote commands and the pipeline.
+pub struct Uplink {
+    counter_channel: Option<usize>,
+    /// Where frames go (the serial port) and its `attach` token; `None`
+    /// while disconnected.
+    writer: Option<(u64, Mutex<Box<dyn Write + Send>>)>,
+    next_token: u64,
+    /// Every command, oldest first (bounded).
+    pub commands: VecDeque<UplinkCommand>,
+    next_id: u64,
+}
+
+impl Uplink {
+    /// Pick the packet counter from the schema roles.
+    pub fn new(schema: &Schema) -> Self {
+        Self {
+            counter_channel: schema.with_role(ChannelRole::Counter).next(),
+            writer: None,
+            next_token: 1,
+            commands: VecDeque::new(),
+            next_id: 1,
+        }
+    }
+
+    /// Whether frames can be written right now.
+    pub fn connected(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    /// Start writing to `writer` (the receiver came up) and send what is
+    /// queued. Returns the token to `detach` it with.
+    pub fn attach(&mut self, writer: Box<dyn Write + Send>) -> u64 {
+        let token = self.next_token;
+        self.next_token += 1;
+        self.writer = Some((token, Mutex::new(writer)));
+        self.pump();
+        token
+    }
+
+    /// Stop writing to the writer attached with `token` (the receiver went
+    /// away); queued commands wait. A writer attached since, after this one
+    /// failed, stays.
+    pub fn detach(&mut self, token: u64) {
+        if self.writer.as_ref().is_some_and(|(t, _)| *t == token) {
+            self.writer = None;
+        }
+    }
+
+    /// Queue `text` for the CanSat; returns the command id.
+    pub fn send(&mut self, text: &str) -> Result<u64, String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err("empty command".into());
+        }
+        if text.len() > MAX_COMMAND {
+            return Err(format!("command longer than {} bytes", MAX_COMMAND));
+        }
+        if let Some(c) = text
+            .chars()
+            .find(|c| !c.is_ascii() || c.is_ascii_control() || matches!(c, '$' | '*'))
+        {
+            return Err(format!("character {:?} not allowed in a command", c));
+        }
+        // make room by forgetting the oldest finished command, never a pending one
+        if self.commands.len() >= CAPACITY {
+            let Some(i) = self.commands.iter().position(|c| !c.state.pending()) else {
+                return Err("uplink queue full".into());
+            };
+            self.commands.remove(i);
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        let body = format!("CMD,{},{}", id, text);
+        let frame = format!("${}*{:02X}", body, checksum(&body));
+        self.commands.push_back(UplinkCommand {
+            id,
+            text: text.to_string(),
+            frame,
+            state: UplinkState::Queued,
+            sent_at: None,
+        });
+        self.pump();
+        Ok(id)
+    }
+
+    /// Number of commands waiting to be written.
+    pub fn queued(&self) -> usize {
+        self.commands
+            .iter()
+            .filter(|c| c.state == UplinkState::Queued)
+            .count()
+    }
+
+    /// Expire an unanswered command and write the next queued one when
+    /// nothing is waiting for an ACK.
+    fn pump(&mut self) {
+        for c in &mut self.commands {
+            if c.state == UplinkState::Sent && c.sent_at.is_some_and(|t| t.elapsed() > SEND_TIMEOUT)
+            {
+                c.state = UplinkState::NoAck;
+            }
+        }
+        if self.commands.iter().any(|c| c.state == UplinkState::Sent) {
+            return;
+        }
+        let Some((_, w)) = &self.writer else {
+            return;
+        };
+        let Some(c) = self
+            .commands
+            .iter_mut()
+            .find(|c| c.state == UplinkState::Queued)
+        else {
+            return;
+        };
+        let written = w.lock().map_err(|e| e.to_string()).and_then(|mut w| {
+            w.write_all(format!("{}\r\n", c.frame).as_bytes())
+                .and_then(|_| w.flush())
+                .map_err(|e| e.to_string())
+        });
+        match written {
+            Ok(()) => {
+                c.state = UplinkState::Sent;
+                c.sent_at = Some(Instant::now());
+            }
+            Err(e) => {
+                c.state = UplinkState::Failed(e);
+                self.writer = None;
+            }
+        }
+    }
+
+    /// Feed one received line and its samples: an ACK line carries the sent
+    /// command, the next packet counter confirms the carried ones.
+    pub fn observe(&mut self, line: &str, samples: &[(usize, f64)]) {
+        if let Some(n) = samples
+            .iter()
+            .find(|(idx, _)| Some(*idx) == self.counter_channel)
+            .map(|&(_, n)| n)
+            .filter(|n| n.is_finite() && *n >= 0.0)
+        {
+            for c in &mut self.commands {
+                if c.state == UplinkState::Carried {
+                    c.state = UplinkState::Confirmed(n as u64);
+                }
+            }
+        }
+        if line.starts_with(ACK_PREFIX) {
+            for c in &mut self.commands {
+                if c.state == UplinkState::Sent {
+                    c.state = UplinkState::Carried;
+                }
+            }
+        }
+        self.pump();
+    }
+}
+
+/// Alias: Arc<RwLock<Uplink>>
+pub type SharedUplink = Arc<RwLock<Uplink>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Receiver stand-in that keeps everything written to it.
+    #[derive(Clone, Default)]
+    struct Wire(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Wire {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Wire {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    /// Receiver whose port went away.
+    struct Broken;
+
+    impl Write for Broken {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::ErrorKind::BrokenPipe.into())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Packet counter on channel 0, attached to a fresh `Wire`.
+    fn uplink() -> (Uplink, Wire) {
+        let mut u = Uplink {
+            counter_channel: Some(0),
+            writer: None,
+            next_token: 1,
+            commands: VecDeque::new(),
+            next_id: 1,
+        };
+        let wire = Wire::default();
+        u.attach(Box::new(wire.clone()));
+        (u, wire)
+    }
+
+    fn state(u: &Uplink, id: u64) -> UplinkState {
+        u.commands
+            .iter()
+            .find(|c| c.id == id)
+            .unwrap()
+            .state
+            .clone()
+    }
+
+    #[test]
+    fn command_walks_from_queued_to_confirmed() {
+        let (mut u, wire) = uplink();
+        let id = u.send(" DEPLOY ").unwrap();
+        let frame = u.commands[0].frame.clone();
+        assert_eq!(
+            frame,
+            format!("$CMD,1,DEPLOY*{:02X}", checksum("CMD,1,DEPLOY"))
+        );
+        assert_eq!(wire.text(), format!("{}\r\n", frame));
+        assert_eq!(state(&u, id), UplinkState::Sent);
+
+        // a packet before the ACK confirms nothing
+        u.observe("Received: 41", &[(0, 41.0)]);
+        assert_eq!(state(&u, id), UplinkState::Sent);
+        u.observe("ACK 41", &[]);
+        assert_eq!(state(&u, id), UplinkState::Carried);
+        u.observe("Received: 42", &[(0, 42.0)]);
+        assert_eq!(state(&u, id), UplinkState::Confirmed(42));
+        assert_eq!(state(&u, id).label(), "CONFIRMED #42");
+    }
+
+    #[test]
+    fn one_command_in_flight_at_a_time() {
+        let (mut u, wire) = uplink();
+        let first = u.send("A").unwrap();
+        let second = u.send("B").unwrap();
+        assert_eq!((state(&u, second), u.queued()), (UplinkState::Queued, 1));
+        assert_eq!(wire.text().lines().count(), 1);
+
+        // the ACK frees the receiver for the next frame
+        u.observe("ACK", &[]);
+        assert_eq!(state(&u, first), UplinkState::Carried);
+        assert_eq!(state(&u, second), UplinkState::Sent);
+        assert_eq!(wire.text().lines().count(), 2);
+    }
+
+    #[test]
+    fn unanswered_command_times_out() {
+        let (mut u, _) = uplink();
+        let first = u.send("A").unwrap();
+        let second = u.send("B").unwrap();
+        u.commands[0].sent_at = Instant::now().checked_sub(SEND_TIMEOUT + Duration::from_secs(1));
+        u.observe("Received: 7", &[(0, 7.0)]);
+        assert_eq!(state(&u, first), UplinkState::NoAck);
+        assert_eq!(state(&u, second), UplinkState::Sent);
+        assert!(!state(&u, first).pending());
+    }
+
+    #[test]
+    fn commands_wait_while_detached() {
+        let (mut u, _) = uplink();
+        u.detach(1);
+        let id = u.send("A").unwrap();
+        assert_eq!(state(&u, id), UplinkState::Queued);
+
+        let wire = Wire::default();
+        u.attach(Box::new(wire.clone()));
+        assert_eq!(state(&u, id), UplinkState::Sent);
+        assert!(wire.text().starts_with("$CMD,1,A*"));
+    }
+
+    #[test]
+    fn write_errors_fail_the_command_and_detach() {
+        let (mut u, _) = uplink();
+        u.attach(Box::new(Broken));
+        let id = u.send("A").unwrap();
+        assert!(matches!(state(&u, id), UplinkState::Failed(_)));
+        assert!(!u.connected());
+        let next = u.send("B").unwrap();
+        assert_eq!(state(&u, next), UplinkState::Queued);
+    }
+
+    #[test]
+    fn detach_only_drops_its_own_writer() {
+        let (mut u, _) = uplink();
+        let broken = u.attach(Box::new(Broken));
+        u.send("A").unwrap();
+        assert!(!u.connected());
+
+        // a second source takes over after the failure
+        let wire = Wire::default();
+        let second = u.attach(Box::new(wire.clone()));
+        u.detach(broken);
+        assert!(u.connected());
+        u.send("B").unwrap();
+        assert!(wire.text().starts_with("$CMD,2,B*"));
+        u.detach(second);
+        assert!(!u.connected());
+    }
+
+    #[test]
+    fn bad_commands_are_rejected() {
+        let (mut u, _) = uplink();
+        assert!(u.send("  ").unwrap_err().contains("empty"));
+        assert!(
+            u.send(&"X".repeat(MAX_COMMAND + 1))
+                .unwrap_err()
+                .contains("longer")
+        );
+        assert!(u.send(&"X".repeat(MAX_COMMAND)).is_ok());
+        for bad in ["A$B", "A*B", "A\tB", "ÄB"] {
+            assert!(
+                u.send(bad).unwrap_err().contains("not allowed"),
+                "{:?}",
+                bad
+            );
+        }
+        assert_eq!(u.commands.len(), 1);
+    }
+
+    #[test]
+    fn full_queue_only_drops_finished_commands() {
+        let (mut u, _) = uplink();
+        u.detach(1);
+        for i in 0..CAPACITY {
+            u.send(&format!("C{}", i)).unwrap();
+        }
+        assert!(u.send("X").unwrap_err().contains("queue full"));
+
+        u.commands[5].state = UplinkState::NoAck;
+        let id = u.send("X").unwrap();
+        assert_eq!(u.commands.len(), CAPACITY);
+        assert!(u.commands.iter().all(|c| c.id != 6));
+        assert_eq!(u.commands.back().unwrap().id, id);
+    }
+}